@@ -1,19 +1,27 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![cfg_attr(debug_assertions, feature(trace_macros, log_syntax))]
 
+#[cfg(windows)]
 use ::clap::Parser;
+#[cfg(windows)]
 use ::futures::{FutureExt, executor::LocalPool, task::LocalSpawnExt};
+#[cfg(windows)]
 use ::nwg::{self as nwg, GridLayout, Icon, Monitor, NativeUi, Window};
+#[cfg(windows)]
 use ::nwd::NwgUi;
-use ::nwg_webview_ctrl::{WebviewContainer, WebviewContainerFlags};
+#[cfg(windows)]
+use ::nwg_webview_ctrl::{WebviewContainer, WebviewContainerFlags, WebviewHandles};
+#[cfg(windows)]
 use ::std::error::Error;
 
+#[cfg(windows)]
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct CliParams {
     #[arg(short, long, value_name = "URL")]
     url: String
 }
+#[cfg(windows)]
 #[derive(Default, NwgUi)]
 pub struct DemoUi {
     #[nwg_resource(
@@ -48,6 +56,7 @@ pub struct DemoUi {
     #[nwg_layout_item(layout: grid, row: 0, col: 0)]
     webview_container: WebviewContainer,
 }
+#[cfg(windows)]
 impl DemoUi {
     /// 主窗体大小
     const SIZE:(i32, i32) = (1024, 168);
@@ -60,7 +69,7 @@ impl DemoUi {
         let executor = LocalPool::new();
         let webview_ready_fut = self.webview_container.ready_fut()?;
         executor.spawner().spawn_local(async move {
-            let (_, _, webview) = webview_ready_fut.await;
+            let WebviewHandles { webview, .. } = webview_ready_fut.await?;
             webview.navigate(&cli_params.url)?;
             Ok::<_, Box<dyn Error>>(())
         }.map(|result| {
@@ -71,6 +80,11 @@ impl DemoUi {
         Ok(executor)
     }
 }
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("该例程仅能运行于 Windows");
+}
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     let cli_params = CliParams::parse();
     nwg::init()?;
@@ -1,11 +1,21 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![cfg_attr(debug_assertions, feature(trace_macros, log_syntax))]
 
+#[cfg(windows)]
 use ::clap::Parser;
+#[cfg(windows)]
 use ::futures::{FutureExt, executor::LocalPool, task::LocalSpawnExt};
+#[cfg(windows)]
 use ::nwg::{self as nwg, Event as NwgEvent, GridLayout, Window};
+#[cfg(windows)]
 use ::nwg_webview_ctrl::{WebviewContainer, WebviewContainerFlags};
+#[cfg(windows)]
 use ::std::error::Error;
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("该例程仅能运行于 Windows");
+}
+#[cfg(windows)]
 fn main() -> Result<(), Box<dyn Error>> {
     #[derive(Parser)]
     #[command(author, version, about, long_about = None)]
@@ -32,10 +42,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     // 业务处理逻辑
     let mut executor = {
         let executor = LocalPool::new();
-        let webview_ready_fut = webview_container.ready_fut()?;
+        // 在 webview 控件就绪之后导航，并等待导航结束
+        let navigation = webview_container.navigate(cli_params.url);
         executor.spawner().spawn_local(async move {
-            let (_, _, webview) = webview_ready_fut.await;
-            webview.navigate(&cli_params.url)?;
+            navigation.await?;
             Ok::<_, Box<dyn Error>>(())
         }.map(|result| {
             if let Err(err) = result {
@@ -0,0 +1,482 @@
+//! 经由虚拟主机（比如，`https://app.local/*`）向网页提供内嵌于程序的静态资源。
+//!
+//! 资源的来源由[`AssetProvider`]抽象：磁盘目录[`DirectoryAssets`]、内嵌映射表[`EmbeddedAssets`]与`zip`压缩包`ZipAssets`（需开启`zip`特性）。
+use ::std::{borrow::Cow, collections::HashMap, fmt::{self, Debug, Formatter}, fs, io, path::PathBuf};
+#[cfg(feature = "zip")]
+use ::std::{cell::RefCell, io::{Cursor, Read}, path::Path};
+#[cfg(feature = "zip")]
+use ::zip::{ZipArchive, result::ZipError};
+
+/// 被拦截的网络请求
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssetRequest {
+    /// 完整的请求地址
+    pub uri: String,
+    /// 请求方法
+    pub method: String,
+    /// 请求头`Range`
+    pub range: Option<String>,
+    /// 请求头`If-None-Match`
+    pub if_none_match: Option<String>
+}
+/// 对被拦截请求的响应
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetResponse {
+    /// 状态码
+    pub status: u16,
+    /// 状态描述
+    pub reason: &'static str,
+    /// 响应头
+    pub headers: Vec<(String, String)>,
+    /// 响应体
+    pub body: Vec<u8>
+}
+impl AssetResponse {
+    fn new(status: u16, reason: &'static str) -> Self {
+        Self { status, reason, headers: Vec::new(), body: Vec::new() }
+    }
+    fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+    /// `HEAD`请求仅响应`Content-Length`，而不响应内容
+    fn body(mut self, body: Vec<u8>, is_head: bool) -> Self {
+        self = self.header("Content-Length", body.len().to_string());
+        if !is_head {
+            self.body = body;
+        }
+        self
+    }
+    /// 读取响应头
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+    /// 按`HTTP`格式拼接全部响应头。即，`Name: value\r\n`
+    pub fn headers_string(&self) -> String {
+        self.headers.iter().map(|(name, value)| format!("{name}: {value}\r\n")).collect()
+    }
+}
+/// 静态资源的来源
+pub trait AssetProvider: 'static {
+    /// 读取被规范化之后的相对路径`path`（比如，`css/main.css`）。资源不存在时返回`Ok(None)`。
+    fn load(&self, path: &str) -> io::Result<Option<Cow<'static, [u8]>>>;
+}
+/// 磁盘目录
+#[derive(Clone, Debug)]
+pub struct DirectoryAssets {
+    root: PathBuf
+}
+impl DirectoryAssets {
+    /// 以`root`为根目录
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+impl AssetProvider for DirectoryAssets {
+    fn load(&self, path: &str) -> io::Result<Option<Cow<'static, [u8]>>> {
+        let path = path.split('/').fold(self.root.clone(), |path, segment| path.join(segment));
+        if !path.is_file() {
+            return Ok(None);
+        }
+        match fs::read(path) {
+            Ok(content) => Ok(Some(Cow::Owned(content))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+}
+/// 内嵌于程序的资源映射表。比如，
+/// ```ignore
+/// EmbeddedAssets::new([
+///     ("index.html", include_bytes!("../ui/index.html").as_slice()),
+///     ("js/app.js", include_bytes!("../ui/js/app.js").as_slice())
+/// ])
+/// ```
+#[derive(Clone, Default)]
+pub struct EmbeddedAssets {
+    files: HashMap<String, &'static [u8]>
+}
+impl EmbeddedAssets {
+    /// 由`(路径, 内容)`序列构造。路径的前导`/`会被忽略。
+    pub fn new<I: IntoIterator<Item = (&'static str, &'static [u8])>>(files: I) -> Self {
+        Self {
+            files: files.into_iter().filter_map(|(path, content)| normalize_path(path).ok().map(|path| (path, content))).collect()
+        }
+    }
+    /// 追加一个资源
+    pub fn insert(&mut self, path: &str, content: &'static [u8]) -> &mut Self {
+        if let Ok(path) = normalize_path(path) {
+            self.files.insert(path, content);
+        }
+        self
+    }
+}
+impl Debug for EmbeddedAssets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbeddedAssets").field("files", &self.files.keys().collect::<Vec<_>>()).finish()
+    }
+}
+impl AssetProvider for EmbeddedAssets {
+    fn load(&self, path: &str) -> io::Result<Option<Cow<'static, [u8]>>> {
+        Ok(self.files.get(path).map(|content| Cow::Borrowed(*content)))
+    }
+}
+/// `zip`压缩包
+#[cfg(feature = "zip")]
+pub struct ZipAssets {
+    archive: RefCell<ZipArchive<Cursor<Cow<'static, [u8]>>>>
+}
+#[cfg(feature = "zip")]
+impl ZipAssets {
+    /// 由内存中的压缩包构造。比如，`ZipAssets::from_bytes(include_bytes!("../ui.zip").as_slice())`
+    pub fn from_bytes(bytes: impl Into<Cow<'static, [u8]>>) -> io::Result<Self> {
+        let archive = ZipArchive::new(Cursor::new(bytes.into())).map_err(io::Error::from)?;
+        Ok(Self { archive: RefCell::new(archive) })
+    }
+    /// 由磁盘上的压缩包构造
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(fs::read(path)?)
+    }
+}
+#[cfg(feature = "zip")]
+impl Debug for ZipAssets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipAssets").field("len", &self.archive.borrow().len()).finish()
+    }
+}
+#[cfg(feature = "zip")]
+impl AssetProvider for ZipAssets {
+    fn load(&self, path: &str) -> io::Result<Option<Cow<'static, [u8]>>> {
+        let mut archive = self.archive.borrow_mut();
+        let mut file = match archive.by_name(path) {
+            Ok(file) if file.is_file() => file,
+            Ok(_) | Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(io::Error::from(err))
+        };
+        let mut content = Vec::with_capacity(usize::try_from(file.size()).unwrap_or_default());
+        file.read_to_end(&mut content)?;
+        Ok(Some(Cow::Owned(content)))
+    }
+}
+/// 请求路径无法被规范化的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathError {
+    /// 非法的百分号编码，或解码结果不是`UTF-8`
+    Malformed,
+    /// 企图访问根目录之外的资源。比如，`..`、盘符或`NUL`字符
+    Traversal
+}
+/// 规范化请求路径：剥离`?query`与`#fragment`，解码百分号编码，统一分隔符，去掉`.`与空段，并拒绝`..`。
+/// 以`/`结尾（或为空）的路径指向该目录下的`index.html`。
+pub fn normalize_path(path: &str) -> Result<String, PathError> {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let path = percent_decode(path)?;
+    let is_directory = path.is_empty() || path.ends_with(['/', '\\']); // 须在解码之后判断：`%2F`也是分隔符
+    let mut segments = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => continue,
+            ".." => return Err(PathError::Traversal),
+            segment if segment.contains([':', '\0']) => return Err(PathError::Traversal),
+            segment => segments.push(segment)
+        }
+    }
+    if is_directory || segments.is_empty() {
+        segments.push("index.html");
+    }
+    Ok(segments.join("/"))
+}
+fn percent_decode(path: &str) -> Result<String, PathError> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut input = path.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let digits = [input.next(), input.next()];
+            let [Some(high), Some(low)] = digits else {
+                return Err(PathError::Malformed);
+            };
+            let decoded = char::from(high).to_digit(16).zip(char::from(low).to_digit(16)).ok_or(PathError::Malformed)?;
+            bytes.push(u8::try_from(decoded.0 * 16 + decoded.1).map_err(|_| PathError::Malformed)?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| PathError::Malformed)
+}
+/// 按扩展名推断`MIME`类型；扩展名未知时，再嗅探内容。
+pub fn mime_type(path: &str, content: &[u8]) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript; charset=utf-8",
+        Some("json" | "map") => "application/json",
+        Some("wasm") => "application/wasm",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("xml") => "application/xml",
+        Some("pdf") => "application/pdf",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        _ => sniff_mime_type(content)
+    }
+}
+fn sniff_mime_type(content: &[u8]) -> &'static str {
+    const SIGNATURES: [(&[u8], &str); 8] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"\0asm", "application/wasm"),
+        (b"wOF2", "font/woff2"),
+        (b"wOFF", "font/woff")
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(signature, _)| content.starts_with(signature)) {
+        return mime;
+    }
+    if content.len() >= 12 && content.starts_with(b"RIFF") && &content[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    let head = String::from_utf8_lossy(&content[..content.len().min(512)]).trim_start().to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        "text/html; charset=utf-8"
+    } else if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+        "image/svg+xml"
+    } else if ::std::str::from_utf8(content).is_ok() {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    }
+}
+/// 内容的`ETag`。它是内容长度与`FNV-1a`散列值的组合。
+pub fn etag(content: &[u8]) -> String {
+    let hash = content.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3));
+    format!("\"{:x}-{hash:016x}\"", content.len())
+}
+/// `If-None-Match`请求头是否命中`etag`（弱比较）
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.trim() == "*" || if_none_match.split(',').any(|tag| strip(tag) == strip(etag))
+}
+/// `Range`请求头的解析结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// 无`Range`请求头，或请求头无法识别（包括多段范围）。应响应全部内容
+    Full,
+    /// 闭区间`[start, end]`
+    Partial {
+        /// 首字节的下标
+        start: usize,
+        /// 末字节的下标（含）
+        end: usize
+    },
+    /// 范围超出了内容长度。应响应`416`
+    Unsatisfiable
+}
+/// 按内容长度`len`解析`Range: bytes=start-end | start- | -suffix`请求头
+pub fn parse_range(range: Option<&str>, len: usize) -> ByteRange {
+    let Some(spec) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let parse = |value: &str| value.parse::<usize>().ok();
+    let range = match (start.is_empty(), end.is_empty()) {
+        (true, true) => return ByteRange::Full,
+        (true, false) => match parse(end) { // 末尾的 suffix 个字节
+            Some(0) => return ByteRange::Unsatisfiable,
+            Some(suffix) => (len.saturating_sub(suffix), len.checked_sub(1)),
+            None => return ByteRange::Full
+        },
+        (false, true) => match parse(start) {
+            Some(start) => (start, len.checked_sub(1)),
+            None => return ByteRange::Full
+        },
+        (false, false) => match (parse(start), parse(end)) {
+            (Some(start), Some(end)) if start <= end => (start, len.checked_sub(1).map(|last| last.min(end))),
+            _ => return ByteRange::Full
+        }
+    };
+    match range {
+        (start, Some(end)) if start <= end => ByteRange::Partial { start, end },
+        _ => ByteRange::Unsatisfiable
+    }
+}
+/// 某个虚拟主机的资源服务
+pub struct AssetServer {
+    host: String,
+    provider: Box<dyn AssetProvider>
+}
+impl Debug for AssetServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetServer").field("host", &self.host).finish_non_exhaustive()
+    }
+}
+impl AssetServer {
+    /// 以`host`（比如，`app.local`）为虚拟主机名
+    pub fn new(host: impl Into<String>, provider: impl AssetProvider) -> Self {
+        Self { host: host.into(), provider: Box::new(provider) }
+    }
+    /// 虚拟主机名
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+    /// 网络请求拦截的地址过滤器。即，`https://<host>/*`
+    pub fn uri_filter(&self) -> String {
+        format!("https://{}/*", self.host)
+    }
+    /// 响应被拦截的请求。若请求地址不属于该虚拟主机，则返回`None`。主机名的比较不区分大小写。
+    pub fn serve(&self, request: &AssetRequest) -> Option<AssetResponse> {
+        let rest = request.uri.get(..8).filter(|scheme| scheme.eq_ignore_ascii_case("https://")).map(|_| &request.uri[8..])?;
+        let (host, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+        if !host.eq_ignore_ascii_case(&self.host) {
+            return None;
+        }
+        let is_head = request.method.eq_ignore_ascii_case("HEAD");
+        if !is_head && !request.method.eq_ignore_ascii_case("GET") {
+            return Some(AssetResponse::new(405, "Method Not Allowed").header("Allow", "GET, HEAD"));
+        }
+        let path = match normalize_path(path.trim_start_matches('/')) {
+            Ok(path) => path,
+            Err(PathError::Malformed) => return Some(AssetResponse::new(400, "Bad Request")),
+            Err(PathError::Traversal) => return Some(AssetResponse::new(403, "Forbidden"))
+        };
+        let content = match self.provider.load(&path) {
+            Ok(Some(content)) => content,
+            Ok(None) => return Some(AssetResponse::new(404, "Not Found").header("Content-Type", "text/plain; charset=utf-8")),
+            Err(err) => return Some(AssetResponse::new(500, "Internal Server Error").header("Content-Type", "text/plain; charset=utf-8").body(err.to_string().into_bytes(), is_head))
+        };
+        let etag = etag(&content);
+        if request.if_none_match.as_deref().is_some_and(|if_none_match| etag_matches(if_none_match, &etag)) {
+            return Some(AssetResponse::new(304, "Not Modified").header("ETag", etag));
+        }
+        let response = |status, reason| AssetResponse::new(status, reason)
+            .header("Content-Type", mime_type(&path, &content))
+            .header("ETag", etag.clone())
+            .header("Accept-Ranges", "bytes")
+            .header("Cache-Control", "no-cache");
+        Some(match parse_range(request.range.as_deref(), content.len()) {
+            ByteRange::Full => response(200, "OK").body(content.into_owned(), is_head),
+            ByteRange::Partial { start, end } => response(206, "Partial Content")
+                .header("Content-Range", format!("bytes {start}-{end}/{}", content.len()))
+                .body(content[start..=end].to_vec(), is_head),
+            ByteRange::Unsatisfiable => AssetResponse::new(416, "Range Not Satisfiable").header("Content-Range", format!("bytes */{}", content.len()))
+        })
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> AssetRequest {
+        AssetRequest { uri: uri.to_string(), method: String::from("GET"), ..AssetRequest::default() }
+    }
+    fn server() -> AssetServer {
+        AssetServer::new("app.local", EmbeddedAssets::new([
+            ("index.html", b"<!doctype html>".as_slice()),
+            ("docs/index.html", b"docs".as_slice()),
+            ("js/app.js", b"0123456789".as_slice())
+        ]))
+    }
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path(""), Ok(String::from("index.html")));
+        assert_eq!(normalize_path("/"), Ok(String::from("index.html")));
+        assert_eq!(normalize_path("js/./app.js?v=1#top"), Ok(String::from("js/app.js")));
+        assert_eq!(normalize_path("\\js\\\\app.js"), Ok(String::from("js/app.js")));
+        assert_eq!(normalize_path("docs/"), Ok(String::from("docs/index.html")));
+        assert_eq!(normalize_path("my%20file.txt"), Ok(String::from("my file.txt")));
+    }
+    #[test]
+    fn encoded_trailing_separators_mean_directories() {
+        assert_eq!(normalize_path("docs%2F"), Ok(String::from("docs/index.html")));
+        assert_eq!(normalize_path("docs%5c"), Ok(String::from("docs/index.html")));
+    }
+    #[test]
+    fn rejects_traversal_and_malformed_paths() {
+        assert_eq!(normalize_path("../secret"), Err(PathError::Traversal));
+        assert_eq!(normalize_path("js/%2e%2e/%2e%2e/secret"), Err(PathError::Traversal));
+        assert_eq!(normalize_path("..%5csecret"), Err(PathError::Traversal));
+        assert_eq!(normalize_path("C:/Windows/win.ini"), Err(PathError::Traversal));
+        assert_eq!(normalize_path("a%00b"), Err(PathError::Traversal));
+        assert_eq!(normalize_path("bad%2"), Err(PathError::Malformed));
+        assert_eq!(normalize_path("bad%zz"), Err(PathError::Malformed));
+    }
+    #[test]
+    fn percent_decodes_utf8() {
+        assert_eq!(percent_decode("%E4%B8%AD%e6%96%87"), Ok(String::from("中文")));
+        assert_eq!(percent_decode("plain+text"), Ok(String::from("plain+text")));
+        assert_eq!(percent_decode("%FF"), Err(PathError::Malformed));
+        assert_eq!(percent_decode("%"), Err(PathError::Malformed));
+    }
+    #[test]
+    fn parses_ranges() {
+        assert_eq!(parse_range(None, 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=2-5"), 10), ByteRange::Partial { start: 2, end: 5 });
+        assert_eq!(parse_range(Some("bytes=2-"), 10), ByteRange::Partial { start: 2, end: 9 });
+        assert_eq!(parse_range(Some("bytes=-3"), 10), ByteRange::Partial { start: 7, end: 9 });
+        assert_eq!(parse_range(Some("bytes=-30"), 10), ByteRange::Partial { start: 0, end: 9 });
+        assert_eq!(parse_range(Some("bytes=5-100"), 10), ByteRange::Partial { start: 5, end: 9 });
+        assert_eq!(parse_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=-0"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-"), 0), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=5-2"), 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-1"), 10), ByteRange::Full);
+    }
+    #[test]
+    fn serves_only_its_own_host() {
+        let server = server();
+        assert!(server.serve(&request("https://other.local/index.html")).is_none());
+        assert!(server.serve(&request("https://app.local.evil.com/")).is_none());
+        assert!(server.serve(&request("http://app.local/")).is_none());
+        assert_eq!(server.serve(&request("HTTPS://App.Local/")).map(|response| response.status), Some(200));
+        assert_eq!(server.serve(&request("https://app.local")).map(|response| response.body), Some(b"<!doctype html>".to_vec()));
+    }
+    #[test]
+    fn serves_statuses() {
+        let server = server();
+        let status = |request: AssetRequest| server.serve(&request).map(|response| response.status);
+        assert_eq!(status(request("https://app.local/missing.css")), Some(404));
+        assert_eq!(status(request("https://app.local/%2e%2e/secret")), Some(403));
+        assert_eq!(status(request("https://app.local/bad%zz")), Some(400));
+        assert_eq!(status(request("https://app.local/docs%2F")), Some(200));
+        assert_eq!(status(AssetRequest { method: String::from("POST"), ..request("https://app.local/") }), Some(405));
+    }
+    #[test]
+    fn serves_ranges_etags_and_head() {
+        let server = server();
+        let full = server.serve(&request("https://app.local/js/app.js")).unwrap();
+        assert_eq!(full.get_header("content-type"), Some("text/javascript; charset=utf-8"));
+        assert_eq!(full.get_header("Content-Length"), Some("10"));
+        let etag = full.get_header("ETag").unwrap().to_string();
+        let cached = server.serve(&AssetRequest { if_none_match: Some(format!("W/{etag}")), ..request("https://app.local/js/app.js") }).unwrap();
+        assert_eq!(cached.status, 304);
+        let partial = server.serve(&AssetRequest { range: Some(String::from("bytes=2-4")), ..request("https://app.local/js/app.js") }).unwrap();
+        assert_eq!((partial.status, partial.body.as_slice()), (206, b"234".as_slice()));
+        assert_eq!(partial.get_header("Content-Range"), Some("bytes 2-4/10"));
+        let unsatisfiable = server.serve(&AssetRequest { range: Some(String::from("bytes=20-")), ..request("https://app.local/js/app.js") }).unwrap();
+        assert_eq!((unsatisfiable.status, unsatisfiable.get_header("Content-Range")), (416, Some("bytes */10")));
+        let head = server.serve(&AssetRequest { method: String::from("HEAD"), ..request("https://app.local/js/app.js") }).unwrap();
+        assert_eq!((head.status, head.get_header("Content-Length"), head.body.len()), (200, Some("10"), 0));
+    }
+}
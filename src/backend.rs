@@ -0,0 +1,731 @@
+use ::std::{cell::RefCell, collections::VecDeque, error::Error, ffi::c_void, fmt::{self, Debug, Display, Formatter}, path::Path, rc::Rc};
+#[cfg(windows)]
+use ::webview2::{Controller, Environment, Error as WvError, MoveFocusReason, ProcessFailedKind, Stream, WebResourceContext, WebView};
+#[cfg(windows)]
+use ::std::{iter, ptr};
+#[cfg(windows)]
+use ::winapi::{shared::windef::RECT, um::{shellapi::ShellExecuteW, winuser::SW_SHOWNORMAL}};
+#[cfg(windows)]
+use super::com;
+use super::{assets::{AssetRequest, AssetResponse}, events::{NavigationCompleted, NavigationStarting, ProcessFailure}};
+
+/// 宿主`Win32`窗体句柄（即，`HWND`）。为了让非`Windows`平台也能编译该接口，这里将其擦除为无类型指针。
+pub type ParentWindow = *mut c_void;
+/// 网页消息的处理函数。其实参是`JSON`字符串。
+pub type WebMessageHandler = Box<dyn FnMut(String)>;
+/// 网络请求的拦截函数。返回`None`表示放行该请求。
+pub type WebResourceHandler = Box<dyn FnMut(&AssetRequest) -> Option<AssetResponse>>;
+/// 导航事件的处理函数。其实参是目标地址。
+pub type NavigationHandler = Box<dyn FnMut(&str) -> bool>;
+/// 导航开始的观察函数。它不能取消导航。
+pub type NavigationObserver = Box<dyn FnMut(&NavigationStarting)>;
+/// 网页脚本执行结束的回调。其实参是`JSON`编码的脚本结果。
+pub type ScriptCompletedHandler = Box<dyn FnOnce(BackendResult<String>)>;
+/// 挂起结束的回调。其实参表示挂起是否成功。
+pub type SuspendCompletedHandler = Box<dyn FnOnce(BackendResult<bool>)>;
+/// 网页标题变化的处理函数。其实参是新标题。
+pub type DocumentTitleHandler = Box<dyn FnMut(String)>;
+/// 导航结束的处理函数
+pub type NavigationCompletedHandler = Box<dyn FnMut(NavigationCompleted)>;
+/// 浏览器子进程失败的处理函数
+pub type ProcessFailedHandler = Box<dyn FnMut(ProcessFailure)>;
+/// 后端接口的返回值
+pub type BackendResult<T> = Result<T, BackendError>;
+/// 后端接口调用失败的原因。
+///
+/// `code`是底层`COM`接口返回的`HRESULT`错误码。模拟后端可以随意填写它。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackendError {
+    code: i32,
+    message: String
+}
+impl BackendError {
+    /// 构造一个后端错误
+    pub fn new<S: Into<String>>(code: i32, message: S) -> Self {
+        Self { code, message: message.into() }
+    }
+    /// `HRESULT`错误码
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+    /// 错误描述
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl Display for BackendError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(HRESULT = {:#010x})", self.message, self.code)
+    }
+}
+impl Error for BackendError {}
+#[cfg(windows)]
+impl From<WvError> for BackendError {
+    fn from(value: WvError) -> Self {
+        Self::new(value.hresult(), value.to_string())
+    }
+}
+/// `webview`控件在容器`nwg::Frame`客户区内的边界矩形（单位：物理像素）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    /// 左边界
+    pub left: i32,
+    /// 上边界
+    pub top: i32,
+    /// 右边界
+    pub right: i32,
+    /// 下边界
+    pub bottom: i32
+}
+impl Bounds {
+    /// 宽度
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+    /// 高度
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+}
+#[cfg(windows)]
+impl From<RECT> for Bounds {
+    fn from(value: RECT) -> Self {
+        Self { left: value.left, top: value.top, right: value.right, bottom: value.bottom }
+    }
+}
+#[cfg(windows)]
+impl From<Bounds> for RECT {
+    fn from(value: Bounds) -> Self {
+        Self { left: value.left, top: value.top, right: value.right, bottom: value.bottom }
+    }
+}
+/// `webview2::EnvironmentBuilder`的配置项。由[`crate::WebviewContainerBuilder`]收集，再被转交给[`WebviewBackend::create_environment()`]。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EnvironmentOptions<'a> {
+    /// 浏览器安装目录
+    pub browser_executable_folder: Option<&'a Path>,
+    /// 缓存目录
+    pub user_data_folder: Option<&'a Path>,
+    /// 浏览器启动参数
+    pub additional_browser_arguments: Option<&'a str>,
+    /// 默认语言
+    pub language: Option<&'a str>,
+    /// 浏览器版本号
+    pub target_compatible_browser_version: Option<&'a str>,
+    /// 是否允许单点登录
+    pub allow_single_sign_on_using_osprimary_account: bool
+}
+/// `WebviewContainer`与浏览器内核之间的接口层。
+///
+/// `WebviewContainer`对浏览器内核的全部调用都经由该`trait`转发。于是，
+/// 1. 在`Windows`上，默认后端[`Webview2Backend`]直接转发给`webview2 crate`；
+/// 2. 在任意平台上，[`RecordingBackend`]将调用记录于内存，以便断言`WebviewContainer`针对每个窗体事件所发起的调用序列。
+pub trait WebviewBackend: 'static {
+    /// 浏览器环境。对`webview2`而言，即是`webview2::Environment`
+    type Environment: Clone + 'static;
+    /// 浏览器控件。对`webview2`而言，即是`webview2::Controller`
+    type Controller: Clone + 'static;
+    /// 网页视图。对`webview2`而言，即是`webview2::WebView`
+    type WebView: Clone + 'static;
+    /// 异步地构造浏览器环境。构造结果经由`completed`回调函数返回。
+    fn create_environment(&self, options: &EnvironmentOptions<'_>, completed: Box<dyn FnOnce(BackendResult<Self::Environment>)>) -> BackendResult<()>;
+    /// 获取当前浏览器实例版本字符串
+    fn get_available_browser_version_string(&self, options: &EnvironmentOptions<'_>) -> BackendResult<String>;
+    /// 异步地构造浏览器控件，并将其嵌入`parent`窗体。构造结果经由`completed`回调函数返回。
+    fn create_controller(&self, env: &Self::Environment, parent: ParentWindow, completed: Box<dyn FnOnce(BackendResult<Self::Controller>)>) -> BackendResult<()>;
+    /// 从浏览器控件获取网页视图
+    fn get_webview(&self, controller: &Self::Controller) -> BackendResult<Self::WebView>;
+    /// 调整浏览器控件的边界矩形
+    fn put_bounds(&self, controller: &Self::Controller, bounds: Bounds) -> BackendResult<()>;
+    /// 显示或隐藏浏览器控件
+    fn put_is_visible(&self, controller: &Self::Controller, is_visible: bool) -> BackendResult<()>;
+    /// 通知浏览器控件：宿主窗体的屏幕坐标变了
+    fn notify_parent_window_position_changed(&self, controller: &Self::Controller) -> BackendResult<()>;
+    /// 以程序方式将键盘焦点移入浏览器控件
+    fn move_focus(&self, controller: &Self::Controller) -> BackendResult<()>;
+    /// 析构浏览器控件
+    fn close(&self, controller: &Self::Controller) -> BackendResult<()>;
+    /// 发送`JSON`消息给网页
+    fn post_web_message_as_json(&self, webview: &Self::WebView, json: &str) -> BackendResult<()>;
+    /// 监听网页发来的消息。处理函数收到的是`JSON`字符串。
+    fn add_web_message_received(&self, webview: &Self::WebView, handler: WebMessageHandler) -> BackendResult<()>;
+    /// 添加在每个新文档被创建时都会执行的脚本。`on_added`会收到该脚本的`id`。
+    fn add_script_to_execute_on_document_created(&self, webview: &Self::WebView, script: &str, on_added: Box<dyn FnOnce(BackendResult<String>)>) -> BackendResult<()>;
+    /// 拦截地址匹配`uri_filter`的网络请求。`handler`返回`None`表示放行该请求。
+    fn add_web_resource_requested(&self, environment: &Self::Environment, webview: &Self::WebView, uri_filter: &str, handler: WebResourceHandler) -> BackendResult<()>;
+    /// 监听导航开始事件。`handler`的实参是目标地址，返回`true`表示取消该导航。
+    fn add_navigation_starting(&self, webview: &Self::WebView, handler: NavigationHandler) -> BackendResult<()>;
+    /// 监听新窗体请求（比如，`target="_blank"`与`window.open()`）。`handler`的实参是目标地址，返回`true`表示该请求已被处理，而不再弹出新窗体。
+    fn add_new_window_requested(&self, webview: &Self::WebView, handler: NavigationHandler) -> BackendResult<()>;
+    /// 用系统默认浏览器（或协议关联程序）打开地址
+    fn open_external(&self, uri: &str) -> BackendResult<()>;
+    /// 监听网页标题的变化。`handler`的实参是最新的网页标题。
+    fn add_document_title_changed(&self, webview: &Self::WebView, handler: DocumentTitleHandler) -> BackendResult<()>;
+    /// 观察导航的开始。与[`WebviewBackend::add_navigation_starting()`]不同，它还能获知导航序号，但不能取消导航。
+    fn observe_navigation_starting(&self, webview: &Self::WebView, handler: NavigationObserver) -> BackendResult<()>;
+    /// 监听导航的结束
+    fn add_navigation_completed(&self, webview: &Self::WebView, handler: NavigationCompletedHandler) -> BackendResult<()>;
+    /// 监听浏览器子进程的失败
+    fn add_process_failed(&self, webview: &Self::WebView, handler: ProcessFailedHandler) -> BackendResult<()>;
+    /// 导航至`uri`。导航结果经由导航事件异步地送达。
+    fn navigate(&self, webview: &Self::WebView, uri: &str) -> BackendResult<()>;
+    /// 移除由[`WebviewBackend::add_script_to_execute_on_document_created()`]注入的脚本。`id`是注入时得到的脚本`id`。
+    fn remove_script_to_execute_on_document_created(&self, webview: &Self::WebView, id: &str) -> BackendResult<()>;
+    /// 在当前网页内执行脚本。`completed`的实参是`JSON`编码的脚本结果。
+    fn execute_script(&self, webview: &Self::WebView, script: &str, completed: ScriptCompletedHandler) -> BackendResult<()>;
+    /// 挂起网页视图（`ICoreWebView2_3::TrySuspend`）。浏览器控件需先被隐藏。
+    fn try_suspend(&self, webview: &Self::WebView, completed: SuspendCompletedHandler) -> BackendResult<()>;
+    /// 恢复被挂起的网页视图（`ICoreWebView2_3::Resume`）
+    fn resume(&self, webview: &Self::WebView) -> BackendResult<()>;
+    /// 调低（或恢复）网页视图的内存用量目标（`ICoreWebView2_19::put_MemoryUsageTargetLevel`）
+    fn put_memory_usage_target_level(&self, webview: &Self::WebView, is_low: bool) -> BackendResult<()>;
+    /// 设置浏览器控件的光栅化缩放比（`ICoreWebView2Controller3::put_RasterizationScale`）
+    fn put_rasterization_scale(&self, controller: &Self::Controller, scale: f64) -> BackendResult<()>;
+    /// 浏览器控件是否自己检测显示器缩放比的变化（`ICoreWebView2Controller3::put_ShouldDetectMonitorScaleChanges`）
+    fn put_should_detect_monitor_scale_changes(&self, controller: &Self::Controller, should_detect: bool) -> BackendResult<()>;
+}
+/// 默认后端：直接转发给`webview2 crate`
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Webview2Backend;
+#[cfg(windows)]
+impl Webview2Backend {
+    fn env_builder<'a>(options: &EnvironmentOptions<'a>) -> ::webview2::EnvironmentBuilder<'a> {
+        let mut env_builder = Environment::builder().with_allow_single_sign_on_using_osprimary_account(options.allow_single_sign_on_using_osprimary_account);
+        if let Some(browser_executable_folder) = options.browser_executable_folder {
+            env_builder = env_builder.with_browser_executable_folder(browser_executable_folder);
+        }
+        if let Some(user_data_folder) = options.user_data_folder {
+            env_builder = env_builder.with_user_data_folder(user_data_folder);
+        }
+        if let Some(additional_browser_arguments) = options.additional_browser_arguments {
+            env_builder = env_builder.with_additional_browser_arguments(additional_browser_arguments);
+        }
+        if let Some(language) = options.language {
+            env_builder = env_builder.with_language(language);
+        }
+        if let Some(target_compatible_browser_version) = options.target_compatible_browser_version {
+            env_builder = env_builder.with_target_compatible_browser_version(target_compatible_browser_version);
+        }
+        env_builder
+    }
+}
+#[cfg(windows)]
+impl WebviewBackend for Webview2Backend {
+    type Environment = Environment;
+    type Controller = Controller;
+    type WebView = WebView;
+    fn create_environment(&self, options: &EnvironmentOptions<'_>, completed: Box<dyn FnOnce(BackendResult<Environment>)>) -> BackendResult<()> {
+        Self::env_builder(options).build(move |env| {
+            completed(env.map_err(BackendError::from));
+            Ok(())
+        }).map_err(BackendError::from)
+    }
+    fn get_available_browser_version_string(&self, options: &EnvironmentOptions<'_>) -> BackendResult<String> {
+        Self::env_builder(options).get_available_browser_version_string().map_err(BackendError::from)
+    }
+    fn create_controller(&self, env: &Environment, parent: ParentWindow, completed: Box<dyn FnOnce(BackendResult<Controller>)>) -> BackendResult<()> {
+        env.create_controller(parent.cast(), move |controller| {
+            completed(controller.map_err(BackendError::from));
+            Ok(())
+        }).map_err(BackendError::from)
+    }
+    fn get_webview(&self, controller: &Controller) -> BackendResult<WebView> {
+        controller.get_webview().map_err(BackendError::from)
+    }
+    fn put_bounds(&self, controller: &Controller, bounds: Bounds) -> BackendResult<()> {
+        controller.put_bounds(bounds.into()).map_err(BackendError::from)
+    }
+    fn put_is_visible(&self, controller: &Controller, is_visible: bool) -> BackendResult<()> {
+        controller.put_is_visible(is_visible).map_err(BackendError::from)
+    }
+    fn notify_parent_window_position_changed(&self, controller: &Controller) -> BackendResult<()> {
+        controller.notify_parent_window_position_changed().map_err(BackendError::from)
+    }
+    fn move_focus(&self, controller: &Controller) -> BackendResult<()> {
+        controller.move_focus(MoveFocusReason::Programmatic).map_err(BackendError::from)
+    }
+    fn close(&self, controller: &Controller) -> BackendResult<()> {
+        controller.close().map_err(BackendError::from)
+    }
+    fn post_web_message_as_json(&self, webview: &WebView, json: &str) -> BackendResult<()> {
+        webview.post_web_message_as_json(json).map_err(BackendError::from)
+    }
+    fn add_web_message_received(&self, webview: &WebView, handler: WebMessageHandler) -> BackendResult<()> {
+        // webview2 只接受 Fn 回调，所以借助 RefCell 调用 FnMut 处理函数
+        let handler = RefCell::new(handler);
+        webview.add_web_message_received(move |_, args| {
+            (handler.borrow_mut())(args.get_web_message_as_json()?);
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn add_script_to_execute_on_document_created(&self, webview: &WebView, script: &str, on_added: Box<dyn FnOnce(BackendResult<String>)>) -> BackendResult<()> {
+        webview.add_script_to_execute_on_document_created(script, move |id| {
+            on_added(Ok(id));
+            Ok(())
+        }).map_err(BackendError::from)
+    }
+    fn add_web_resource_requested(&self, environment: &Environment, webview: &WebView, uri_filter: &str, handler: WebResourceHandler) -> BackendResult<()> {
+        webview.add_web_resource_requested_filter(uri_filter, WebResourceContext::All)?;
+        let environment = environment.clone();
+        let handler = RefCell::new(handler);
+        webview.add_web_resource_requested(move |_, args| {
+            let request = args.get_request()?;
+            let headers = request.get_headers()?;
+            let header = |name| headers.contains(name).ok().filter(|contains| *contains).and_then(|_| headers.get_header(name).ok());
+            let request = AssetRequest {
+                uri: request.get_uri()?,
+                method: request.get_method()?,
+                range: header("Range"),
+                if_none_match: header("If-None-Match")
+            };
+            let response = (handler.borrow_mut())(&request);
+            if let Some(response) = response {
+                // 无正文的响应（比如，304）也要携带一个空的内容流
+                let content = Stream::from_bytes(&response.body);
+                let response = environment.create_web_resource_response(content, response.status.into(), response.reason, &response.headers_string())?;
+                args.put_response(response)?;
+            }
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn add_navigation_starting(&self, webview: &WebView, handler: NavigationHandler) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_navigation_starting(move |_, args| {
+            let uri = args.get_uri()?;
+            if (handler.borrow_mut())(&uri) {
+                args.put_cancel(true)?;
+            }
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn add_new_window_requested(&self, webview: &WebView, handler: NavigationHandler) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_new_window_requested(move |_, args| {
+            let uri = args.get_uri()?;
+            if (handler.borrow_mut())(&uri) {
+                args.put_handled(true)?;
+            }
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn open_external(&self, uri: &str) -> BackendResult<()> {
+        let to_wide = |text: &str| text.encode_utf16().chain(iter::once(0)).collect::<Vec<u16>>();
+        let (operation, file) = (to_wide("open"), to_wide(uri));
+        let instance = unsafe { ShellExecuteW(ptr::null_mut(), operation.as_ptr(), file.as_ptr(), ptr::null(), ptr::null(), SW_SHOWNORMAL) };
+        // ShellExecuteW 的返回值大于 32 表示成功；否则，它就是错误码
+        match instance as isize {
+            code @ ..=32 => Err(BackendError::new(i32::try_from(code).unwrap_or_default(), format!("无法打开 {uri}"))),
+            _ => Ok(())
+        }
+    }
+    fn add_document_title_changed(&self, webview: &WebView, handler: DocumentTitleHandler) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_document_title_changed(move |webview| {
+            (handler.borrow_mut())(webview.get_document_title()?);
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn observe_navigation_starting(&self, webview: &WebView, handler: NavigationObserver) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_navigation_starting(move |_, args| {
+            (handler.borrow_mut())(&NavigationStarting { navigation_id: args.get_navigation_id()?, uri: args.get_uri()? });
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn add_navigation_completed(&self, webview: &WebView, handler: NavigationCompletedHandler) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_navigation_completed(move |_, args| {
+            (handler.borrow_mut())(NavigationCompleted {
+                navigation_id: args.get_navigation_id()?,
+                is_success: args.get_is_success()?,
+                web_error_status: args.get_web_error_status()? as u32,
+                http_status_code: unsafe { com::http_status_code(args.as_inner().as_raw().cast()) }
+            });
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn add_process_failed(&self, webview: &WebView, handler: ProcessFailedHandler) -> BackendResult<()> {
+        let handler = RefCell::new(handler);
+        webview.add_process_failed(move |_, args| {
+            (handler.borrow_mut())(match args.get_process_failed_kind()? {
+                ProcessFailedKind::BrowserProcessExited => ProcessFailure::BrowserProcessExited,
+                ProcessFailedKind::RenderProcessExited => ProcessFailure::RenderProcessExited,
+                ProcessFailedKind::RenderProcessUnresponsive => ProcessFailure::RenderProcessUnresponsive,
+                kind => ProcessFailure::Other(kind as u32)
+            });
+            Ok(())
+        }).map(|_| ()).map_err(BackendError::from)
+    }
+    fn navigate(&self, webview: &WebView, uri: &str) -> BackendResult<()> {
+        webview.navigate(uri).map_err(BackendError::from)
+    }
+    fn remove_script_to_execute_on_document_created(&self, webview: &WebView, id: &str) -> BackendResult<()> {
+        webview.remove_script_to_execute_on_document_created(id).map_err(BackendError::from)
+    }
+    fn execute_script(&self, webview: &WebView, script: &str, completed: ScriptCompletedHandler) -> BackendResult<()> {
+        webview.execute_script(script, move |result| {
+            completed(Ok(result));
+            Ok(())
+        }).map_err(BackendError::from)
+    }
+    fn try_suspend(&self, webview: &WebView, completed: SuspendCompletedHandler) -> BackendResult<()> {
+        unsafe { com::try_suspend(webview.as_inner().as_raw().cast(), completed) }
+    }
+    fn resume(&self, webview: &WebView) -> BackendResult<()> {
+        unsafe { com::resume(webview.as_inner().as_raw().cast()) }
+    }
+    fn put_memory_usage_target_level(&self, webview: &WebView, is_low: bool) -> BackendResult<()> {
+        unsafe { com::put_memory_usage_target_level(webview.as_inner().as_raw().cast(), is_low) }
+    }
+    fn put_rasterization_scale(&self, controller: &Controller, scale: f64) -> BackendResult<()> {
+        unsafe { com::put_rasterization_scale(controller.as_inner().as_raw().cast(), scale) }
+    }
+    fn put_should_detect_monitor_scale_changes(&self, controller: &Controller, should_detect: bool) -> BackendResult<()> {
+        unsafe { com::put_should_detect_monitor_scale_changes(controller.as_inner().as_raw().cast(), should_detect) }
+    }
+}
+/// 被[`RecordingBackend`]记录下来的一次后端调用
+#[allow(variant_size_differences)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackendCall {
+    /// [`WebviewBackend::create_environment()`]
+    CreateEnvironment,
+    /// [`WebviewBackend::create_controller()`]
+    CreateController,
+    /// [`WebviewBackend::get_webview()`]
+    GetWebview,
+    /// [`WebviewBackend::put_bounds()`]
+    PutBounds(Bounds),
+    /// [`WebviewBackend::put_is_visible()`]
+    PutIsVisible(bool),
+    /// [`WebviewBackend::notify_parent_window_position_changed()`]
+    NotifyParentWindowPositionChanged,
+    /// [`WebviewBackend::move_focus()`]
+    MoveFocus,
+    /// [`WebviewBackend::close()`]
+    Close,
+    /// [`WebviewBackend::post_web_message_as_json()`]
+    PostWebMessageAsJson(String),
+    /// [`WebviewBackend::add_web_message_received()`]
+    AddWebMessageReceived,
+    /// [`WebviewBackend::add_script_to_execute_on_document_created()`]
+    AddScriptToExecuteOnDocumentCreated(String),
+    /// [`WebviewBackend::add_web_resource_requested()`]
+    AddWebResourceRequested(String),
+    /// [`WebviewBackend::add_navigation_starting()`]
+    AddNavigationStarting,
+    /// [`WebviewBackend::add_new_window_requested()`]
+    AddNewWindowRequested,
+    /// [`WebviewBackend::open_external()`]
+    OpenExternal(String),
+    /// [`WebviewBackend::add_document_title_changed()`]
+    AddDocumentTitleChanged,
+    /// [`WebviewBackend::observe_navigation_starting()`]
+    ObserveNavigationStarting,
+    /// [`WebviewBackend::add_navigation_completed()`]
+    AddNavigationCompleted,
+    /// [`WebviewBackend::add_process_failed()`]
+    AddProcessFailed,
+    /// [`WebviewBackend::navigate()`]
+    Navigate(String),
+    /// [`WebviewBackend::remove_script_to_execute_on_document_created()`]
+    RemoveScriptToExecuteOnDocumentCreated(String),
+    /// [`WebviewBackend::execute_script()`]
+    ExecuteScript(String),
+    /// [`WebviewBackend::try_suspend()`]
+    TrySuspend,
+    /// [`WebviewBackend::resume()`]
+    Resume,
+    /// [`WebviewBackend::put_memory_usage_target_level()`]
+    PutMemoryUsageTargetLevel(bool),
+    /// [`WebviewBackend::put_rasterization_scale()`]
+    PutRasterizationScale(f64),
+    /// [`WebviewBackend::put_should_detect_monitor_scale_changes()`]
+    PutShouldDetectMonitorScaleChanges(bool)
+}
+/// 内存记录后端。它不依赖任何浏览器内核，而仅将每次调用追加至调用日志，以供断言。
+///
+/// 克隆出来的实例共享同一份调用日志。所以，先克隆一份交给`WebviewContainerBuilder::backend()`，再经由另一份读取调用日志。
+/// 异步构造接口都会被同步地完成。浏览器控件被模拟为自增的序号。
+#[derive(Clone, Default)]
+pub struct RecordingBackend {
+    calls: Rc<RefCell<Vec<BackendCall>>>,
+    controller_count: Rc<RefCell<u32>>,
+    environment_failure: Rc<RefCell<Option<BackendError>>>,
+    controller_failure: Rc<RefCell<Option<BackendError>>>,
+    browser_version: Rc<RefCell<Option<BackendResult<String>>>>,
+    web_message_handlers: Rc<RefCell<Vec<WebMessageHandler>>>,
+    script_count: Rc<RefCell<u32>>,
+    web_resource_handlers: Rc<RefCell<Vec<(String, WebResourceHandler)>>>,
+    navigation_starting_handlers: Rc<RefCell<Vec<NavigationHandler>>>,
+    new_window_requested_handlers: Rc<RefCell<Vec<NavigationHandler>>>,
+    document_title_changed_handlers: Rc<RefCell<Vec<DocumentTitleHandler>>>,
+    navigation_observers: Rc<RefCell<Vec<NavigationObserver>>>,
+    navigation_count: Rc<RefCell<u64>>,
+    navigation_completed_handlers: Rc<RefCell<Vec<NavigationCompletedHandler>>>,
+    process_failed_handlers: Rc<RefCell<Vec<ProcessFailedHandler>>>,
+    script_callbacks: Rc<RefCell<VecDeque<ScriptCompletedHandler>>>,
+    suspend_callbacks: Rc<RefCell<VecDeque<SuspendCompletedHandler>>>
+}
+impl Debug for RecordingBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingBackend")
+            .field("calls", &self.calls)
+            .field("controller_count", &self.controller_count)
+            .field("environment_failure", &self.environment_failure)
+            .field("controller_failure", &self.controller_failure)
+            .field("browser_version", &self.browser_version)
+            .field("web_message_handlers", &self.web_message_handlers.borrow().len())
+            .field("script_count", &self.script_count)
+            .field("web_resource_handlers", &self.web_resource_handlers.borrow().iter().map(|(uri_filter, _)| uri_filter.clone()).collect::<Vec<_>>())
+            .field("navigation_starting_handlers", &self.navigation_starting_handlers.borrow().len())
+            .field("new_window_requested_handlers", &self.new_window_requested_handlers.borrow().len())
+            .field("document_title_changed_handlers", &self.document_title_changed_handlers.borrow().len())
+            .field("navigation_observers", &self.navigation_observers.borrow().len())
+            .field("navigation_count", &self.navigation_count)
+            .field("navigation_completed_handlers", &self.navigation_completed_handlers.borrow().len())
+            .field("process_failed_handlers", &self.process_failed_handlers.borrow().len())
+            .field("script_callbacks", &self.script_callbacks.borrow().len())
+            .field("suspend_callbacks", &self.suspend_callbacks.borrow().len())
+            .finish()
+    }
+}
+impl RecordingBackend {
+    /// 迄今为止被记录下的全部调用
+    pub fn calls(&self) -> Vec<BackendCall> {
+        self.calls.borrow().clone()
+    }
+    /// 清空调用日志，并返回被清掉的调用
+    pub fn take_calls(&self) -> Vec<BackendCall> {
+        self.calls.borrow_mut().drain(..).collect()
+    }
+    /// 令后续的浏览器环境构造（异步地）失败
+    pub fn fail_environment_creation(&self, err: BackendError) {
+        self.environment_failure.borrow_mut().replace(err);
+    }
+    /// 令后续的浏览器控件构造（异步地）失败
+    pub fn fail_controller_creation(&self, err: BackendError) {
+        self.controller_failure.borrow_mut().replace(err);
+    }
+    /// 令后续的`WebView2 Runtime`版本探测返回`version`。未配置时，探测结果是`0.0.0.0`。
+    pub fn set_browser_version(&self, version: &str) {
+        self.browser_version.borrow_mut().replace(Ok(version.to_string()));
+    }
+    /// 令后续的`WebView2 Runtime`版本探测失败
+    pub fn fail_browser_version(&self, err: BackendError) {
+        self.browser_version.borrow_mut().replace(Err(err));
+    }
+    /// 模拟未安装`WebView2 Runtime`。同真实的`WebView2`一样，版本探测以`HRESULT_FROM_WIN32(ERROR_FILE_NOT_FOUND)`失败。
+    pub fn uninstall_runtime(&self) {
+        self.fail_browser_version(BackendError::new(0x8007_0002_u32 as i32, "系统找不到指定的文件。"));
+    }
+    /// 模拟网页发来一条`JSON`消息。返回值是接收到该消息的处理函数个数。
+    pub fn deliver_web_message(&self, json: &str) -> usize {
+        let mut handlers = self.web_message_handlers.take();
+        handlers.iter_mut().for_each(|handler| handler(json.to_string()));
+        let count = handlers.len();
+        let mut web_message_handlers = self.web_message_handlers.borrow_mut();
+        handlers.append(&mut web_message_handlers);
+        *web_message_handlers = handlers;
+        count
+    }
+    /// 模拟一次网络请求。它被交给首个过滤器匹配请求地址、且作出响应的拦截函数。过滤器仅支持末尾的`*`通配符。
+    pub fn request_web_resource(&self, request: &AssetRequest) -> Option<AssetResponse> {
+        self.web_resource_handlers.borrow_mut().iter_mut().filter(|(uri_filter, _)| match uri_filter.strip_suffix('*') {
+            Some(prefix) => request.uri.starts_with(prefix),
+            None => request.uri == *uri_filter
+        }).find_map(|(_, handler)| handler(request))
+    }
+    /// 模拟一次导航。返回值表示该导航是否被取消了。导航序号自增，且可经由[`RecordingBackend::last_navigation_id()`]读取。
+    pub fn start_navigation(&self, uri: &str) -> bool {
+        let is_cancelled = self.navigation_starting_handlers.borrow_mut().iter_mut().filter_map(|handler| handler(uri).then_some(())).count() > 0;
+        let starting = NavigationStarting {
+            navigation_id: {
+                let mut navigation_count = self.navigation_count.borrow_mut();
+                *navigation_count += 1;
+                *navigation_count
+            },
+            uri: uri.to_string()
+        };
+        self.navigation_observers.borrow_mut().iter_mut().for_each(|handler| handler(&starting));
+        is_cancelled
+    }
+    /// 最近一次模拟导航的序号
+    pub fn last_navigation_id(&self) -> Option<u64> {
+        Some(*self.navigation_count.borrow()).filter(|navigation_count| *navigation_count > 0)
+    }
+    /// 模拟一次新窗体请求。返回值表示该请求是否被处理了。
+    pub fn request_new_window(&self, uri: &str) -> bool {
+        self.new_window_requested_handlers.borrow_mut().iter_mut().filter_map(|handler| handler(uri).then_some(())).count() > 0
+    }
+    /// 模拟一次网页标题的变化
+    pub fn change_document_title(&self, title: &str) {
+        self.document_title_changed_handlers.borrow_mut().iter_mut().for_each(|handler| handler(title.to_string()));
+    }
+    /// 模拟一次导航的结束
+    pub fn complete_navigation(&self, completed: NavigationCompleted) {
+        self.navigation_completed_handlers.borrow_mut().iter_mut().for_each(|handler| handler(completed));
+    }
+    /// 模拟一次浏览器子进程的失败
+    pub fn fail_process(&self, failure: ProcessFailure) {
+        self.process_failed_handlers.borrow_mut().iter_mut().for_each(|handler| handler(failure));
+    }
+    /// 模拟最早一次还未结束的脚本执行的结束。返回值表示是否有还未结束的脚本执行。
+    pub fn finish_script(&self, result: BackendResult<&str>) -> bool {
+        let completed = self.script_callbacks.borrow_mut().pop_front();
+        completed.map(|completed| completed(result.map(str::to_string))).is_some()
+    }
+    /// 模拟最早一次还未结束的挂起的结束。返回值表示是否有还未结束的挂起。
+    pub fn finish_suspend(&self, result: BackendResult<bool>) -> bool {
+        let completed = self.suspend_callbacks.borrow_mut().pop_front();
+        completed.map(|completed| completed(result)).is_some()
+    }
+    fn record(&self, call: BackendCall) {
+        self.calls.borrow_mut().push(call);
+    }
+}
+impl WebviewBackend for RecordingBackend {
+    type Environment = ();
+    type Controller = u32;
+    type WebView = ();
+    fn create_environment(&self, _options: &EnvironmentOptions<'_>, completed: Box<dyn FnOnce(BackendResult<()>)>) -> BackendResult<()> {
+        self.record(BackendCall::CreateEnvironment);
+        let failure = self.environment_failure.borrow().clone();
+        completed(failure.map_or(Ok(()), Err));
+        Ok(())
+    }
+    fn get_available_browser_version_string(&self, _options: &EnvironmentOptions<'_>) -> BackendResult<String> {
+        self.browser_version.borrow().clone().unwrap_or_else(|| Ok(String::from("0.0.0.0")))
+    }
+    fn create_controller(&self, _env: &(), _parent: ParentWindow, completed: Box<dyn FnOnce(BackendResult<u32>)>) -> BackendResult<()> {
+        self.record(BackendCall::CreateController);
+        if let Some(err) = self.controller_failure.borrow().clone() {
+            completed(Err(err));
+            return Ok(());
+        }
+        let controller = {
+            let mut controller_count = self.controller_count.borrow_mut();
+            *controller_count += 1;
+            *controller_count
+        };
+        completed(Ok(controller));
+        Ok(())
+    }
+    fn get_webview(&self, _controller: &u32) -> BackendResult<()> {
+        self.record(BackendCall::GetWebview);
+        Ok(())
+    }
+    fn put_bounds(&self, _controller: &u32, bounds: Bounds) -> BackendResult<()> {
+        self.record(BackendCall::PutBounds(bounds));
+        Ok(())
+    }
+    fn put_is_visible(&self, _controller: &u32, is_visible: bool) -> BackendResult<()> {
+        self.record(BackendCall::PutIsVisible(is_visible));
+        Ok(())
+    }
+    fn notify_parent_window_position_changed(&self, _controller: &u32) -> BackendResult<()> {
+        self.record(BackendCall::NotifyParentWindowPositionChanged);
+        Ok(())
+    }
+    fn move_focus(&self, _controller: &u32) -> BackendResult<()> {
+        self.record(BackendCall::MoveFocus);
+        Ok(())
+    }
+    fn close(&self, _controller: &u32) -> BackendResult<()> {
+        self.record(BackendCall::Close);
+        Ok(())
+    }
+    fn post_web_message_as_json(&self, _webview: &(), json: &str) -> BackendResult<()> {
+        self.record(BackendCall::PostWebMessageAsJson(json.to_string()));
+        Ok(())
+    }
+    fn add_web_message_received(&self, _webview: &(), handler: WebMessageHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddWebMessageReceived);
+        self.web_message_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn add_script_to_execute_on_document_created(&self, _webview: &(), script: &str, on_added: Box<dyn FnOnce(BackendResult<String>)>) -> BackendResult<()> {
+        self.record(BackendCall::AddScriptToExecuteOnDocumentCreated(script.to_string()));
+        let id = {
+            let mut script_count = self.script_count.borrow_mut();
+            *script_count += 1;
+            *script_count
+        };
+        on_added(Ok(format!("script-{id}")));
+        Ok(())
+    }
+    fn add_web_resource_requested(&self, _environment: &(), _webview: &(), uri_filter: &str, handler: WebResourceHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddWebResourceRequested(uri_filter.to_string()));
+        self.web_resource_handlers.borrow_mut().push((uri_filter.to_string(), handler));
+        Ok(())
+    }
+    fn add_navigation_starting(&self, _webview: &(), handler: NavigationHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddNavigationStarting);
+        self.navigation_starting_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn add_new_window_requested(&self, _webview: &(), handler: NavigationHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddNewWindowRequested);
+        self.new_window_requested_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn open_external(&self, uri: &str) -> BackendResult<()> {
+        self.record(BackendCall::OpenExternal(uri.to_string()));
+        Ok(())
+    }
+    fn add_document_title_changed(&self, _webview: &(), handler: DocumentTitleHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddDocumentTitleChanged);
+        self.document_title_changed_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn observe_navigation_starting(&self, _webview: &(), handler: NavigationObserver) -> BackendResult<()> {
+        self.record(BackendCall::ObserveNavigationStarting);
+        self.navigation_observers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn add_navigation_completed(&self, _webview: &(), handler: NavigationCompletedHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddNavigationCompleted);
+        self.navigation_completed_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn add_process_failed(&self, _webview: &(), handler: ProcessFailedHandler) -> BackendResult<()> {
+        self.record(BackendCall::AddProcessFailed);
+        self.process_failed_handlers.borrow_mut().push(handler);
+        Ok(())
+    }
+    fn navigate(&self, _webview: &(), uri: &str) -> BackendResult<()> {
+        self.record(BackendCall::Navigate(uri.to_string()));
+        Ok(())
+    }
+    fn remove_script_to_execute_on_document_created(&self, _webview: &(), id: &str) -> BackendResult<()> {
+        self.record(BackendCall::RemoveScriptToExecuteOnDocumentCreated(id.to_string()));
+        Ok(())
+    }
+    fn execute_script(&self, _webview: &(), script: &str, completed: ScriptCompletedHandler) -> BackendResult<()> {
+        self.record(BackendCall::ExecuteScript(script.to_string()));
+        self.script_callbacks.borrow_mut().push_back(completed);
+        Ok(())
+    }
+    fn try_suspend(&self, _webview: &(), completed: SuspendCompletedHandler) -> BackendResult<()> {
+        self.record(BackendCall::TrySuspend);
+        self.suspend_callbacks.borrow_mut().push_back(completed);
+        Ok(())
+    }
+    fn resume(&self, _webview: &()) -> BackendResult<()> {
+        self.record(BackendCall::Resume);
+        Ok(())
+    }
+    fn put_memory_usage_target_level(&self, _webview: &(), is_low: bool) -> BackendResult<()> {
+        self.record(BackendCall::PutMemoryUsageTargetLevel(is_low));
+        Ok(())
+    }
+    fn put_rasterization_scale(&self, _controller: &u32, scale: f64) -> BackendResult<()> {
+        self.record(BackendCall::PutRasterizationScale(scale));
+        Ok(())
+    }
+    fn put_should_detect_monitor_scale_changes(&self, _controller: &u32, should_detect: bool) -> BackendResult<()> {
+        self.record(BackendCall::PutShouldDetectMonitorScaleChanges(should_detect));
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! `JavaScript <-> Rust`消息桥。
+//!
+//! 双向消息都被包装于同一种信封`{"channel": "...", "payload": ...}`内。
+//! * `JS -> Rust`：`window.chrome.webview.postMessage({channel: "todo", payload: {...}})`
+//! * `Rust -> JS`：`window.chrome.webview.addEventListener("message", ({data}) => data.channel === "todo" && ...)`
+use ::serde::{Deserialize, Serialize, de::DeserializeOwned};
+use ::serde_json::Value;
+use ::std::{cell::{Cell, RefCell}, collections::HashMap, fmt::{self, Debug, Formatter}, marker::PhantomData, rc::{Rc, Weak}};
+use super::{backend::WebviewBackend, error::WebviewContainerError, log::debug};
+
+#[derive(Serialize)]
+struct EnvelopeRef<'a, T> {
+    channel: &'a str,
+    payload: &'a T
+}
+/// 消息信封
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Envelope<T = Value> {
+    /// 频道名。它决定了消息被分发给哪个处理函数。
+    pub channel: String,
+    /// 消息体
+    pub payload: T
+}
+/// 将消息体编码为`JSON`信封
+pub fn encode_envelope<T: Serialize>(channel: &str, payload: &T) -> Result<String, WebviewContainerError> {
+    ::serde_json::to_string(&EnvelopeRef { channel, payload }).map_err(|err| WebviewContainerError::Serialization(err.to_string()))
+}
+/// 从`JSON`信封解码出频道名与（还未被反序列化的）消息体
+pub fn decode_envelope(json: &str) -> Result<Envelope, WebviewContainerError> {
+    ::serde_json::from_str(json).map_err(|err| WebviewContainerError::Serialization(err.to_string()))
+}
+/// 将消息体反序列化为具体的数据类型
+pub fn decode_payload<T: DeserializeOwned>(payload: Value) -> Result<T, WebviewContainerError> {
+    ::serde_json::from_value(payload).map_err(|err| WebviewContainerError::Serialization(err.to_string()))
+}
+type ChannelHandler = Box<dyn FnMut(Value)>;
+/// 频道的一次注册。执行期间，`handler`被暂时取走。
+struct Registration {
+    generation: u64,
+    handler: Option<ChannelHandler>
+}
+/// 按`channel`将网页消息分发给各个处理函数
+#[derive(Default)]
+pub(crate) struct MessageRouter {
+    handlers: RefCell<HashMap<String, Registration>>,
+    generation: Cell<u64>
+}
+#[cfg_attr(not(windows), allow(dead_code))]
+impl MessageRouter {
+    /// 注册（或替换）频道的处理函数
+    pub(crate) fn set_handler(&self, channel: String, handler: ChannelHandler) {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        self.handlers.borrow_mut().insert(channel, Registration { generation, handler: Some(handler) });
+    }
+    /// 注销频道的处理函数
+    pub(crate) fn remove_handler(&self, channel: &str) {
+        self.handlers.borrow_mut().remove(channel);
+    }
+    /// 分发一条`JSON`网页消息。返回值表示是否有处理函数接收了该消息。
+    ///
+    /// 在处理函数执行期间，它会被暂时取出分发表。所以，处理函数可以安全地注册、替换或注销任意频道（包括它自己的）的处理函数。
+    /// 每次注册都有新的代次。仅当该频道的代次未变时，执行完的处理函数才会被放回去。
+    pub(crate) fn route(&self, json: &str) -> Result<bool, WebviewContainerError> {
+        let Envelope { channel, payload } = decode_envelope(json)?;
+        let taken = self.handlers.borrow_mut().get_mut(&channel)
+            .and_then(|registration| registration.handler.take().map(|handler| (registration.generation, handler)));
+        let Some((generation, mut handler)) = taken else {
+            debug!("[MessageRouter][route]频道 {channel} 没有处理函数");
+            return Ok(false);
+        };
+        handler(payload);
+        if let Some(registration) = self.handlers.borrow_mut().get_mut(&channel).filter(|registration| registration.generation == generation) {
+            registration.handler.get_or_insert(handler);
+        }
+        Ok(true)
+    }
+}
+/// 类型化的消息桥。`In`是从网页收到的消息类型，`Out`是发往网页的消息类型。
+///
+/// 由`WebviewContainer::bridge()`构造。在`webview`控件就绪之前发送消息会得到[`WebviewContainerError::NotReady`]。
+/// 消息处理函数的生命周期跟随`WebviewContainer`，而不是`WebviewBridge`。克隆出来的实例共享同一个频道。
+pub struct WebviewBridge<In, Out, B: WebviewBackend> {
+    channel: String,
+    backend: Rc<B>,
+    webview: Weak<RefCell<Option<B::WebView>>>,
+    router: Weak<MessageRouter>,
+    _marker: PhantomData<fn(In) -> Out>
+}
+impl<In, Out, B: WebviewBackend> WebviewBridge<In, Out, B> {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(channel: String, backend: Rc<B>, webview: &Rc<RefCell<Option<B::WebView>>>, router: &Rc<MessageRouter>) -> Self {
+        Self {
+            channel,
+            backend,
+            webview: Rc::downgrade(webview),
+            router: Rc::downgrade(router),
+            _marker: PhantomData
+        }
+    }
+    /// 频道名
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+    /// 注销该频道的消息处理函数
+    pub fn off_message(&self) {
+        if let Some(router) = self.router.upgrade() {
+            router.remove_handler(&self.channel);
+        }
+    }
+}
+impl<In: DeserializeOwned + 'static, Out: Serialize, B: WebviewBackend> WebviewBridge<In, Out, B> {
+    /// 注册（或替换）该频道的消息处理函数。无法被反序列化为`In`的消息会被丢弃。
+    pub fn on_message<F: FnMut(In) + 'static>(&self, mut handler: F) -> Result<(), WebviewContainerError> {
+        let router = self.router.upgrade().ok_or(WebviewContainerError::AlreadyClosed)?;
+        let channel = self.channel.clone();
+        router.set_handler(self.channel.clone(), Box::new(move |payload| match decode_payload(payload) {
+            Ok(message) => handler(message),
+            Err(err) => debug!("[WebviewBridge][on_message]频道 {channel} 的消息被丢弃了：{err}")
+        }));
+        Ok(())
+    }
+    /// 发送一条消息给网页
+    pub fn post(&self, message: &Out) -> Result<(), WebviewContainerError> {
+        let webview = self.webview.upgrade().ok_or(WebviewContainerError::AlreadyClosed)?;
+        let webview = webview.borrow();
+        let webview = webview.as_ref().ok_or(WebviewContainerError::NotReady)?;
+        let json = encode_envelope(&self.channel, message)?;
+        self.backend.post_web_message_as_json(webview, &json).map_err(WebviewContainerError::WebMessage)
+    }
+}
+impl<In, Out, B: WebviewBackend> Clone for WebviewBridge<In, Out, B> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+            backend: Rc::clone(&self.backend),
+            webview: Weak::clone(&self.webview),
+            router: Weak::clone(&self.router),
+            _marker: PhantomData
+        }
+    }
+}
+impl<In, Out, B: WebviewBackend> Debug for WebviewBridge<In, Out, B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewBridge").field("channel", &self.channel).finish_non_exhaustive()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use ::serde_json::json;
+    use crate::backend::{BackendCall, RecordingBackend};
+    use super::*;
+
+    fn recorder() -> (Rc<RefCell<Vec<Value>>>, ChannelHandler) {
+        let received = Rc::<RefCell<Vec<Value>>>::default();
+        let sink = Rc::clone(&received);
+        (received, Box::new(move |payload| sink.borrow_mut().push(payload)))
+    }
+    #[test]
+    fn envelopes_round_trip() {
+        let json = encode_envelope("todo", &json!({"id": 1})).unwrap();
+        assert_eq!(decode_envelope(&json).unwrap(), Envelope { channel: String::from("todo"), payload: json!({"id": 1}) });
+        assert_eq!(decode_payload::<(u8, String)>(json!([1, "a"])).unwrap(), (1, String::from("a")));
+    }
+    #[test]
+    fn routes_json_by_channel() {
+        let router = MessageRouter::default();
+        let (todo, handler) = recorder();
+        router.set_handler(String::from("todo"), handler);
+        assert!(router.route(r#"{"channel": "todo", "payload": {"done": true}}"#).unwrap());
+        assert!(router.route(r#"{"channel": "todo", "payload": 2}"#).unwrap());
+        assert_eq!(*todo.borrow(), vec![json!({"done": true}), json!(2)]);
+    }
+    #[test]
+    fn unknown_channel_is_not_routed() {
+        let router = MessageRouter::default();
+        let (todo, handler) = recorder();
+        router.set_handler(String::from("todo"), handler);
+        assert!(!router.route(r#"{"channel": "other", "payload": null}"#).unwrap());
+        router.remove_handler("todo");
+        assert!(!router.route(r#"{"channel": "todo", "payload": null}"#).unwrap());
+        assert!(todo.borrow().is_empty());
+    }
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let router = MessageRouter::default();
+        for json in ["not json", r#"{"payload": 1}"#, r#"{"channel": 1, "payload": 1}"#, r#""todo""#] {
+            assert!(matches!(router.route(json), Err(WebviewContainerError::Serialization(_))), "{json}");
+        }
+    }
+    #[test]
+    fn off_message_inside_the_handler_sticks() {
+        let router = Rc::new(MessageRouter::default());
+        let count = Rc::new(Cell::new(0));
+        let (weak_router, counter) = (Rc::downgrade(&router), Rc::clone(&count));
+        router.set_handler(String::from("once"), Box::new(move |_| {
+            counter.set(counter.get() + 1);
+            weak_router.upgrade().unwrap().remove_handler("once");
+        }));
+        assert!(router.route(r#"{"channel": "once", "payload": null}"#).unwrap());
+        assert!(!router.route(r#"{"channel": "once", "payload": null}"#).unwrap());
+        assert_eq!(count.get(), 1);
+    }
+    #[test]
+    fn replacement_inside_the_handler_wins() {
+        let router = Rc::new(MessageRouter::default());
+        let (replaced, replacement) = recorder();
+        let replacement = RefCell::new(Some(replacement));
+        let weak_router = Rc::downgrade(&router);
+        router.set_handler(String::from("swap"), Box::new(move |_| if let Some(replacement) = replacement.borrow_mut().take() {
+            weak_router.upgrade().unwrap().set_handler(String::from("swap"), replacement);
+        }));
+        router.route(r#"{"channel": "swap", "payload": 1}"#).unwrap();
+        router.route(r#"{"channel": "swap", "payload": 2}"#).unwrap();
+        assert_eq!(*replaced.borrow(), vec![json!(2)]);
+    }
+    #[test]
+    fn bridge_decodes_incoming_and_posts_outgoing_envelopes() {
+        let backend = RecordingBackend::default();
+        let (router, webview) = (Rc::new(MessageRouter::default()), Rc::new(RefCell::new(None)));
+        let bridge = WebviewBridge::<u32, &str, _>::new(String::from("count"), Rc::new(backend.clone()), &webview, &router);
+        assert_eq!(bridge.post(&"early"), Err(WebviewContainerError::NotReady));
+        webview.replace(Some(()));
+        bridge.post(&"hello").unwrap();
+        assert_eq!(backend.calls(), vec![BackendCall::PostWebMessageAsJson(String::from(r#"{"channel":"count","payload":"hello"}"#))]);
+        let received = Rc::new(Cell::new(0));
+        let sink = Rc::clone(&received);
+        bridge.on_message(move |count| sink.set(count)).unwrap();
+        router.route(r#"{"channel": "count", "payload": "not a number"}"#).unwrap();
+        router.route(r#"{"channel": "count", "payload": 7}"#).unwrap();
+        assert_eq!(received.get(), 7);
+        bridge.off_message();
+        assert!(!router.route(r#"{"channel": "count", "payload": 8}"#).unwrap());
+    }
+}
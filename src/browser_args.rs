@@ -0,0 +1,345 @@
+//! `Chromium`浏览器启动参数（即，`additional_browser_arguments`）的类型化构造器。
+//!
+//! [`BrowserArgs`]负责
+//! 1. 按`Win32`命令行规则（`CommandLineToArgvW`）给含空白与双引号的参数值加引号与转义；
+//! 2. 合并`--enable-features`与`--disable-features`列表，和对同名开关去重（后者覆盖前者）；
+//! 3. 解析用户手写的原始启动参数，并与类型化的配置合并。
+//!
+//! 渲染与解析互为逆运算。
+use ::std::{error::Error, fmt::{self, Display, Formatter}, str::FromStr};
+
+const ENABLE_FEATURES: &str = "enable-features";
+const DISABLE_FEATURES: &str = "disable-features";
+/// 启动参数的解析错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BrowserArgsError {
+    /// 双引号未闭合
+    UnterminatedQuote,
+    /// 参数不是以`--`开头的开关
+    NotSwitch(String),
+    /// 开关名为空，或含有除字母、数字、`-`、`_`与`.`之外的字符
+    InvalidSwitchName(String)
+}
+impl Display for BrowserArgsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedQuote => write!(f, "启动参数的双引号未闭合"),
+            Self::NotSwitch(arg) => write!(f, "启动参数需以 -- 开头：{arg}"),
+            Self::InvalidSwitchName(name) => write!(f, "启动参数的开关名不合法：{name}")
+        }
+    }
+}
+impl Error for BrowserArgsError {}
+/// 媒体自动播放策略（`--autoplay-policy`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoplayPolicy {
+    /// 无需用户手势即可自动播放
+    NoUserGestureRequired,
+    /// 需要用户手势才能自动播放
+    UserGestureRequired,
+    /// 需要文档被用户激活过才能自动播放
+    DocumentUserActivationRequired
+}
+impl AutoplayPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoUserGestureRequired => "no-user-gesture-required",
+            Self::UserGestureRequired => "user-gesture-required",
+            Self::DocumentUserActivationRequired => "document-user-activation-required"
+        }
+    }
+}
+/// `Chromium`浏览器启动参数。比如，
+/// ```ignore
+/// let browser_args = BrowserArgs::new()
+///     .disable_feature("msSmartScreenProtection")
+///     .proxy_server("http=proxy.local:8080;https=proxy.local:8443")
+///     .remote_debugging_port(9222)
+///     .autoplay_policy(AutoplayPolicy::NoUserGestureRequired)
+///     .switch_value("lang", "zh-CN")?
+///     .raw("--disable-gpu --enable-features=OverlayScrollbar")?;
+/// builder.browser_args(browser_args);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BrowserArgs {
+    enabled_features: Vec<String>,
+    disabled_features: Vec<String>,
+    switches: Vec<(String, Option<String>)>
+}
+impl BrowserArgs {
+    /// 空的启动参数
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 解析原始启动参数。比如，`--disable-gpu --proxy-server="http=proxy.local:8080"`
+    pub fn parse(raw: &str) -> Result<Self, BrowserArgsError> {
+        split_command_line(raw)?.into_iter().try_fold(Self::new(), |browser_args, arg| {
+            let switch = arg.strip_prefix("--").ok_or_else(|| BrowserArgsError::NotSwitch(arg.clone()))?;
+            match switch.split_once('=') {
+                Some((name, value)) => browser_args.switch_value(name, value),
+                None => browser_args.switch(switch)
+            }
+        })
+    }
+    /// 启用`Chromium`特性。逗号分隔的多个特性会被逐一启用。
+    pub fn enable_feature(mut self, features: &str) -> Self {
+        for feature in split_features(features) {
+            self.disabled_features.retain(|disabled| disabled != feature);
+            if !self.enabled_features.iter().any(|enabled| enabled == feature) {
+                self.enabled_features.push(feature.to_string());
+            }
+        }
+        self
+    }
+    /// 禁用`Chromium`特性。逗号分隔的多个特性会被逐一禁用。
+    pub fn disable_feature(mut self, features: &str) -> Self {
+        for feature in split_features(features) {
+            self.enabled_features.retain(|enabled| enabled != feature);
+            if !self.disabled_features.iter().any(|disabled| disabled == feature) {
+                self.disabled_features.push(feature.to_string());
+            }
+        }
+        self
+    }
+    /// 代理服务器（`--proxy-server`）。比如，`socks5://proxy.local:1080`
+    pub fn proxy_server(self, proxy_server: &str) -> Self {
+        self.set(String::from("proxy-server"), Some(proxy_server.to_string()))
+    }
+    /// 不经由代理服务器的地址列表（`--proxy-bypass-list`）。比如，`*.local;<local>`
+    pub fn proxy_bypass_list(self, proxy_bypass_list: &str) -> Self {
+        self.set(String::from("proxy-bypass-list"), Some(proxy_bypass_list.to_string()))
+    }
+    /// 远程调试端口（`--remote-debugging-port`）
+    pub fn remote_debugging_port(self, port: u16) -> Self {
+        self.set(String::from("remote-debugging-port"), Some(port.to_string()))
+    }
+    /// 媒体自动播放策略（`--autoplay-policy`）
+    pub fn autoplay_policy(self, autoplay_policy: AutoplayPolicy) -> Self {
+        self.set(String::from("autoplay-policy"), Some(autoplay_policy.as_str().to_string()))
+    }
+    /// 禁用`GPU`硬件加速（`--disable-gpu`）
+    pub fn disable_gpu(self) -> Self {
+        self.set(String::from("disable-gpu"), None)
+    }
+    /// 无值的自定义开关。开关名不含前缀`--`。
+    pub fn switch(self, name: &str) -> Result<Self, BrowserArgsError> {
+        self.custom(name, None)
+    }
+    /// 有值的自定义开关。开关名不含前缀`--`；值会在渲染时被自动加引号与转义。
+    pub fn switch_value(self, name: &str, value: &str) -> Result<Self, BrowserArgsError> {
+        self.custom(name, Some(value))
+    }
+    /// 合并另一份启动参数。同名开关以`other`为准。
+    pub fn merge(self, other: BrowserArgs) -> Self {
+        let browser_args = other.enabled_features.iter().fold(self, |browser_args, feature| browser_args.enable_feature(feature));
+        let browser_args = other.disabled_features.iter().fold(browser_args, |browser_args, feature| browser_args.disable_feature(feature));
+        other.switches.into_iter().fold(browser_args, |browser_args, (name, value)| browser_args.set(name, value))
+    }
+    /// 解析并合并用户手写的原始启动参数。同名开关以原始启动参数为准。
+    pub fn raw(self, raw: &str) -> Result<Self, BrowserArgsError> {
+        Ok(self.merge(Self::parse(raw)?))
+    }
+    /// 被启用的特性
+    pub fn enabled_features(&self) -> &[String] {
+        &self.enabled_features
+    }
+    /// 被禁用的特性
+    pub fn disabled_features(&self) -> &[String] {
+        &self.disabled_features
+    }
+    /// 开关的值。外层`None`表示没有该开关；内层`None`表示该开关无值。
+    pub fn get(&self, name: &str) -> Option<Option<&str>> {
+        self.switches.iter().find(|(switch, _)| switch == name).map(|(_, value)| value.as_deref())
+    }
+    /// 按添加顺序遍历全部开关（不含特性列表）
+    pub fn switches(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.switches.iter().map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+    /// 是否没有任何启动参数
+    pub fn is_empty(&self) -> bool {
+        self.enabled_features.is_empty() && self.disabled_features.is_empty() && self.switches.is_empty()
+    }
+    fn custom(self, name: &str, value: Option<&str>) -> Result<Self, BrowserArgsError> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+            return Err(BrowserArgsError::InvalidSwitchName(name.to_string()));
+        }
+        Ok(match (name, value) {
+            (ENABLE_FEATURES, Some(features)) => self.enable_feature(features),
+            (DISABLE_FEATURES, Some(features)) => self.disable_feature(features),
+            _ => self.set(name.to_string(), value.map(str::to_string))
+        })
+    }
+    /// 添加开关。已有的同名开关会被原地覆盖。
+    fn set(mut self, name: String, value: Option<String>) -> Self {
+        match self.switches.iter_mut().find(|(switch, _)| *switch == name) {
+            Some((_, switch_value)) => *switch_value = value,
+            None => self.switches.push((name, value))
+        }
+        self
+    }
+}
+impl FromStr for BrowserArgs {
+    type Err = BrowserArgsError;
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        Self::parse(raw)
+    }
+}
+impl Display for BrowserArgs {
+    /// 渲染为`webview2::EnvironmentBuilder::with_additional_browser_arguments()`的实参
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let features = [(ENABLE_FEATURES, &self.enabled_features), (DISABLE_FEATURES, &self.disabled_features)];
+        let features = features.into_iter().filter(|(_, features)| !features.is_empty()).map(|(name, features)| (name, Some(features.join(","))));
+        let switches = self.switches.iter().map(|(name, value)| (name.as_str(), value.clone()));
+        for (index, (name, value)) in features.chain(switches).enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "--{name}")?;
+            if let Some(value) = value {
+                write!(f, "={}", quote(&value))?;
+            }
+        }
+        Ok(())
+    }
+}
+fn split_features(features: &str) -> impl Iterator<Item = &str> {
+    features.split(',').map(str::trim).filter(|feature| !feature.is_empty())
+}
+/// 按`CommandLineToArgvW`规则给参数值加引号与转义。无需引号的值被原样返回。
+fn quote(value: &str) -> String {
+    if !value.is_empty() && !value.chars().any(|c| c.is_whitespace() || c == '"') {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in value.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => { // 双引号之前的反斜杠需要加倍，双引号本身需要被转义
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            },
+            c => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2)); // 结尾引号之前的反斜杠也需要加倍
+    quoted.push('"');
+    quoted
+}
+/// 按`CommandLineToArgvW`规则将命令行拆分为参数
+fn split_command_line(command_line: &str) -> Result<Vec<String>, BrowserArgsError> {
+    let (mut args, mut arg) = (Vec::new(), None::<String>);
+    let (mut quoted, mut backslashes) = (false, 0);
+    for c in command_line.chars() {
+        match c {
+            '\\' => {
+                arg.get_or_insert_with(String::new);
+                backslashes += 1;
+            },
+            '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                arg.push_str(&"\\".repeat(backslashes / 2));
+                if backslashes % 2 == 1 { // 被转义的双引号
+                    arg.push('"');
+                } else {
+                    quoted = !quoted;
+                }
+                backslashes = 0;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if let Some(mut arg) = arg.take() {
+                    arg.push_str(&"\\".repeat(backslashes));
+                    args.push(arg);
+                }
+                backslashes = 0;
+            },
+            c => {
+                let arg = arg.get_or_insert_with(String::new);
+                arg.push_str(&"\\".repeat(backslashes));
+                arg.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    if quoted {
+        return Err(BrowserArgsError::UnterminatedQuote);
+    }
+    if let Some(mut arg) = arg {
+        arg.push_str(&"\\".repeat(backslashes));
+        args.push(arg);
+    }
+    Ok(args)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &str) {
+        let browser_args = BrowserArgs::new().switch_value("value", value).unwrap();
+        let rendered = browser_args.to_string();
+        assert_eq!(BrowserArgs::parse(&rendered), Ok(browser_args), "{value:?} 被渲染为 {rendered}");
+    }
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote(""), r#""""#);
+        assert_eq!(quote("with space"), r#""with space""#);
+        assert_eq!(quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(quote(r"C:\Program Files\"), r#""C:\Program Files\\""#);
+    }
+    #[test]
+    fn splits_like_command_line_to_argv() {
+        assert_eq!(split_command_line(r#"  --a   --b="x y"  "#), Ok(vec![String::from("--a"), String::from("--b=x y")]));
+        assert_eq!(split_command_line(r#"--a=\\\"q"#), Ok(vec![String::from(r#"--a=\"q"#)]));
+        assert_eq!(split_command_line(r"--dir=C:\temp\\"), Ok(vec![String::from(r"--dir=C:\temp\\")]));
+        assert_eq!(split_command_line(r#"--a="x\\" --b"#), Ok(vec![String::from(r"--a=x\"), String::from("--b")]));
+        assert_eq!(split_command_line(r#"--a="x"#), Err(BrowserArgsError::UnterminatedQuote));
+        assert_eq!(split_command_line(""), Ok(Vec::new()));
+    }
+    #[test]
+    fn round_trips_values() {
+        for value in ["plain", "", "with space", "tab\there", r#"say "hi""#, r#""leading"#, r#"a\"b"#, r#"a\\"b"#, r"C:\Program Files\", r"C:\Program Files\\", r"\\server\share", "http=proxy.local:8080;https=proxy.local:8443"] {
+            round_trip(value);
+        }
+    }
+    #[test]
+    fn renders_features_before_switches() {
+        let browser_args = BrowserArgs::new()
+            .disable_feature("msSmartScreenProtection")
+            .enable_feature("OverlayScrollbar, msWebView2EnableDraggableRegions")
+            .proxy_server("socks5://proxy.local:1080")
+            .switch_value("lang", "zh CN")
+            .unwrap();
+        assert_eq!(browser_args.to_string(), r#"--enable-features=OverlayScrollbar,msWebView2EnableDraggableRegions --disable-features=msSmartScreenProtection --proxy-server=socks5://proxy.local:1080 --lang="zh CN""#);
+    }
+    #[test]
+    fn merges_flag_values() {
+        let browser_args = BrowserArgs::new()
+            .disable_gpu()
+            .enable_feature("A,B")
+            .proxy_server("old.local:8080")
+            .raw(r#"--proxy-server=new.local:8080 --enable-features=C --disable-features=A --lang="zh-CN""#)
+            .unwrap();
+        assert_eq!(browser_args.get("proxy-server"), Some(Some("new.local:8080")));
+        assert_eq!(browser_args.get("disable-gpu"), Some(None));
+        assert_eq!(browser_args.get("lang"), Some(Some("zh-CN")));
+        assert_eq!(browser_args.enabled_features(), ["B", "C"]);
+        assert_eq!(browser_args.disabled_features(), ["A"]);
+        assert_eq!(browser_args.switches().map(|(name, _)| name).collect::<Vec<_>>(), vec!["disable-gpu", "proxy-server", "lang"]);
+        assert_eq!(BrowserArgs::parse(&browser_args.to_string()), Ok(browser_args));
+    }
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(BrowserArgs::parse("disable-gpu"), Err(BrowserArgsError::NotSwitch(String::from("disable-gpu"))));
+        assert_eq!(BrowserArgs::parse("--=value"), Err(BrowserArgsError::InvalidSwitchName(String::new())));
+        assert_eq!(BrowserArgs::new().switch("bad name"), Err(BrowserArgsError::InvalidSwitchName(String::from("bad name"))));
+        assert_eq!("--a \"".parse::<BrowserArgs>(), Err(BrowserArgsError::UnterminatedQuote));
+        assert!(BrowserArgs::parse("  ").unwrap().is_empty());
+    }
+}
@@ -1,119 +1,265 @@
 use ::bitflags::bitflags;
-use ::deferred_future::LocalDeferredFuture;
-use ::futures::FutureExt;
-use ::nwg::{self as nwg, ControlHandle, Event as NwgEvent, Frame, FrameBuilder, FrameFlags, NwgError};
-use ::webview2::{Controller, Environment, EnvironmentBuilder, Result as WvResult};
-use ::std::{cell::RefCell, path::Path, mem, rc::Rc, sync::atomic::{AtomicUsize, Ordering}};
-use ::winapi::{shared::windef::{HWND, RECT}, um::winuser::{GetClientRect, SC_RESTORE, WM_SYSCOMMAND, WS_BORDER, WS_DISABLED, WS_VISIBLE}};
-use super::{NwgResult, WebviewContainer};
+use ::futures::channel::mpsc;
+use ::nwg::{self as nwg, ControlHandle, Event as NwgEvent, Frame, FrameBuilder, FrameFlags};
+use ::std::{cell::{Cell, RefCell}, fmt::{self, Debug, Formatter}, path::PathBuf, mem, rc::{Rc, Weak}, sync::atomic::{AtomicUsize, Ordering}, time::{Duration, Instant}};
+use ::winapi::{shared::windef::HWND, um::{libloaderapi::{GetModuleHandleW, GetProcAddress}, winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS}, winuser::{GetClientRect, GetSystemMetrics, GetWindowLongW, IsIconic, IsWindowVisible, IsZoomed, GWL_STYLE, SM_CXBORDER, SM_CYBORDER, WS_BORDER, WS_DISABLED, WS_VISIBLE}}};
+use super::{WebviewContainer, assets::{AssetProvider, AssetServer}, browser_args::BrowserArgs, config::{ConfigError, EnvironmentConfig, FrameConfig, WebviewConfig}, dpi::{self, USER_DEFAULT_SCREEN_DPI}, backend::{BackendResult, Bounds, EnvironmentOptions, NavigationHandler, Webview2Backend, WebviewBackend}, error::WebviewContainerError, events, fallback::FallbackUi, host_event::{self, WindowEvent}, init::{self, CreatedHook}, init_script::{self, InitScript}, insets::{self, Insets}, navigate, navigation::{NavigationAction, NavigationPolicy}, resize::{self, ResizeDecision, ResizePolicy, ResizeScheduler}, rpc, log::{ContainerSpan, debug, error, trace}, runtime::{self, RuntimeFallback, RuntimeStatus}, suspend::{self, SuspendAction, SuspendPolicy, SuspendScheduler}, timer::{self, Delay}, window_state::{WindowMessage, WindowState, WindowStateMachine}};
 
 static HANDLE_ID: AtomicUsize = AtomicUsize::new(0xffff + 1);
+static CONTAINER_ID: AtomicUsize = AtomicUsize::new(1);
 
 bitflags! {
-    #[derive(PartialEq, Eq)]
+    /// 容器`nwg::Frame`的窗体样式
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub struct WebviewContainerFlags: u32 {
+        /// 无样式
         const NONE = 0;
+        /// 可见
         const VISIBLE = WS_VISIBLE;
+        /// 被禁用
         const DISABLED = WS_DISABLED;
+        /// 带边框
         const BORDER = WS_BORDER;
     }
 }
-pub struct WebviewContainerBuilder<'a> {
+/// [`WebviewContainer`]的构造器
+pub struct WebviewContainerBuilder<B: WebviewBackend = Webview2Backend> {
     window: Option<ControlHandle>,
-    webview_env: Option<Environment>,
-    webview_env_builder: EnvironmentBuilder<'a>,
+    backend: B,
+    webview_env: Option<B::Environment>,
+    environment: EnvironmentConfig,
+    browser_args: Option<BrowserArgs>,
+    asset_servers: Vec<AssetServer>,
+    navigation_policy: Option<NavigationPolicy>,
+    init_scripts: Vec<InitScript>,
+    runtime_fallback: Option<RuntimeFallback>,
+    rpc_timeout: Option<Duration>,
+    suspend_policy: SuspendPolicy,
+    low_memory_when_suspended: bool,
+    resize_policy: ResizePolicy,
+    insets: Insets,
+    created_hooks: Vec<CreatedHook<B>>,
     frame_builder: FrameBuilder
 }
-impl<'a> Default for WebviewContainerBuilder<'a> {
+impl<B: WebviewBackend> Debug for WebviewContainerBuilder<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewContainerBuilder")
+            .field("window", &self.window)
+            .field("has_webview_env", &self.webview_env.is_some())
+            .field("environment", &self.environment)
+            .field("browser_args", &self.browser_args)
+            .field("asset_servers", &self.asset_servers.len())
+            .field("init_scripts", &self.init_scripts.len())
+            .field("rpc_timeout", &self.rpc_timeout)
+            .field("suspend_policy", &self.suspend_policy)
+            .field("resize_policy", &self.resize_policy)
+            .field("insets", &self.insets)
+            .finish_non_exhaustive()
+    }
+}
+impl<B: WebviewBackend + Default> Default for WebviewContainerBuilder<B> {
     fn default() -> Self {
         Self {
             window: None,
+            backend: B::default(),
             webview_env: None,
-            webview_env_builder: Environment::builder(),
+            environment: EnvironmentConfig::default(),
+            browser_args: None,
+            asset_servers: Vec::new(),
+            navigation_policy: None,
+            init_scripts: Vec::new(),
+            runtime_fallback: None,
+            rpc_timeout: None,
+            suspend_policy: SuspendPolicy::default(),
+            low_memory_when_suspended: false,
+            resize_policy: ResizePolicy::default(),
+            insets: Insets::default(),
+            created_hooks: Vec::new(),
             frame_builder: Frame::builder()
         }
     }
 }
-impl<'a> WebviewContainerBuilder<'a> {
+impl<B: WebviewBackend> WebviewContainerBuilder<B> {
+    /// 由声明式配置构造。配置项会先被校验，且校验错误会指明不合法的配置项。比如，
+    /// ```ignore
+    /// let config = WebviewConfig::from_json(&fs::read_to_string("webview.json")?)?;
+    /// WebviewContainerBuilder::from_config(&config)?.window(&window).parent(&window).build(&mut webview_container)?;
+    /// ```
+    pub fn from_config(config: &WebviewConfig) -> Result<Self, ConfigError> where B: Default {
+        config.validate()?;
+        let FrameConfig { visible, disabled, border, size, position } = config.frame;
+        let mut flags = WebviewContainerFlags::NONE;
+        flags.set(WebviewContainerFlags::VISIBLE, visible);
+        flags.set(WebviewContainerFlags::DISABLED, disabled);
+        flags.set(WebviewContainerFlags::BORDER, border);
+        let mut builder = Self::default().flags(flags);
+        if let Some(size) = size {
+            builder = builder.size(size);
+        }
+        if let Some(position) = position {
+            builder = builder.position(position);
+        }
+        builder.environment = config.environment.clone();
+        Ok(builder)
+    }
     /// nwg::FrameBuilder 的配置项
-    pub fn flags(mut self, flags: WebviewContainerFlags) -> WebviewContainerBuilder<'a> {
+    pub fn flags(mut self, flags: WebviewContainerFlags) -> WebviewContainerBuilder<B> {
         let mut frame_flags = FrameFlags::NONE;
         if flags.contains(WebviewContainerFlags::BORDER) {
-            frame_flags = frame_flags | FrameFlags::BORDER;
+            frame_flags |= FrameFlags::BORDER;
         }
         if flags.contains(WebviewContainerFlags::DISABLED) {
-            frame_flags = frame_flags | FrameFlags::DISABLED;
+            frame_flags |= FrameFlags::DISABLED;
         }
         if flags.contains(WebviewContainerFlags::VISIBLE) {
-            frame_flags = frame_flags | FrameFlags::VISIBLE;
+            frame_flags |= FrameFlags::VISIBLE;
         }
         self.frame_builder = self.frame_builder.flags(frame_flags);
         self
     }
     /// nwg::FrameBuilder 的配置项
-    pub fn size(mut self, size: (i32, i32)) -> WebviewContainerBuilder<'a> {
+    pub fn size(mut self, size: (i32, i32)) -> WebviewContainerBuilder<B> {
         self.frame_builder = self.frame_builder.size(size);
         self
     }
     /// nwg::FrameBuilder 的配置项
-    pub fn position(mut self, pos: (i32, i32)) -> WebviewContainerBuilder<'a> {
+    pub fn position(mut self, pos: (i32, i32)) -> WebviewContainerBuilder<B> {
         self.frame_builder = self.frame_builder.position(pos);
         self
     }
     /// nwg::FrameBuilder 的配置项
-    pub fn enabled(mut self, e: bool) -> WebviewContainerBuilder<'a> {
+    pub fn enabled(mut self, e: bool) -> WebviewContainerBuilder<B> {
         self.frame_builder = self.frame_builder.enabled(e);
         self
     }
     /// nwg::FrameBuilder 的配置项
-    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> WebviewContainerBuilder<'a> {
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> WebviewContainerBuilder<B> {
         self.frame_builder = self.frame_builder.parent(p);
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
-    pub fn browser_executable_folder(mut self, browser_executable_folder: &'a Path) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_browser_executable_folder(browser_executable_folder);
+    pub fn browser_executable_folder(mut self, browser_executable_folder: impl Into<PathBuf>) -> Self {
+        self.environment.browser_executable_folder = Some(browser_executable_folder.into());
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
-    pub fn user_data_folder(mut self, user_data_folder: &'a Path) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_user_data_folder(user_data_folder);
+    pub fn user_data_folder(mut self, user_data_folder: impl Into<PathBuf>) -> Self {
+        self.environment.user_data_folder = Some(user_data_folder.into());
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
-    pub fn additional_browser_arguments(mut self, additional_browser_arguments: &'a str) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_additional_browser_arguments(additional_browser_arguments);
+    pub fn additional_browser_arguments(mut self, additional_browser_arguments: impl Into<String>) -> Self {
+        self.environment.additional_browser_arguments = Some(additional_browser_arguments.into());
+        self
+    }
+    /// 类型化的浏览器启动参数。若同时配置了`additional_browser_arguments`，那么后者会被解析并合并于其后（同名开关以后者为准）。
+    pub fn browser_args(mut self, browser_args: BrowserArgs) -> Self {
+        self.browser_args = Some(browser_args);
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
-    pub fn language(mut self, language: &'a str) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_language(language);
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.environment.language = Some(language.into());
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
-    pub fn target_compatible_browser_version(mut self, target_compatible_browser_version: &'a str) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_target_compatible_browser_version(target_compatible_browser_version);
+    pub fn target_compatible_browser_version(mut self, target_compatible_browser_version: impl Into<String>) -> Self {
+        self.environment.target_compatible_browser_version = Some(target_compatible_browser_version.into());
         self
     }
     /// webview2::EnvironmentBuilder 的配置项
     pub fn allow_single_sign_on_using_osprimary_account(mut self, allow_single_sign_on_using_osprimary_account: bool) -> Self {
-        self.webview_env_builder = self.webview_env_builder.with_allow_single_sign_on_using_osprimary_account(allow_single_sign_on_using_osprimary_account);
+        self.environment.allow_single_sign_on_using_osprimary_account = allow_single_sign_on_using_osprimary_account;
         self
     }
     /// webview2::EnvironmentBuilder 的配置项。
     /// 获取当前浏览器实例版本字符串
-    pub fn get_available_browser_version_string(&self) -> WvResult<String> {
-        self.webview_env_builder.get_available_browser_version_string()
+    pub fn get_available_browser_version_string(&self) -> BackendResult<String> {
+        self.backend.get_available_browser_version_string(&self.environment.options())
+    }
+    /// 探测`WebView2 Runtime`的可用性。版本要求来自`target_compatible_browser_version`配置项。
+    pub fn runtime_status(&self) -> RuntimeStatus {
+        runtime::probe(&self.backend, &self.environment.options())
     }
     // 其它
-    pub fn window<C: Into<ControlHandle>>(mut self, window: C) -> WebviewContainerBuilder<'a> {
+    /// 主窗体。`webview`控件的显隐跟随它的最小化、隐藏与还原。
+    pub fn window<C: Into<ControlHandle>>(mut self, window: C) -> WebviewContainerBuilder<B> {
         self.window = Some(window.into());
         self
     }
-    pub fn webview_env<E: Into<Environment>>(mut self, webview_env: E) -> WebviewContainerBuilder<'a> {
+    /// 复用既有的浏览器环境。比如，另一个`WebviewContainer`的`WebviewHandles::environment`。
+    pub fn webview_env<E: Into<B::Environment>>(mut self, webview_env: E) -> WebviewContainerBuilder<B> {
         self.webview_env = Some(webview_env.into());
         self
     }
+    /// 经由虚拟主机`https://<host>/*`向网页提供`provider`里的静态资源。比如，
+    /// `.serve_assets("app.local", EmbeddedAssets::new([("index.html", include_bytes!("../ui/index.html").as_slice())]))`
+    /// 之后，再导航至`https://app.local/`即可。多次调用可以挂载多个虚拟主机。
+    pub fn serve_assets<P: AssetProvider>(mut self, host: impl Into<String>, provider: P) -> WebviewContainerBuilder<B> {
+        self.asset_servers.push(AssetServer::new(host, provider));
+        self
+    }
+    /// 导航策略。它同时约束页内导航（`NavigationStarting`）与新窗体请求（`NewWindowRequested`）。
+    /// 被判定为`OpenExternal`的地址会被转交给系统默认浏览器（或协议关联程序）打开。
+    pub fn navigation_policy(mut self, navigation_policy: NavigationPolicy) -> WebviewContainerBuilder<B> {
+        self.navigation_policy = Some(navigation_policy);
+        self
+    }
+    /// 追加初始化脚本。它在每个网页的任何脚本之前被执行，所以适合注入`polyfill`与桥接垫片。多个初始化脚本按追加的顺序被执行。
+    /// 注入得到的脚本`id`可由`WebviewContainer::init_script_id()`读取，并被交给`WebviewContainer::remove_init_script()`移除。
+    pub fn init_script(mut self, script: impl Into<String>) -> WebviewContainerBuilder<B> {
+        self.init_scripts.push(InitScript::Source(script.into()));
+        self
+    }
+    /// 追加初始化脚本文件。该文件在`build()`时被读取；读取失败会得到`WebviewContainerError::InitScriptFile`。
+    pub fn init_script_file(mut self, path: impl Into<PathBuf>) -> WebviewContainerBuilder<B> {
+        self.init_scripts.push(InitScript::File(path.into()));
+        self
+    }
+    /// `WebView2 Runtime`未安装或版本过低时，在容器内绘制提示文字与下载按钮，而不再初始化`webview`控件。
+    /// 此时，`ready_fut()`会被决议为`WebviewContainerError::RuntimeUnavailable`。复用既有的`webview_env`时，不做探测。
+    pub fn runtime_fallback(mut self, runtime_fallback: RuntimeFallback) -> WebviewContainerBuilder<B> {
+        self.runtime_fallback = Some(runtime_fallback);
+        self
+    }
+    /// 启用`JSON-RPC`调用：向每个网页注入`window.nwg.invoke()`垫片，并由`WebviewContainer::rpc_server()`服务循环执行
+    /// `WebviewContainer::rpc_handler()`与`WebviewContainer::expose()`注册的处理函数。默认不启用，所以网页里也没有该垫片。
+    ///
+    /// `timeout`既是垫片的默认超时，也是`Rust`端处理函数的执行时限。比如，[`DEFAULT_RPC_TIMEOUT`](crate::DEFAULT_RPC_TIMEOUT)。
+    pub fn rpc(mut self, timeout: Duration) -> WebviewContainerBuilder<B> {
+        self.rpc_timeout = Some(timeout);
+        self
+    }
+    /// 追加`webview`控件初始化成功之后的回调
+    pub(crate) fn on_created(mut self, hook: CreatedHook<B>) -> WebviewContainerBuilder<B> {
+        self.created_hooks.push(hook);
+        self
+    }
+    /// 何时挂起不可见的`webview`控件。默认从不挂起。
+    pub fn suspend_policy(mut self, suspend_policy: SuspendPolicy) -> WebviewContainerBuilder<B> {
+        self.suspend_policy = suspend_policy;
+        self
+    }
+    /// 挂起`webview`控件时，是否也调低其内存用量目标。恢复时，内存用量目标也被恢复。
+    pub fn low_memory_when_suspended(mut self, low_memory_when_suspended: bool) -> WebviewContainerBuilder<B> {
+        self.low_memory_when_suspended = low_memory_when_suspended;
+        self
+    }
+    /// 如何同步`webview`控件的边界矩形。默认每个`OnResize`事件都立即同步；`ResizePolicy::Coalesced`则每个时间间隔至多同步一次，并在拖拽缩放结束时补齐最后一次。
+    pub fn resize_policy(mut self, resize_policy: ResizePolicy) -> WebviewContainerBuilder<B> {
+        self.resize_policy = resize_policy;
+        self
+    }
+    /// `webview`控件在容器客户区内的留白。留白处可被用来叠放原生控件。
+    pub fn insets(mut self, insets: Insets) -> WebviewContainerBuilder<B> {
+        self.insets = insets;
+        self
+    }
+    /// 替换后端实例。比如，注入一个预先克隆的`RecordingBackend`，以便在构造之后读取它的调用日志。
+    pub fn backend(mut self, backend: B) -> WebviewContainerBuilder<B> {
+        self.backend = backend;
+        self
+    }
     /// 1. 在多 TAB 应用程序场景下，重用`webview2::Environment(i.e. CoreWebView2Environment)`实例。
     ///    于是，由相同`CoreWebView2Environment`实例构造的多`webview`将共用相同的
     ///     1. 浏览器进程
@@ -126,14 +272,36 @@ impl<'a> WebviewContainerBuilder<'a> {
     ///     4. 浏览器版本号
     ///     5. 浏览器安装目录
     ///     6. 是否允许单点登录
-    pub fn build(self, webview_container: &mut WebviewContainer) -> NwgResult<()> {
+    pub fn build(self, webview_container: &mut WebviewContainer<B>) -> Result<(), WebviewContainerError> {
         // 主窗体
-        let window_handle = self.window.ok_or(NwgError::initialization("window 配置项代表了主窗体。它是必填项"))?;
-        let window_hwnd = window_handle.hwnd().ok_or(NwgError::control_create("主窗体不是有效的 Win32 COM 控件"))?;
+        let window_handle = self.window.ok_or(WebviewContainerError::MissingWindow)?;
+        let window_hwnd = window_handle.hwnd().ok_or(WebviewContainerError::InvalidWindowHandle)?;
+        // 初始化脚本
+        let init_scripts = init_script::load(self.init_scripts)?;
+        webview_container.init_script_ids.replace(vec![None; init_scripts.len()]);
         // webview 容器
-        self.frame_builder.build(&mut webview_container.frame.borrow_mut())?;
-        let frame_hwnd = webview_container.frame.borrow().handle.hwnd().ok_or(NwgError::control_create("Webview 容器控件 Frame 初始化失败"))?;
+        self.frame_builder.build(&mut webview_container.frame.borrow_mut()).map_err(|err| WebviewContainerError::FrameBuild(err.to_string()))?;
+        let frame_hwnd = webview_container.frame.borrow().handle.hwnd().ok_or(WebviewContainerError::FrameBuild(String::from("Frame 不是有效的 Win32 窗体")))?;
+        webview_container.backend = Rc::new(self.backend);
+        webview_container.span = ContainerSpan::new(CONTAINER_ID.fetch_add(1, Ordering::Relaxed), frame_hwnd.cast());
+        let _span = webview_container.span.enter();
+        // 合并类型化的与原始的浏览器启动参数
+        let env_options = self.environment.options();
+        let additional_browser_arguments = self.browser_args.map(|browser_args|
+            browser_args.raw(env_options.additional_browser_arguments.unwrap_or_default()).map(|browser_args| browser_args.to_string())
+        ).transpose().map_err(WebviewContainerError::BrowserArguments)?;
+        // 仅在这里才将配置项借给 webview2::EnvironmentBuilder
+        let env_options = EnvironmentOptions {
+            additional_browser_arguments: additional_browser_arguments.as_deref().or(env_options.additional_browser_arguments),
+            ..env_options
+        };
         macro_rules! unpack {
+            ($variable: ident) => {
+                match $variable.upgrade() {
+                    Some(variable) => variable,
+                    None => return
+                }
+            };
             ($variable: ident, $return: expr) => {
                 match $variable.upgrade() {
                     Some(variable) => variable,
@@ -141,115 +309,463 @@ impl<'a> WebviewContainerBuilder<'a> {
                 }
             };
         }
+        let rpc_timeout = self.rpc_timeout;
+        if let Some(rpc_timeout) = rpc_timeout { // RPC 请求排队等待 WebviewContainer::rpc_server() 服务循环的处理
+            webview_container.rpc.set_timeout(rpc_timeout);
+            webview_container.rpc_enabled = true;
+            let (rpc_sender, rpc_receiver) = mpsc::unbounded();
+            webview_container.rpc_requests.replace(Some(rpc_receiver));
+            webview_container.router.set_handler(String::from(rpc::RPC_CHANNEL), Box::new(move |payload| {
+                rpc_sender.unbounded_send(payload).ok();
+            }));
+        }
+        webview_container.environment = self.environment.clone();
+        webview_container.dpi.set(dpi_for_window(window_hwnd));
+        webview_container.resize.replace(ResizeScheduler::new(self.resize_policy));
+        webview_container.insets.set(self.insets);
+        let resize_driver = ResizeDriver {
+            backend: Rc::downgrade(&webview_container.backend),
+            webview_ctrl: Rc::downgrade(&webview_container.webview_ctrl),
+            frame: Rc::downgrade(&webview_container.frame),
+            frame_hwnd,
+            insets: Rc::downgrade(&webview_container.insets),
+            scheduler: Rc::downgrade(&webview_container.resize),
+            timer: Rc::downgrade(&webview_container.resize_timer)
+        };
+        webview_container.window_state.replace(initial_window_state(window_hwnd)); // 主窗体可能在被最小化（或隐藏）之后，才构造 webview 容器
+        webview_container.suspend.replace({ // 挂起调度器
+            let mut scheduler = SuspendScheduler::new(self.suspend_policy);
+            scheduler.on_power(is_on_battery(), Instant::now());
+            scheduler
+        });
+        let suspend_driver = SuspendDriver {
+            backend: Rc::downgrade(&webview_container.backend),
+            webview: Rc::downgrade(&webview_container.webview),
+            scheduler: Rc::downgrade(&webview_container.suspend),
+            timer: Rc::downgrade(&webview_container.suspend_timer),
+            low_memory_when_suspended: self.low_memory_when_suspended
+        };
+        let runtime_unavailable = self.runtime_fallback.filter(|_| self.webview_env.is_none())
+            .map(|runtime_fallback| (runtime::probe(&*webview_container.backend, &env_options), runtime_fallback))
+            .filter(|(runtime_status, _)| !runtime_status.is_available());
         // webview 组件构造异步锁
-        webview_container.ready_fut.replace({
-            let webview_ctrl = Rc::clone(&webview_container.webview_ctrl);
-            let webview_ready_future = LocalDeferredFuture::default();
-            let defer = webview_ready_future.defer();
+        webview_container.ready_fut.replace(if let Some((runtime_status, runtime_fallback)) = runtime_unavailable { // WebView2 Runtime 不可用时，在容器内绘制替代界面
+            let fallback_ui = FallbackUi::build(&webview_container.frame.borrow(), runtime_fallback, runtime_status).map_err(|err| WebviewContainerError::FrameBuild(err.to_string()))?;
+            webview_container.fallback.replace(Some(fallback_ui));
+            debug!("[WebviewContainerBuilder][build]{runtime_status}");
+            init::settled(Err(WebviewContainerError::RuntimeUnavailable(runtime_status)))
+        } else {
             let frame = Rc::clone(&webview_container.frame);
-            let build = move |env: Environment| env.clone().create_controller(frame_hwnd, move |webview_ctrl_core| {
-                let webview_ctrl_core = webview_ctrl_core?;
-                let webview = webview_ctrl_core.get_webview()?;
-                align_webview_2_container(&webview_ctrl_core, frame, frame_hwnd)?;
-                #[cfg(debug_assertions)]
-                println!("[WebviewContainerBuilder][build]Webview 实例化成功");
-                webview_ctrl.borrow_mut().replace(webview_ctrl_core.clone());
-                defer.borrow_mut().complete((env, webview_ctrl_core, webview));
+            let router = Rc::downgrade(&webview_container.router);
+            let asset_servers = self.asset_servers;
+            let navigation_policy = self.navigation_policy.map(Rc::new);
+            let events = Rc::downgrade(&webview_container.events);
+            let navigations = Rc::downgrade(&webview_container.navigations);
+            let init_script_ids = Rc::downgrade(&webview_container.init_script_ids);
+            let host_proxies = Rc::downgrade(&webview_container.host_proxies);
+            let (scale_mode, dpi) = (Rc::downgrade(&webview_container.scale_mode), Rc::downgrade(&webview_container.dpi));
+            let insets = Rc::downgrade(&webview_container.insets);
+            let created_hooks = self.created_hooks;
+            let window_state = Rc::downgrade(&webview_container.window_state);
+            let suspend_driver = suspend_driver.clone();
+            let span = webview_container.span.clone();
+            init::initialize(&webview_container.backend, self.webview_env, &env_options, frame_hwnd.cast(), &webview_container.webview_ctrl, &webview_container.webview, move |backend, handles| {
+                let is_frame_visible = frame.borrow().visible();
+                align_webview_2_container(&**backend, &handles.controller, frame, frame_hwnd, insets.upgrade().map(|insets| insets.get()).unwrap_or_default())?;
+                if let (Some(scale_mode), Some(dpi)) = (scale_mode.upgrade(), dpi.upgrade()) { // 在 webview 控件就绪之前被固定的缩放比。缩放比同步失败不影响 webview 控件的使用，所以仅记录之。
+                    if !scale_mode.get().detects_monitor_scale_changes() {
+                        dpi::apply(&**backend, &handles.controller, scale_mode.get(), dpi.get()).map_err(|err| error!("[WebviewContainerBuilder][build]{err}")).ok();
+                    }
+                }
+                { // 按 channel 将网页消息分发给 WebviewBridge 的处理函数
+                    let span = span.clone();
+                    backend.add_web_message_received(&handles.webview, Box::new(move |json| {
+                        let _span = span.enter();
+                        let router = unpack!(router);
+                        if let Err(err) = router.route(&json) {
+                            error!("[WebMessageReceived]{err}");
+                        }
+                    })).map_err(WebviewContainerError::WebMessage)?;
+                }
+                if let Some(rpc_timeout) = rpc_timeout { // 注入 window.nwg.invoke() 垫片
+                    let span = span.clone();
+                    backend.add_script_to_execute_on_document_created(&handles.webview, &rpc::invoke_shim(rpc_timeout), Box::new(move |id| {
+                        let _span = span.enter();
+                        match id {
+                            Ok(id) => trace!("[WebviewContainerBuilder][build]RPC 垫片脚本 {id}"),
+                            Err(err) => error!("[WebviewContainerBuilder][build]RPC 垫片注入失败：{err}")
+                        }
+                    })).map_err(WebviewContainerError::ScriptInjection)?;
+                }
+                if let Some(host_proxies) = host_proxies.upgrade() { // 注入在 webview 控件就绪之前被暴露的宿主对象的代理脚本
+                    for proxy_script in host_proxies.take() {
+                        let span = span.clone();
+                        backend.add_script_to_execute_on_document_created(&handles.webview, &proxy_script, Box::new(move |id| {
+                            let _span = span.enter();
+                            match id {
+                                Ok(id) => trace!("[WebviewContainerBuilder][build]宿主对象代理脚本 {id}"),
+                                Err(err) => error!("[WebviewContainerBuilder][build]宿主对象代理脚本注入失败：{err}")
+                            }
+                        })).map_err(WebviewContainerError::ScriptInjection)?;
+                    }
+                }
+                init_script::inject(&**backend, &handles.webview, &init_scripts, &init_script_ids, &span)?; // 注入初始化脚本
+                for asset_server in asset_servers { // 虚拟主机的静态资源服务
+                    let uri_filter = asset_server.uri_filter();
+                    let span = span.clone();
+                    backend.add_web_resource_requested(&handles.environment, &handles.webview, &uri_filter, Box::new(move |request| {
+                        let response = asset_server.serve(request);
+                        let _span = span.enter();
+                        trace!("[WebResourceRequested]{} {} -> {:?}", request.method, request.uri, response.as_ref().map(|response| response.status));
+                        response
+                    })).map_err(WebviewContainerError::ResourceInterception)?;
+                }
+                if let Some(navigation_policy) = navigation_policy { // 导航策略
+                    let handler = |event: &'static str| -> NavigationHandler {
+                        let (navigation_policy, backend, span) = (Rc::clone(&navigation_policy), Rc::clone(backend), span.clone());
+                        Box::new(move |uri| {
+                            let action = navigation_policy.decide(uri);
+                            let _span = span.enter();
+                            debug!("[{event}]{uri} -> {action:?}");
+                            if action == NavigationAction::OpenExternal {
+                                if let Err(err) = backend.open_external(uri) {
+                                    error!("[{event}]{err}");
+                                }
+                            }
+                            action != NavigationAction::Allow
+                        })
+                    };
+                    backend.add_navigation_starting(&handles.webview, handler("NavigationStarting")).map_err(WebviewContainerError::NavigationHook)?;
+                    backend.add_new_window_requested(&handles.webview, handler("NewWindowRequested")).map_err(WebviewContainerError::NavigationHook)?;
+                }
+                if let Some(navigations) = navigations.upgrade() { // 关联 WebviewContainer::navigate() 与导航事件
+                    navigate::attach(&**backend, &handles.webview, &navigations).map_err(WebviewContainerError::NavigationHook)?;
+                }
+                if let Some(events) = events.upgrade() { // 将网页事件广播给 WebviewContainer::events() 事件流
+                    events::forward_events(&**backend, &handles.webview, &events).map_err(WebviewContainerError::EventSubscription)?;
+                }
+                created_hooks.into_iter().try_for_each(|hook| hook(backend, handles))?;
+                { // 在 webview 组件就绪之前，挂起调度器错过了主窗体的可见性变化，所以补上当前的可见性
+                    let is_visible = window_state.upgrade().is_some_and(|window_state| window_state.borrow().state().is_visible()) && is_frame_visible;
+                    suspend_driver.replay(&**backend, &handles.webview, is_visible);
+                }
+                let _span = span.enter();
+                debug!("[WebviewContainerBuilder][build]Webview 实例化成功");
                 Ok(())
-            });
-            if let Some(webview_env) = self.webview_env {
-                build(webview_env)
-            } else {
-                self.webview_env_builder.build(move |env| build(env?))
-            }.map(|_| webview_ready_future.shared()).map_err(|err|NwgError::control_create(err.to_string()))
-        }?);
+            })?
+        });
         webview_container.event_handle.replace({ // 因为【主窗体】直接就是 webview 的父组件，所以传递主窗体的事件给 webview 组件。
+            let backend = Rc::downgrade(&webview_container.backend);
             let webview_ctrl = Rc::downgrade(&webview_container.webview_ctrl);
             let is_closing = Rc::downgrade(&webview_container.is_closing);
             let frame = Rc::downgrade(&webview_container.frame);
+            let fallback = Rc::downgrade(&webview_container.fallback);
+            let resize_driver = resize_driver.clone();
+            let span = webview_container.span.clone();
             nwg::full_bind_event_handler(&window_handle, move |event, _data, handle| {
-                let is_closing = unpack!(is_closing, ());
+                let _span = span.enter();
+                let is_closing = unpack!(is_closing);
                 if *is_closing.borrow() {
                     return;
                 }
                 if let ControlHandle::Hwnd(hwnd) = handle {
                     if window_hwnd == hwnd { // 事件源是主窗体
-                        let webview_ctrl = unpack!(webview_ctrl, ());
+                        let backend = unpack!(backend);
+                        let webview_ctrl = unpack!(webview_ctrl);
                         match event {
-                            // 当主窗体被最小化时，关闭 webview 组件，以减小空耗。
-                            NwgEvent::OnWindowMinimize => webview_ctrl.borrow().as_ref().and_then(|controller| {
-                                #[cfg(debug_assertions)]
-                                println!("[WebviewContainer][OnWindowMinimize]Webview 被挂起了");
-                                controller.put_is_visible(false).map_err(|err| eprintln!("[OnWindowMinimize]{err}")).ok()
-                            }),
                             // 当主窗体被移动时，徒手传递位移事件给 webview 组件。
-                            NwgEvent::OnMove => webview_ctrl.borrow().as_ref().and_then(|controller|
-                                controller.notify_parent_window_position_changed().map_err(|err| eprintln!("[OnMove]{err}")).ok()
-                            ),
+                            NwgEvent::OnMove => host_event::route(&*backend, webview_ctrl.borrow().as_ref(), WindowEvent::Moved).map_err(|err| error!("[OnMove]{err}")).ok(),
                             _ => Some(())
                         };
                     } else if frame_hwnd == hwnd { // 事件源是 webview 容器 Frame
-                        let webview_ctrl = unpack!(webview_ctrl, ());
+                        let backend = unpack!(backend);
+                        let webview_ctrl = unpack!(webview_ctrl);
                         match event {
-                            NwgEvent::OnResize => { // 当主窗体被调整大小时，徒手传递尺寸调整事件给 webview 组件。
-                                let frame = unpack!(frame, ());
-                                webview_ctrl.borrow().as_ref().and_then(move |controller| {
-                                    align_webview_2_container(controller, frame, frame_hwnd).map_err(|err| eprintln!("[OnResize|OnWindowMaximize]{err}")).ok()
-                                })
+                            NwgEvent::OnResize => { // 当主窗体被调整大小时，徒手传递尺寸调整事件给 webview 组件。拖拽缩放期间的连串事件被合并。
+                                let frame = unpack!(frame);
+                                let fallback = unpack!(fallback);
+                                if let Some(fallback_ui) = fallback.borrow().as_ref() {
+                                    let (width, height) = frame.borrow().size();
+                                    fallback_ui.layout(width, height);
+                                }
+                                resize_driver.on_resize();
+                                Some(())
                             },
-                            NwgEvent::OnMove => webview_ctrl.borrow().as_ref().and_then(|controller|
-                                controller.notify_parent_window_position_changed().map_err(|err| eprintln!("[OnMove]{err}")).ok()
-                            ),
+                            NwgEvent::OnMove => host_event::route(&*backend, webview_ctrl.borrow().as_ref(), WindowEvent::Moved).map_err(|err| error!("[OnMove]{err}")).ok(),
                             _ => Some(())
                         };
+                    } else if matches!(event, NwgEvent::OnButtonClick) { // 事件源可能是替代界面的下载按钮
+                        let backend = unpack!(backend);
+                        let fallback = unpack!(fallback);
+                        if let Some(download_url) = fallback.borrow().as_ref().and_then(|fallback_ui| fallback_ui.download_url(hwnd)) {
+                            backend.open_external(download_url).map_err(|err| error!("[OnButtonClick]{err}")).ok();
+                        };
                     }
                 }
             })
         });
-        webview_container.raw_event_handle.replace({ // nwg 封闭里漏掉了【主窗体】的 restore 事件，所以这里直接经由 winapi crate 的原始接口挂事件处理函数了。
+        webview_container.raw_event_handle.replace({ // nwg 封装里漏掉了【主窗体】的 restore 与 show/hide 事件，所以这里直接经由 winapi crate 的原始接口挂事件处理函数，并以状态机决定 webview 组件的可见性。
             let handle_id = loop {
                 let handle_id = HANDLE_ID.fetch_add(1, Ordering::Relaxed);
                 if !nwg::has_raw_handler(&window_handle, handle_id) {
                     break handle_id;
                 }
             };
+            let backend = Rc::downgrade(&webview_container.backend);
             let webview_ctrl = Rc::downgrade(&webview_container.webview_ctrl);
             let is_closing = Rc::downgrade(&webview_container.is_closing);
+            let frame = Rc::downgrade(&webview_container.frame);
+            let window_state = Rc::downgrade(&webview_container.window_state);
+            let (scale_mode, dpi) = (Rc::downgrade(&webview_container.scale_mode), Rc::downgrade(&webview_container.dpi));
+            let insets = Rc::downgrade(&webview_container.insets);
+            let suspend_driver = suspend_driver.clone();
+            let span = webview_container.span.clone();
             nwg::bind_raw_event_handler(&window_handle, handle_id, move |_, msg, w, _| {
-                let webview_ctrl = unpack!(webview_ctrl, None);
+                let _span = span.enter();
                 let is_closing = unpack!(is_closing, None);
-                if !*is_closing.borrow() && (WM_SYSCOMMAND, SC_RESTORE) == (msg, w as usize) {
-                    #[cfg(debug_assertions)]
-                    println!("[WebviewContainer][OnWindowMinimize]Webview 被恢复了");
-                    webview_ctrl.borrow().as_ref().and_then(|controller| // 当主窗体被还原时，打开 webview 组件。
-                        controller.put_is_visible(true).map_err(|err| eprintln!("[OnWindowRestore]{err}")).ok()
-                    );
+                if *is_closing.borrow() {
+                    return None;
+                }
+                if resize::is_exit_size_move(msg) { // 拖拽缩放结束了，补齐最后一次尺寸同步
+                    resize_driver.flush();
+                    return None;
                 }
+                if suspend::is_power_status_change(msg, w) { // 电源状态变化了
+                    suspend_driver.drive(|scheduler, now| scheduler.on_power(is_on_battery(), now));
+                    return None;
+                }
+                if let Some(new_dpi) = dpi::dpi_changed(msg, w) { // 主窗体被移到了另一个缩放比的显示器上。移动主窗体至系统建议的矩形是主程序的事，这里仅同步缩放比与边界矩形。
+                    let (scale_mode, dpi) = (unpack!(scale_mode, None), unpack!(dpi, None));
+                    let old_dpi = dpi.replace(new_dpi);
+                    debug!("[WebviewContainer][DpiChanged]{old_dpi} -> {new_dpi}");
+                    let (backend, webview_ctrl, frame, insets) = (unpack!(backend, None), unpack!(webview_ctrl, None), unpack!(frame, None), unpack!(insets, None));
+                    webview_ctrl.borrow().as_ref().and_then(|controller| {
+                        dpi::apply(&*backend, controller, scale_mode.get(), new_dpi).map_err(|err| error!("[DpiChanged]{err}")).ok();
+                        align_webview_2_container(&*backend, controller, frame, frame_hwnd, insets.get()).map_err(|err| error!("[DpiChanged]{err}")).ok()
+                    });
+                    return None;
+                }
+                let message = WindowMessage::from_raw(msg, w)?;
+                let window_state = unpack!(window_state, None);
+                let transition = window_state.borrow_mut().apply(message)?;
+                debug!("[WebviewContainer][WindowState]{:?} -> {:?}", transition.from, transition.to);
+                let frame = unpack!(frame, None);
+                let is_frame_visible = frame.borrow().visible(); // 容器自己被隐藏了，就不恢复 webview 组件了。
+                let is_visible = transition.to.is_visible() && is_frame_visible;
+                let (backend, webview_ctrl) = (unpack!(backend, None), unpack!(webview_ctrl, None));
+                host_event::route(&*backend, webview_ctrl.borrow().as_ref(), WindowEvent::StateChanged { transition, is_frame_visible }).map_err(|err| error!("[WindowState]{err}")).ok();
+                suspend_driver.drive(|scheduler, now| scheduler.on_visibility(is_visible, now));
                 None
-            })?
+            }).map_err(|err| WebviewContainerError::EventBinding(err.to_string()))?
         });
-        #[cfg(debug_assertions)]
-        println!("[WebviewContainerBuilder][build]同步执行结束");
+        debug!("[WebviewContainerBuilder][build]同步执行结束");
         Ok(())
     }
 }
-/// 调整 webview 控件的大小·至·包含该 webview 控件的容器元素的最新大小
-fn align_webview_2_container(webview_ctrl: &Controller, frame: Rc<RefCell<Frame>>, frame_hwnd: HWND) -> WvResult<()> {
-    let (successful, mut rect) = unsafe {
+/// 调整 webview 控件的大小·至·包含该 webview 控件的容器元素的最新大小，再扣除留白
+pub(crate) fn align_webview_2_container<B: WebviewBackend>(backend: &B, webview_ctrl: &B::Controller, frame: Rc<RefCell<Frame>>, frame_hwnd: HWND, insets: Insets) -> Result<(), WebviewContainerError> {
+    let (successful, rect) = unsafe {
         let mut rect = mem::zeroed();
         let successful = GetClientRect(frame_hwnd, &mut rect);
         (successful, rect)
     };
-    if successful == 0 {
-        let position = frame.borrow().position();
+    let client = if successful == 0 { // 由容器的外部尺寸推算客户区。带边框的容器还需扣除边框。
+        let has_border = unsafe { GetWindowLongW(frame_hwnd, GWL_STYLE) } as u32 & WS_BORDER != 0;
+        let border = if has_border {
+            unsafe { (GetSystemMetrics(SM_CXBORDER), GetSystemMetrics(SM_CYBORDER)) }
+        } else {
+            (0, 0)
+        };
         let size = frame.borrow().size();
-        rect = RECT {
-            top: position.1,
-            left: position.0,
-            right: size.0 as i32,
-            bottom: size.1 as i32
+        insets::client_bounds(size, (border.0.max(0) as u32, border.1.max(0) as u32))
+    } else {
+        Bounds::from(rect)
+    };
+    trace!("[align_webview_2_container]client={{top: {}, left: {}, width: {}, height: {} }}, insets={insets:?}", client.top, client.left, client.width(), client.height());
+    host_event::route(backend, Some(webview_ctrl), WindowEvent::Resized { client, insets }).map_err(WebviewContainerError::BoundsUpdate)
+}
+/// 以主窗体的当前状态为初始状态的状态机。被隐藏的主窗体在重新显示时，回到其最小化（或最大化）状态。
+fn initial_window_state(window_hwnd: HWND) -> WindowStateMachine {
+    let shown = if unsafe { IsIconic(window_hwnd) } != 0 {
+        WindowState::Minimized
+    } else if unsafe { IsZoomed(window_hwnd) } != 0 {
+        WindowState::Maximized
+    } else {
+        WindowState::Normal
+    };
+    let mut window_state = WindowStateMachine::new(shown);
+    if unsafe { IsWindowVisible(window_hwnd) } == 0 {
+        window_state.apply(WindowMessage::ShowWindow(false));
+    }
+    window_state
+}
+/// 主窗体所在显示器的`DPI`。`GetDpiForWindow`仅在`Windows 10 1607`及更新的系统上才有，所以经由`GetProcAddress`动态地查找它。找不到时，视为`100%`缩放比。
+fn dpi_for_window(window_hwnd: HWND) -> u32 {
+    type GetDpiForWindow = unsafe extern "system" fn(HWND) -> u32;
+    let user32 = "user32.dll".encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+    let module = unsafe { GetModuleHandleW(user32.as_ptr()) };
+    if module.is_null() {
+        return USER_DEFAULT_SCREEN_DPI;
+    }
+    let get_dpi_for_window = unsafe { GetProcAddress(module, c"GetDpiForWindow".as_ptr()) };
+    if get_dpi_for_window.is_null() {
+        return USER_DEFAULT_SCREEN_DPI;
+    }
+    let get_dpi_for_window: GetDpiForWindow = unsafe { mem::transmute(get_dpi_for_window) };
+    match unsafe { get_dpi_for_window(window_hwnd) } {
+        0 => USER_DEFAULT_SCREEN_DPI, // 无效的窗体句柄
+        dpi => dpi
+    }
+}
+/// 计算机是否由电池供电。若电源状态未知，则视为接通了电源。
+fn is_on_battery() -> bool {
+    let mut power_status: SYSTEM_POWER_STATUS = unsafe { mem::zeroed() };
+    let has_power_status = unsafe { GetSystemPowerStatus(&mut power_status) } != 0;
+    has_power_status && power_status.ACLineStatus == 0
+}
+/// 执行挂起调度器的决定，并为`SuspendPolicy::AfterHidden`策略预约定时器
+struct SuspendDriver<B: WebviewBackend> {
+    backend: Weak<B>,
+    webview: Weak<RefCell<Option<B::WebView>>>,
+    scheduler: Weak<RefCell<SuspendScheduler>>,
+    timer: Weak<RefCell<Option<Delay>>>,
+    low_memory_when_suspended: bool
+}
+impl<B: WebviewBackend> Clone for SuspendDriver<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: Weak::clone(&self.backend),
+            webview: Weak::clone(&self.webview),
+            scheduler: Weak::clone(&self.scheduler),
+            timer: Weak::clone(&self.timer),
+            low_memory_when_suspended: self.low_memory_when_suspended
+        }
+    }
+}
+impl<B: WebviewBackend> SuspendDriver<B> {
+    /// 以`decide`向调度器输入一个事件，并执行它的决定。webview 组件还未就绪时，不做决定，而由[`SuspendDriver::replay()`]在其就绪时补上。
+    fn drive(&self, decide: impl FnOnce(&mut SuspendScheduler, Instant) -> Option<SuspendAction>) {
+        let (Some(backend), Some(webview)) = (self.backend.upgrade(), self.webview.upgrade()) else {
+            return;
+        };
+        let Some(webview) = webview.borrow().clone() else {
+            return;
+        };
+        self.drive_with(&*backend, &webview, decide);
+    }
+    /// webview 组件刚就绪（但还未被写入槽位）时，以当前的可见性重做一次决定
+    fn replay(&self, backend: &B, webview: &B::WebView, is_visible: bool) {
+        self.drive_with(backend, webview, |scheduler, now| scheduler.on_visibility(is_visible, now));
+    }
+    fn drive_with(&self, backend: &B, webview: &B::WebView, decide: impl FnOnce(&mut SuspendScheduler, Instant) -> Option<SuspendAction>) {
+        let (Some(scheduler), Some(timer)) = (self.scheduler.upgrade(), self.timer.upgrade()) else {
+            return;
+        };
+        let now = Instant::now();
+        let (action, deadline) = {
+            let mut scheduler = scheduler.borrow_mut();
+            (decide(&mut scheduler, now), scheduler.next_deadline())
+        };
+        if let Some(action) = action {
+            debug!("[WebviewContainer][Suspend]{action:?}");
+            self.perform(backend, webview, action);
         }
+        let driver = self.clone();
+        timer.replace(deadline.map(|deadline| timer::schedule(deadline.saturating_duration_since(now), move ||
+            driver.drive(|scheduler, now| scheduler.poll(now))
+        )));
     }
-    println!("rect={{top: {}, left: {}, width: {}, height: {} }}", rect.top, rect.left, rect.right, rect.bottom);
-    return webview_ctrl.put_bounds(rect);
-}
\ No newline at end of file
+    fn perform(&self, backend: &B, webview: &B::WebView, action: SuspendAction) {
+        match action {
+            SuspendAction::Suspend => {
+                if self.low_memory_when_suspended {
+                    backend.put_memory_usage_target_level(webview, true).map_err(|err| error!("[Suspend]{err}")).ok();
+                }
+                let scheduler = Weak::clone(&self.scheduler);
+                let on_failed = move || if let Some(scheduler) = scheduler.upgrade() {
+                    scheduler.borrow_mut().on_suspend_failed();
+                };
+                let on_failed_now = on_failed.clone();
+                backend.try_suspend(webview, Box::new(move |result| match result {
+                    Ok(true) => trace!("[WebviewContainer][Suspend]Webview 被挂起了"),
+                    Ok(false) => {
+                        error!("[Suspend]Webview 拒绝被挂起");
+                        on_failed();
+                    },
+                    Err(err) => {
+                        error!("[Suspend]{err}");
+                        on_failed();
+                    }
+                })).map_err(|err| {
+                    error!("[Suspend]{err}");
+                    on_failed_now();
+                }).ok();
+            },
+            SuspendAction::Resume => {
+                backend.resume(webview).map_err(|err| error!("[Resume]{err}")).ok();
+                if self.low_memory_when_suspended {
+                    backend.put_memory_usage_target_level(webview, false).map_err(|err| error!("[Resume]{err}")).ok();
+                }
+            }
+        }
+    }
+}
+/// 按合并调度器的决定同步 webview 控件的边界矩形，并为被推迟的同步预约定时器
+struct ResizeDriver<B: WebviewBackend> {
+    backend: Weak<B>,
+    webview_ctrl: Weak<RefCell<Option<B::Controller>>>,
+    frame: Weak<RefCell<Frame>>,
+    frame_hwnd: HWND,
+    insets: Weak<Cell<Insets>>,
+    scheduler: Weak<RefCell<ResizeScheduler>>,
+    timer: Weak<RefCell<Option<Delay>>>
+}
+impl<B: WebviewBackend> Clone for ResizeDriver<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: Weak::clone(&self.backend),
+            webview_ctrl: Weak::clone(&self.webview_ctrl),
+            frame: Weak::clone(&self.frame),
+            frame_hwnd: self.frame_hwnd,
+            insets: Weak::clone(&self.insets),
+            scheduler: Weak::clone(&self.scheduler),
+            timer: Weak::clone(&self.timer)
+        }
+    }
+}
+impl<B: WebviewBackend> ResizeDriver<B> {
+    fn on_resize(&self) {
+        let (Some(scheduler), Some(timer)) = (self.scheduler.upgrade(), self.timer.upgrade()) else {
+            return;
+        };
+        let now = Instant::now();
+        let decision = scheduler.borrow_mut().on_resize(now);
+        match decision {
+            ResizeDecision::ApplyNow => self.apply(),
+            ResizeDecision::ApplyAt(deadline) => {
+                let driver = self.clone();
+                timer.replace(Some(timer::schedule(deadline.saturating_duration_since(now), move || driver.flush())));
+            },
+            ResizeDecision::Coalesced => ()
+        }
+    }
+    /// 立即同步还未同步的尺寸调整（若有），并取消预约
+    fn flush(&self) {
+        let (Some(scheduler), Some(timer)) = (self.scheduler.upgrade(), self.timer.upgrade()) else {
+            return;
+        };
+        timer.take();
+        let is_pending = scheduler.borrow_mut().flush(Instant::now());
+        if is_pending {
+            self.apply();
+        }
+    }
+    fn apply(&self) {
+        let (Some(backend), Some(webview_ctrl), Some(frame), Some(insets)) = (self.backend.upgrade(), self.webview_ctrl.upgrade(), self.frame.upgrade(), self.insets.upgrade()) else {
+            return;
+        };
+        webview_ctrl.borrow().as_ref().and_then(|controller|
+            align_webview_2_container(&*backend, controller, frame, self.frame_hwnd, insets.get()).map_err(|err| error!("[OnResize|OnWindowMaximize]{err}")).ok()
+        );
+    }
+}
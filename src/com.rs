@@ -0,0 +1,224 @@
+//! 经由`COM`虚表调用`webview2-sys crate`还没有绑定的较新接口。
+//!
+//! 先以`QueryInterface`向`WebView2 Runtime`查询较新的接口（比如，`ICoreWebView2NavigationCompletedEventArgs2`）。运行时过旧时，查询以`E_NOINTERFACE`失败。
+//! 再按`WebView2.h`内的方法声明顺序，从虚表取出函数指针来调用。虚表下标都已计入了`IUnknown`的三个方法。
+//!
+//! 需要完成回调的方法（比如，`TrySuspend`）由手写的`COM`对象接收回调。
+use ::std::{cell::Cell, ffi::c_void, mem, ptr};
+use ::winapi::{shared::{guiddef::{GUID, IsEqualGUID}, minwindef::BOOL, winerror::{E_NOINTERFACE, E_POINTER, HRESULT, S_OK}}, um::unknwnbase::IUnknown};
+use super::backend::{BackendError, BackendResult, SuspendCompletedHandler};
+
+/// `ICoreWebView2NavigationCompletedEventArgs2`
+const IID_NAVIGATION_COMPLETED_EVENT_ARGS2: GUID = GUID { Data1: 0xFDF8_B738, Data2: 0xEE1E, Data3: 0x4DB2, Data4: [0xA3, 0x29, 0x8D, 0x7D, 0x7B, 0x74, 0xD7, 0x92] };
+/// `ICoreWebView2NavigationCompletedEventArgs2::get_HttpStatusCode`
+const GET_HTTP_STATUS_CODE: usize = 6;
+/// `ICoreWebView2_3`
+const IID_WEBVIEW2_3: GUID = GUID { Data1: 0xA0D6_DF20, Data2: 0x3B92, Data3: 0x416D, Data4: [0xAA, 0x0C, 0x43, 0x7A, 0x9C, 0x72, 0x78, 0x57] };
+/// `ICoreWebView2_3::TrySuspend`
+const TRY_SUSPEND: usize = 68;
+/// `ICoreWebView2_3::Resume`
+const RESUME: usize = 69;
+/// `ICoreWebView2_19`
+const IID_WEBVIEW2_19: GUID = GUID { Data1: 0x6921_F954, Data2: 0x79B0, Data3: 0x437F, Data4: [0xA9, 0x97, 0xC8, 0x58, 0x11, 0x89, 0x7C, 0x68] };
+/// `ICoreWebView2_19::put_MemoryUsageTargetLevel`
+const PUT_MEMORY_USAGE_TARGET_LEVEL: usize = 120;
+/// `COREWEBVIEW2_MEMORY_USAGE_TARGET_LEVEL_NORMAL`与`COREWEBVIEW2_MEMORY_USAGE_TARGET_LEVEL_LOW`
+const MEMORY_USAGE_TARGET_LEVEL_NORMAL: i32 = 0;
+const MEMORY_USAGE_TARGET_LEVEL_LOW: i32 = 1;
+/// `ICoreWebView2Controller3`
+const IID_CONTROLLER3: GUID = GUID { Data1: 0xF961_4724, Data2: 0x5D2B, Data3: 0x41DC, Data4: [0xAE, 0xF7, 0x73, 0xD6, 0x2B, 0x51, 0x54, 0x3B] };
+/// `ICoreWebView2Controller3::put_RasterizationScale`
+const PUT_RASTERIZATION_SCALE: usize = 29;
+/// `ICoreWebView2Controller3::put_ShouldDetectMonitorScaleChanges`
+const PUT_SHOULD_DETECT_MONITOR_SCALE_CHANGES: usize = 31;
+/// `IUnknown`
+const IID_UNKNOWN: GUID = GUID { Data1: 0x0000_0000, Data2: 0x0000, Data3: 0x0000, Data4: [0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46] };
+/// `ICoreWebView2TrySuspendCompletedHandler`
+const IID_TRY_SUSPEND_COMPLETED_HANDLER: GUID = GUID { Data1: 0x00F2_06A7, Data2: 0x9D17, Data3: 0x4605, Data4: [0x91, 0xF6, 0x4E, 0x8E, 0x4D, 0xE1, 0x92, 0xE3] };
+/// 经由`QueryInterface`得到的接口指针。它持有一个引用计数，并在被析构时释放之。
+pub(crate) struct ComInterface {
+    raw: *mut IUnknown,
+    name: &'static str
+}
+impl ComInterface {
+    /// 向`raw`查询接口`iid`。`name`仅被用于错误描述。
+    ///
+    /// # Safety
+    /// `raw`必须是有效的`COM`接口指针
+    pub(crate) unsafe fn query(raw: *mut c_void, iid: &GUID, name: &'static str) -> BackendResult<Self> {
+        if raw.is_null() {
+            return Err(BackendError::new(0x8000_4003_u32 as i32, format!("查询 {name} 的接口指针是空指针"))); // E_POINTER
+        }
+        let mut interface = ptr::null_mut();
+        let hresult = unsafe { (*raw.cast::<IUnknown>()).QueryInterface(iid, &mut interface) };
+        if hresult < 0 || interface.is_null() {
+            return Err(BackendError::new(hresult, format!("WebView2 Runtime 不支持 {name}")));
+        }
+        Ok(Self { raw: interface.cast(), name })
+    }
+    /// 虚表内第`slot`个函数指针。
+    ///
+    /// # Safety
+    /// `F`必须是与该方法签名一致的`extern "system" fn`，且其首个形参是接口指针
+    pub(crate) unsafe fn method<F: Copy>(&self, slot: usize) -> F {
+        debug_assert_eq!(size_of::<F>(), size_of::<*const c_void>());
+        unsafe {
+            let vtable = *self.raw.cast::<*const *const c_void>();
+            mem::transmute_copy(&*vtable.add(slot))
+        }
+    }
+    /// 接口指针。它被用作虚表方法的首个实参。
+    pub(crate) fn as_raw(&self) -> *mut c_void {
+        self.raw.cast()
+    }
+    /// 将方法`method`返回的`HRESULT`转换为`BackendResult`
+    pub(crate) fn check(&self, method: &str, hresult: HRESULT) -> BackendResult<()> {
+        if hresult < 0 {
+            return Err(BackendError::new(hresult, format!("{}::{method} 失败", self.name)));
+        }
+        Ok(())
+    }
+}
+impl Drop for ComInterface {
+    fn drop(&mut self) {
+        unsafe { (*self.raw).Release() };
+    }
+}
+/// 导航结果的`HTTP`状态码。运行时不支持`ICoreWebView2NavigationCompletedEventArgs2`，或导航没有`HTTP`响应（状态码是`0`）时，返回`None`。
+///
+/// # Safety
+/// `args`必须是有效的`ICoreWebView2NavigationCompletedEventArgs`接口指针
+pub(crate) unsafe fn http_status_code(args: *mut c_void) -> Option<u16> {
+    type GetHttpStatusCode = unsafe extern "system" fn(*mut c_void, *mut i32) -> HRESULT;
+    let args2 = unsafe { ComInterface::query(args, &IID_NAVIGATION_COMPLETED_EVENT_ARGS2, "ICoreWebView2NavigationCompletedEventArgs2") }.ok()?;
+    let mut http_status_code = 0;
+    let hresult = unsafe { args2.method::<GetHttpStatusCode>(GET_HTTP_STATUS_CODE)(args2.as_raw(), &mut http_status_code) };
+    args2.check("get_HttpStatusCode", hresult).ok()?;
+    u16::try_from(http_status_code).ok().filter(|http_status_code| *http_status_code > 0)
+}
+/// 挂起网页的渲染进程与定时器。挂起的结果由`completed`异步地给出。
+///
+/// # Safety
+/// `webview`必须是有效的`ICoreWebView2`接口指针
+pub(crate) unsafe fn try_suspend(webview: *mut c_void, completed: SuspendCompletedHandler) -> BackendResult<()> {
+    type TrySuspend = unsafe extern "system" fn(*mut c_void, *mut c_void) -> HRESULT;
+    let webview3 = unsafe { ComInterface::query(webview, &IID_WEBVIEW2_3, "ICoreWebView2_3") }?;
+    let handler = TrySuspendCompletedHandler::new(completed);
+    let hresult = unsafe { webview3.method::<TrySuspend>(TRY_SUSPEND)(webview3.as_raw(), handler.cast()) };
+    unsafe { TrySuspendCompletedHandler::release(handler) }; // 运行时若要回调，就已持有它自己的引用计数了
+    webview3.check("TrySuspend", hresult)
+}
+/// 恢复被挂起的网页
+///
+/// # Safety
+/// `webview`必须是有效的`ICoreWebView2`接口指针
+pub(crate) unsafe fn resume(webview: *mut c_void) -> BackendResult<()> {
+    type Resume = unsafe extern "system" fn(*mut c_void) -> HRESULT;
+    let webview3 = unsafe { ComInterface::query(webview, &IID_WEBVIEW2_3, "ICoreWebView2_3") }?;
+    let hresult = unsafe { webview3.method::<Resume>(RESUME)(webview3.as_raw()) };
+    webview3.check("Resume", hresult)
+}
+/// 将网页的内存用量目标设为【低】或【正常】
+///
+/// # Safety
+/// `webview`必须是有效的`ICoreWebView2`接口指针
+pub(crate) unsafe fn put_memory_usage_target_level(webview: *mut c_void, is_low: bool) -> BackendResult<()> {
+    type PutMemoryUsageTargetLevel = unsafe extern "system" fn(*mut c_void, i32) -> HRESULT;
+    let webview19 = unsafe { ComInterface::query(webview, &IID_WEBVIEW2_19, "ICoreWebView2_19") }?;
+    let level = if is_low { MEMORY_USAGE_TARGET_LEVEL_LOW } else { MEMORY_USAGE_TARGET_LEVEL_NORMAL };
+    let hresult = unsafe { webview19.method::<PutMemoryUsageTargetLevel>(PUT_MEMORY_USAGE_TARGET_LEVEL)(webview19.as_raw(), level) };
+    webview19.check("put_MemoryUsageTargetLevel", hresult)
+}
+/// 设置浏览器控件的光栅化缩放比
+///
+/// # Safety
+/// `controller`必须是有效的`ICoreWebView2Controller`接口指针
+pub(crate) unsafe fn put_rasterization_scale(controller: *mut c_void, scale: f64) -> BackendResult<()> {
+    type PutRasterizationScale = unsafe extern "system" fn(*mut c_void, f64) -> HRESULT;
+    let controller3 = unsafe { ComInterface::query(controller, &IID_CONTROLLER3, "ICoreWebView2Controller3") }?;
+    let hresult = unsafe { controller3.method::<PutRasterizationScale>(PUT_RASTERIZATION_SCALE)(controller3.as_raw(), scale) };
+    controller3.check("put_RasterizationScale", hresult)
+}
+/// 浏览器控件是否自己检测显示器缩放比的变化
+///
+/// # Safety
+/// `controller`必须是有效的`ICoreWebView2Controller`接口指针
+pub(crate) unsafe fn put_should_detect_monitor_scale_changes(controller: *mut c_void, should_detect: bool) -> BackendResult<()> {
+    type PutShouldDetectMonitorScaleChanges = unsafe extern "system" fn(*mut c_void, BOOL) -> HRESULT;
+    let controller3 = unsafe { ComInterface::query(controller, &IID_CONTROLLER3, "ICoreWebView2Controller3") }?;
+    let hresult = unsafe { controller3.method::<PutShouldDetectMonitorScaleChanges>(PUT_SHOULD_DETECT_MONITOR_SCALE_CHANGES)(controller3.as_raw(), BOOL::from(should_detect)) };
+    controller3.check("put_ShouldDetectMonitorScaleChanges", hresult)
+}
+/// `ICoreWebView2TrySuspendCompletedHandler`的虚表
+#[repr(C)]
+struct TrySuspendCompletedHandlerVtbl {
+    query_interface: unsafe extern "system" fn(*mut TrySuspendCompletedHandler, *const GUID, *mut *mut c_void) -> HRESULT,
+    add_ref: unsafe extern "system" fn(*mut TrySuspendCompletedHandler) -> u32,
+    release: unsafe extern "system" fn(*mut TrySuspendCompletedHandler) -> u32,
+    invoke: unsafe extern "system" fn(*mut TrySuspendCompletedHandler, HRESULT, BOOL) -> HRESULT
+}
+/// 接收`TrySuspend`完成回调的`COM`对象。`WebView2`仅在`UI`线程上回调它，所以引用计数不必是原子的。
+#[repr(C)]
+struct TrySuspendCompletedHandler {
+    vtable: *const TrySuspendCompletedHandlerVtbl,
+    ref_count: Cell<u32>,
+    completed: Cell<Option<SuspendCompletedHandler>>
+}
+static TRY_SUSPEND_COMPLETED_HANDLER_VTBL: TrySuspendCompletedHandlerVtbl = TrySuspendCompletedHandlerVtbl {
+    query_interface: TrySuspendCompletedHandler::query_interface,
+    add_ref: TrySuspendCompletedHandler::add_ref,
+    release: TrySuspendCompletedHandler::release,
+    invoke: TrySuspendCompletedHandler::invoke
+};
+impl TrySuspendCompletedHandler {
+    /// 构造一个引用计数为`1`的`COM`对象
+    fn new(completed: SuspendCompletedHandler) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            vtable: &TRY_SUSPEND_COMPLETED_HANDLER_VTBL,
+            ref_count: Cell::new(1),
+            completed: Cell::new(Some(completed))
+        }))
+    }
+    unsafe extern "system" fn query_interface(this: *mut Self, iid: *const GUID, object: *mut *mut c_void) -> HRESULT {
+        if iid.is_null() || object.is_null() {
+            return E_POINTER;
+        }
+        let iid = unsafe { &*iid };
+        if IsEqualGUID(iid, &IID_UNKNOWN) || IsEqualGUID(iid, &IID_TRY_SUSPEND_COMPLETED_HANDLER) {
+            unsafe {
+                Self::add_ref(this);
+                *object = this.cast();
+            }
+            S_OK
+        } else {
+            unsafe { *object = ptr::null_mut() };
+            E_NOINTERFACE
+        }
+    }
+    unsafe extern "system" fn add_ref(this: *mut Self) -> u32 {
+        let this = unsafe { &*this };
+        this.ref_count.set(this.ref_count.get() + 1);
+        this.ref_count.get()
+    }
+    unsafe extern "system" fn release(this: *mut Self) -> u32 {
+        let ref_count = {
+            let this = unsafe { &*this };
+            this.ref_count.set(this.ref_count.get() - 1);
+            this.ref_count.get()
+        };
+        if ref_count == 0 {
+            drop(unsafe { Box::from_raw(this) });
+        }
+        ref_count
+    }
+    unsafe extern "system" fn invoke(this: *mut Self, error_code: HRESULT, is_successful: BOOL) -> HRESULT {
+        if let Some(completed) = unsafe { &*this }.completed.take() {
+            completed(if error_code < 0 {
+                Err(BackendError::new(error_code, "ICoreWebView2_3::TrySuspend 失败"))
+            } else {
+                Ok(is_successful != 0)
+            });
+        }
+        S_OK
+    }
+}
@@ -0,0 +1,238 @@
+//! 声明式配置：由`JSON`（或开启`toml`特性之后的`TOML`）配置文件构造`WebviewContainerBuilder`。
+//!
+//! ```toml
+//! [frame]
+//! visible = true
+//! border = false
+//! size = [800, 600]
+//! position = [0, 0]
+//!
+//! [environment]
+//! user_data_folder = "C:\\ProgramData\\acme\\webview"
+//! language = "zh-CN"
+//! additional_browser_arguments = "--disable-features=msSmartScreenProtection"
+//! target_compatible_browser_version = "120.0.2210.91"
+//! ```
+use ::serde::Deserialize;
+use ::std::{error::Error, fmt::{self, Display, Formatter}, path::PathBuf};
+use super::{backend::EnvironmentOptions, browser_args::BrowserArgs};
+
+/// 配置文件的解析或校验错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 语法错误、未知配置项或配置项的类型不符。错误信息里含有出错的配置项名与位置。
+    Parse(String),
+    /// 配置项的值不合法
+    InvalidValue {
+        /// 配置项的完整路径。比如，`environment.language`
+        key: &'static str,
+        /// 不合法的原因
+        reason: String
+    }
+}
+impl ConfigError {
+    fn invalid(key: &'static str, reason: impl Into<String>) -> Self {
+        Self::InvalidValue { key, reason: reason.into() }
+    }
+    /// 值不合法的配置项（若有）
+    pub fn key(&self) -> Option<&'static str> {
+        match self {
+            Self::InvalidValue { key, .. } => Some(key),
+            Self::Parse(_) => None
+        }
+    }
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(message) => write!(f, "配置文件解析失败：{message}"),
+            Self::InvalidValue { key, reason } => write!(f, "配置项 {key} 不合法：{reason}")
+        }
+    }
+}
+impl Error for ConfigError {}
+/// `nwg::Frame`的配置项
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrameConfig {
+    /// 是否可见。默认值是`true`
+    pub visible: bool,
+    /// 是否被禁用
+    pub disabled: bool,
+    /// 是否有边框
+    pub border: bool,
+    /// 宽与高
+    pub size: Option<(i32, i32)>,
+    /// 相对于父控件的坐标
+    pub position: Option<(i32, i32)>
+}
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self { visible: true, disabled: false, border: false, size: None, position: None }
+    }
+}
+/// `webview2::EnvironmentBuilder`的配置项。`WebviewContainerBuilder`也以它保存（自有的）浏览器环境配置项。
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvironmentConfig {
+    /// 浏览器安装目录
+    pub browser_executable_folder: Option<PathBuf>,
+    /// 缓存目录
+    pub user_data_folder: Option<PathBuf>,
+    /// 浏览器启动参数
+    pub additional_browser_arguments: Option<String>,
+    /// 默认语言。比如，`zh-CN`
+    pub language: Option<String>,
+    /// 浏览器版本号。比如，`120.0.2210.91`
+    pub target_compatible_browser_version: Option<String>,
+    /// 是否允许单点登录
+    pub allow_single_sign_on_using_osprimary_account: bool
+}
+impl EnvironmentConfig {
+    /// 借出为`webview2::EnvironmentBuilder`的配置项
+    pub fn options(&self) -> EnvironmentOptions<'_> {
+        EnvironmentOptions {
+            browser_executable_folder: self.browser_executable_folder.as_deref(),
+            user_data_folder: self.user_data_folder.as_deref(),
+            additional_browser_arguments: self.additional_browser_arguments.as_deref(),
+            language: self.language.as_deref(),
+            target_compatible_browser_version: self.target_compatible_browser_version.as_deref(),
+            allow_single_sign_on_using_osprimary_account: self.allow_single_sign_on_using_osprimary_account
+        }
+    }
+}
+/// `WebviewContainerBuilder`的声明式配置
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebviewConfig {
+    /// `nwg::Frame`的配置项
+    pub frame: FrameConfig,
+    /// `webview2::EnvironmentBuilder`的配置项
+    pub environment: EnvironmentConfig
+}
+impl WebviewConfig {
+    /// 解析并校验`JSON`配置
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let config: Self = ::serde_json::from_str(json).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+    /// 解析并校验`TOML`配置
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let config: Self = ::toml::from_str(toml).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+    /// 校验各配置项的值。经由其它`serde`格式反序列化出的配置，需要手动调用它。
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some((width, height)) = self.frame.size {
+            if width < 0 || height < 0 {
+                return Err(ConfigError::invalid("frame.size", format!("宽与高不能是负数：[{width}, {height}]")));
+            }
+        }
+        let EnvironmentConfig { browser_executable_folder, user_data_folder, additional_browser_arguments, language, target_compatible_browser_version, .. } = &self.environment;
+        if browser_executable_folder.as_ref().is_some_and(|folder| folder.as_os_str().is_empty()) {
+            return Err(ConfigError::invalid("environment.browser_executable_folder", "目录不能是空字符串"));
+        }
+        if user_data_folder.as_ref().is_some_and(|folder| folder.as_os_str().is_empty()) {
+            return Err(ConfigError::invalid("environment.user_data_folder", "目录不能是空字符串"));
+        }
+        if let Some(Err(err)) = additional_browser_arguments.as_deref().map(BrowserArgs::parse) {
+            return Err(ConfigError::invalid("environment.additional_browser_arguments", err.to_string()));
+        }
+        if let Some(language) = language {
+            if !is_language_tag(language) {
+                return Err(ConfigError::invalid("environment.language", format!("不是有效的 BCP 47 语言标签：{language}")));
+            }
+        }
+        if let Some(version) = target_compatible_browser_version {
+            if !is_browser_version(version) {
+                return Err(ConfigError::invalid("environment.target_compatible_browser_version", format!("版本号应由 1 至 4 段数字组成：{version}")));
+            }
+        }
+        Ok(())
+    }
+    /// 转换为`webview2::EnvironmentBuilder`的配置项
+    pub fn environment_options(&self) -> EnvironmentOptions<'_> {
+        self.environment.options()
+    }
+}
+/// 形如`zh`、`zh-CN`或`zh-Hans-CN`的语言标签
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    (2..=8).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic()) &&
+        subtags.all(|subtag| (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+/// 形如`120`或`120.0.2210.91`的版本号
+fn is_browser_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    (1..=4).contains(&parts.len()) && parts.iter().all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_key(config: &WebviewConfig) -> Option<&'static str> {
+        config.validate().err().and_then(|err| err.key())
+    }
+    fn environment(environment: EnvironmentConfig) -> WebviewConfig {
+        WebviewConfig { environment, ..WebviewConfig::default() }
+    }
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(WebviewConfig::default().validate(), Ok(()));
+        assert!(WebviewConfig::default().frame.visible);
+    }
+    #[test]
+    fn rejects_negative_sizes() {
+        let config = WebviewConfig { frame: FrameConfig { size: Some((800, -1)), ..FrameConfig::default() }, ..WebviewConfig::default() };
+        assert_eq!(invalid_key(&config), Some("frame.size"));
+        let config = WebviewConfig { frame: FrameConfig { size: Some((0, 0)), position: Some((-10, -10)), ..FrameConfig::default() }, ..WebviewConfig::default() };
+        assert_eq!(config.validate(), Ok(()));
+    }
+    #[test]
+    fn rejects_empty_folders() {
+        let config = environment(EnvironmentConfig { browser_executable_folder: Some(PathBuf::new()), ..EnvironmentConfig::default() });
+        assert_eq!(invalid_key(&config), Some("environment.browser_executable_folder"));
+        let config = environment(EnvironmentConfig { user_data_folder: Some(PathBuf::new()), ..EnvironmentConfig::default() });
+        assert_eq!(invalid_key(&config), Some("environment.user_data_folder"));
+    }
+    #[test]
+    fn rejects_malformed_browser_arguments() {
+        let arguments = |arguments: &str| environment(EnvironmentConfig { additional_browser_arguments: Some(arguments.to_string()), ..EnvironmentConfig::default() });
+        assert_eq!(arguments("--disable-gpu --lang=\"zh-CN\"").validate(), Ok(()));
+        assert_eq!(invalid_key(&arguments("--lang=\"zh-CN")), Some("environment.additional_browser_arguments"));
+        assert_eq!(invalid_key(&arguments("disable-gpu")), Some("environment.additional_browser_arguments"));
+    }
+    #[test]
+    fn validates_language_tags() {
+        let language = |language: &str| environment(EnvironmentConfig { language: Some(language.to_string()), ..EnvironmentConfig::default() }).validate().is_ok();
+        assert!(language("zh") && language("zh-CN") && language("zh-Hans-CN") && language("es-419"));
+        assert!(!language("") && !language("z") && !language("zh_CN") && !language("zh-") && !language("1a-CN") && !language("zh-toolongsubtag"));
+    }
+    #[test]
+    fn validates_browser_versions() {
+        let version = |version: &str| environment(EnvironmentConfig { target_compatible_browser_version: Some(version.to_string()), ..EnvironmentConfig::default() }).validate().is_ok();
+        assert!(version("120") && version("120.0.2210.91"));
+        assert!(!version("") && !version("120.") && !version("1.2.3.4.5") && !version("120.0-beta"));
+    }
+    #[test]
+    fn parses_json() {
+        let config = WebviewConfig::from_json(r#"{"frame": {"border": true, "size": [800, 600]}, "environment": {"language": "zh-CN"}}"#).unwrap();
+        assert_eq!(config.frame, FrameConfig { border: true, size: Some((800, 600)), ..FrameConfig::default() });
+        assert_eq!(config.environment_options().language, Some("zh-CN"));
+        assert!(matches!(WebviewConfig::from_json(r#"{"frame": {"colour": "red"}}"#), Err(ConfigError::Parse(_))));
+        let err = WebviewConfig::from_json(r#"{"environment": {"language": "zh_CN"}}"#).unwrap_err();
+        assert_eq!(err.key(), Some("environment.language"));
+        assert_eq!(err.to_string(), "配置项 environment.language 不合法：不是有效的 BCP 47 语言标签：zh_CN");
+    }
+    #[cfg(feature = "toml")]
+    #[test]
+    fn parses_toml() {
+        let config = WebviewConfig::from_toml("[frame]\nvisible = false\n[environment]\ntarget_compatible_browser_version = \"120.0\"\n").unwrap();
+        assert!(!config.frame.visible);
+        assert_eq!(config.environment.target_compatible_browser_version.as_deref(), Some("120.0"));
+    }
+}
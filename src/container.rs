@@ -0,0 +1,395 @@
+use ::futures::{channel::mpsc::UnboundedReceiver, executor};
+use ::nwg::{self as nwg, ControlHandle, EventHandler, Frame, RawEventHandler};
+use ::serde::{Serialize, de::DeserializeOwned};
+use ::serde_json::Value;
+use ::std::{cell::{Cell, RefCell}, fmt::{self, Debug, Formatter}, future::Future, ops::Deref, rc::Rc, time::Duration};
+use ::winapi::shared::windef::HWND;
+use super::{backend::{Webview2Backend, WebviewBackend}, bridge::{MessageRouter, WebviewBridge}, builder::{self, WebviewContainerBuilder}, config::EnvironmentConfig, dpi::{self, ScaleMode, USER_DEFAULT_SCREEN_DPI}, fallback::FallbackUi, error::WebviewContainerError, events::{EventBroadcaster, WebviewEvents}, host_event::{self, HostEvent, WindowEvent}, host_object::HostObject, insets::Insets, init::{ReadyFuture, ReadyResult, WebviewHandles}, init_script, log::{ContainerSpan, debug, error, trace}, navigate::{NavigationOutcome, NavigationTracker}, resize::ResizeScheduler, runtime::{self, RuntimeStatus}, rpc::{self, RpcDispatcher, RpcError, RpcResponse}, script::{self, DEFAULT_SCRIPT_TIMEOUT}, suspend::SuspendScheduler, timer::{self, Delay}, window_state::{WindowState, WindowStateMachine}};
+
+/// [webview2::Controller](https://docs.rs/webview2/0.1.4/webview2/struct.Controller.html)的[NWG](https://docs.rs/native-windows-gui/1.0.1/native_windows_gui/index.html)控件包装器。借助于该`WebviewContainer`包装器，`webview2::Controller`控件实例就能参与`NWG`的【网格布局】【弹性布局】与【动态布局】。
+/// # 原理：
+/// 1. `WebviewContainer`将`webview2::Controller`嵌套于[nwg::Frame](https://docs.rs/native-windows-gui/1.0.1/native_windows_gui/struct.Frame.html)控件内，以参与控件布局管理。
+/// 2. 监听主窗体的【最小化】事件。在主窗体被最小化之后，停止`webview2::Controller`控件对打开网页的帧刷新。即，将网页的`FPS`降到零。
+/// 3. 监听主窗体的【窗体恢复】事件。在主窗体非最小化状态，恢复`webview2::Controller`控件对打开网页的帧刷新。
+/// 4. 监听主窗体的【移动】事件。仅只透传窗体的最新屏幕坐标给底层的`webview2::Controller`控件。
+/// 5. 监听`nwg::Frame`控件的`OnResize`事件。时刻拉伸或压缩`webview2::Controller`的大小。
+/// # `webview2::Controller`的初始化
+/// `webview2::Controller`初始化是异步的。所以在[`WebviewContainerBuilder::build()`]被同步执行结束之后，仅只`nwg::Frame`布局占位控件被初始化好了。而，`webview2::Controller`的初始化就绪需要等待由[`WebviewContainer.ready_fut()`]成员方法返回的`Future`
+/// # 后端
+/// 对浏览器内核的全部调用都经由类型参数`B`（[`WebviewBackend`]）转发。默认后端是[`Webview2Backend`]。
+/// # 消息桥
+/// [`WebviewContainer::bridge()`]构造`JS <-> Rust`的类型化消息通道。消息体经由`serde`编解码。
+/// # `RPC`
+/// 由`WebviewContainerBuilder::rpc()`启用之后，网页端经由`window.nwg.invoke(method, params)`调用由[`WebviewContainer::rpc_handler()`]注册的异步处理函数。
+/// 处理函数在[`WebviewContainer::rpc_server()`]服务循环内被执行。
+/// # 宿主对象
+/// [`WebviewContainer::expose()`]将`Rust`方法暴露给网页，成为`window.host.<name>.<method>(...args)`。它也经由`RPC`服务循环被执行，所以同样需要先启用`RPC`。
+/// # 异步导航
+/// [`WebviewContainer::navigate()`]返回的`Future`在导航结束时被决议。
+/// # 网页脚本
+/// [`WebviewContainer::eval()`]执行网页脚本，并将其结果反序列化为`T`。
+/// # 显示器缩放比
+/// 主窗体在不同缩放比的显示器之间移动时，`webview`控件的光栅化缩放比与边界矩形都会被同步。[`WebviewContainer::set_scale_mode()`]可固定缩放比。
+/// # 事件流
+/// [`WebviewContainer::events()`]将导航、网页标题、网页消息与子进程失败等网页事件转换为`futures::Stream`。
+pub struct WebviewContainer<B: WebviewBackend = Webview2Backend> {
+    pub(crate) is_closing: Rc<RefCell<bool>>,
+    pub(crate) backend: Rc<B>,
+    pub(crate) frame: Rc<RefCell<Frame>>,
+    pub(crate) webview_ctrl: Rc<RefCell<Option<B::Controller>>>,
+    pub(crate) webview: Rc<RefCell<Option<B::WebView>>>,
+    pub(crate) router: Rc<MessageRouter>,
+    pub(crate) rpc: Rc<RpcDispatcher>,
+    pub(crate) rpc_requests: RefCell<Option<UnboundedReceiver<Value>>>,
+    pub(crate) rpc_enabled: bool,
+    pub(crate) events: Rc<EventBroadcaster>,
+    pub(crate) navigations: Rc<NavigationTracker>,
+    pub(crate) script_timeout: Cell<Duration>,
+    pub(crate) init_script_ids: Rc<RefCell<Vec<Option<String>>>>,
+    pub(crate) host_proxies: Rc<RefCell<Vec<String>>>,
+    pub(crate) window_state: Rc<RefCell<WindowStateMachine>>,
+    pub(crate) suspend: Rc<RefCell<SuspendScheduler>>,
+    pub(crate) suspend_timer: Rc<RefCell<Option<Delay>>>,
+    pub(crate) scale_mode: Rc<Cell<ScaleMode>>,
+    pub(crate) dpi: Rc<Cell<u32>>,
+    pub(crate) resize: Rc<RefCell<ResizeScheduler>>,
+    pub(crate) resize_timer: Rc<RefCell<Option<Delay>>>,
+    pub(crate) insets: Rc<Cell<Insets>>,
+    pub(crate) environment: EnvironmentConfig,
+    pub(crate) fallback: Rc<RefCell<Option<FallbackUi>>>,
+    pub(crate) ready_fut: Option<ReadyFuture<B>>,
+    pub(crate) event_handle: Option<EventHandler>,
+    pub(crate) raw_event_handle: Option<RawEventHandler>,
+    pub(crate) span: ContainerSpan
+}
+impl Default for WebviewContainer {
+    fn default() -> Self {
+        Self::with_backend(Webview2Backend)
+    }
+}
+impl<B: WebviewBackend> WebviewContainer<B> {
+    /// 以指定的后端构造容器。比如，在单元测试里改用`RecordingBackend`。该容器仍需经由`WebviewContainerBuilder::build()`被初始化。
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            is_closing: Rc::default(),
+            backend: Rc::new(backend),
+            frame: Rc::default(),
+            webview_ctrl: Rc::default(),
+            webview: Rc::default(),
+            router: Rc::default(),
+            rpc: Rc::default(),
+            rpc_requests: RefCell::default(),
+            rpc_enabled: false,
+            events: Rc::default(),
+            navigations: Rc::default(),
+            script_timeout: Cell::new(DEFAULT_SCRIPT_TIMEOUT),
+            init_script_ids: Rc::default(),
+            host_proxies: Rc::default(),
+            window_state: Rc::default(),
+            suspend: Rc::default(),
+            suspend_timer: Rc::default(),
+            scale_mode: Rc::default(),
+            dpi: Rc::new(Cell::new(USER_DEFAULT_SCREEN_DPI)),
+            resize: Rc::default(),
+            resize_timer: Rc::default(),
+            insets: Rc::default(),
+            environment: EnvironmentConfig::default(),
+            fallback: Rc::default(),
+            ready_fut: None,
+            event_handle: None,
+            raw_event_handle: None,
+            span: ContainerSpan::default()
+        }
+    }
+}
+impl<B: WebviewBackend> PartialEq for WebviewContainer<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.frame.borrow().eq(other.frame.borrow().deref())
+    }
+}
+impl<B: WebviewBackend> Eq for WebviewContainer<B> {}
+impl<B: WebviewBackend> From<WebviewContainer<B>> for ControlHandle {
+    fn from(value: WebviewContainer<B>) -> Self {
+        value.frame.borrow().handle
+    }
+}
+impl<B: WebviewBackend> From<&WebviewContainer<B>> for ControlHandle {
+    fn from(value: &WebviewContainer<B>) -> Self {
+        value.frame.borrow().handle
+    }
+}
+impl<B: WebviewBackend> Drop for WebviewContainer<B> {
+    fn drop(&mut self) {
+        let _span = self.span.enter();
+        debug!("[WebviewContainer][drop]");
+        *self.is_closing.borrow_mut() = true;
+        if self.raw_event_handle.as_ref().map(nwg::unbind_raw_event_handler).map_or(Ok(None), |r| r.map(Some)).is_ok() {
+            self.event_handle.as_ref().map(nwg::unbind_event_handler);
+        }
+        host_event::route(&*self.backend, self.webview_ctrl.borrow().as_ref(), WindowEvent::Closed).map_err(|err| error!("[WebviewContainer][drop]{err}")).ok();
+        self.frame.borrow_mut().handle.destroy();
+    }
+}
+impl<B: WebviewBackend> Debug for WebviewContainer<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewContainer")
+            .field("frame", &self.frame.borrow().handle)
+            .field("is_closing", &self.is_closing.borrow())
+            .field("is_ready", &self.webview_ctrl.borrow().is_some())
+            .field("window_state", &self.window_state.borrow().state())
+            .field("is_suspended", &self.suspend.borrow().is_suspended())
+            .field("scale_mode", &self.scale_mode.get())
+            .field("dpi", &self.dpi.get())
+            .field("insets", &self.insets.get())
+            .finish_non_exhaustive()
+    }
+}
+impl<B: WebviewBackend> WebviewContainer<B> {
+    /// 容器的构造器
+    pub fn builder() -> WebviewContainerBuilder<B> where B: Default {
+        WebviewContainerBuilder::default()
+    }
+    /// `webview`控件初始化的异步锁。无论初始化成功与否，它都一定会被决议为`Result<WebviewHandles, WebviewContainerError>`。
+    pub fn ready_fut(&self) -> Result<ReadyFuture<B>, WebviewContainerError> {
+        if *self.is_closing.borrow() {
+            return Err(WebviewContainerError::AlreadyClosed);
+        }
+        self.ready_fut.clone().ok_or(WebviewContainerError::NotReady)
+    }
+    /// 探测`WebView2 Runtime`的可用性。版本要求来自`target_compatible_browser_version`配置项。
+    /// 它每次都会重新探测。所以，在用户安装了`WebView2 Runtime`之后，可借此决定是否重建`WebviewContainer`。
+    pub fn runtime_status(&self) -> RuntimeStatus {
+        runtime::probe(&*self.backend, &self.environment.options())
+    }
+    /// 同步地阻塞当前线程，和等待`webview`控件初始化结束
+    pub fn ready_block(&self) -> ReadyResult<B> {
+        executor::block_on(self.ready_fut()?)
+    }
+    /// 布局占位控件`nwg::Frame`的句柄。它可被交给`NWG`布局，或被用作其它控件的父控件。
+    pub fn handle(&self) -> ControlHandle {
+        self.frame.borrow().handle
+    }
+    /// 容器的大小
+    pub fn size(&self) -> (u32, u32) {
+        self.frame.borrow().size()
+    }
+    /// 调整容器的大小。`webview`控件的边界矩形会被同步调整。
+    pub fn set_size(&self, width: u32, height: u32) -> Result<(), WebviewContainerError> {
+        self.frame.borrow().set_size(width, height);
+        self.with_controller(|backend, controller, frame_hwnd| builder::align_webview_2_container(backend, controller, Rc::clone(&self.frame), frame_hwnd, self.insets.get()))
+    }
+    /// `webview`控件在容器客户区内的留白
+    pub fn insets(&self) -> Insets {
+        self.insets.get()
+    }
+    /// 调整`webview`控件在容器客户区内的留白。`webview`控件的边界矩形会被同步调整。
+    pub fn set_insets(&self, insets: Insets) -> Result<(), WebviewContainerError> {
+        self.insets.set(insets);
+        self.with_controller(|backend, controller, frame_hwnd| builder::align_webview_2_container(backend, controller, Rc::clone(&self.frame), frame_hwnd, insets))
+    }
+    /// 容器相对于父控件的坐标
+    pub fn position(&self) -> (i32, i32) {
+        self.frame.borrow().position()
+    }
+    /// 移动容器。`webview`控件会被通知重新计算屏幕坐标。
+    pub fn set_position(&self, x: i32, y: i32) -> Result<(), WebviewContainerError> {
+        self.frame.borrow().set_position(x, y);
+        self.with_controller(|backend, controller, _| host_event::dispatch(backend, controller, HostEvent::Moved).map_err(WebviewContainerError::BoundsUpdate))
+    }
+    /// 容器是否可见
+    pub fn visible(&self) -> bool {
+        self.frame.borrow().visible()
+    }
+    /// 显示或隐藏容器。被隐藏的`webview`控件会停止帧刷新。
+    pub fn set_visible(&self, visible: bool) -> Result<(), WebviewContainerError> {
+        self.frame.borrow().set_visible(visible);
+        self.with_controller(|backend, controller, _| backend.put_is_visible(controller, visible).map_err(WebviewContainerError::VisibilityUpdate))
+    }
+    /// 容器是否可交互
+    pub fn enabled(&self) -> bool {
+        self.frame.borrow().enabled()
+    }
+    /// 启用或禁用容器。被禁用容器内的`webview`控件不再接收键盘与鼠标输入。
+    pub fn set_enabled(&self, enabled: bool) {
+        self.frame.borrow().set_enabled(enabled);
+    }
+    /// 将键盘焦点移入`webview`控件
+    pub fn focus(&self) -> Result<(), WebviewContainerError> {
+        self.frame.borrow().set_focus();
+        self.with_controller(|backend, controller, _| backend.move_focus(controller).map_err(WebviewContainerError::FocusChange))
+    }
+    /// 在`webview`控件已被初始化时，才同步它的状态。否则，`nwg::Frame`的新状态会在初始化结束时被同步给`webview`控件。
+    fn with_controller<F>(&self, sync: F) -> Result<(), WebviewContainerError>
+    where F: FnOnce(&B, &B::Controller, HWND) -> Result<(), WebviewContainerError> {
+        if *self.is_closing.borrow() {
+            return Err(WebviewContainerError::AlreadyClosed);
+        }
+        let frame_hwnd = self.frame.borrow().handle.hwnd();
+        match (self.webview_ctrl.borrow().as_ref(), frame_hwnd) {
+            (Some(controller), Some(frame_hwnd)) => sync(&self.backend, controller, frame_hwnd),
+            _ => Ok(())
+        }
+    }
+    /// 构造`channel`频道的类型化消息桥。`In`是从网页收到的消息类型，`Out`是发往网页的消息类型。
+    /// ```ignore
+    /// let bridge = webview_container.bridge::<TodoCommand, TodoState>("todo");
+    /// bridge.on_message(|command| { ... })?;
+    /// bridge.post(&state)?;
+    /// ```
+    pub fn bridge<In, Out>(&self, channel: impl Into<String>) -> WebviewBridge<In, Out, B> {
+        WebviewBridge::new(channel.into(), Rc::clone(&self.backend), &self.webview, &self.router)
+    }
+    /// 注册（或替换）`RPC`方法的异步处理函数。网页端的`params`会被反序列化为`P`。
+    /// ```ignore
+    /// webview_container.rpc_handler("add", |(a, b): (i32, i32)| async move { Ok(a + b) });
+    /// ```
+    pub fn rpc_handler<P, R, F, Fut>(&self, method: impl Into<String>, handler: F)
+    where P: DeserializeOwned,
+          R: Serialize,
+          F: Fn(P) -> Fut + 'static,
+          Fut: Future<Output = Result<R, RpcError>> + 'static {
+        self.rpc.register(method.into(), handler);
+    }
+    /// 注销`RPC`方法的处理函数
+    pub fn remove_rpc_handler(&self, method: &str) {
+        self.rpc.unregister(method);
+    }
+    /// 将宿主对象暴露给网页，成为`window.host.<name>`。同名的宿主对象会被替换。
+    /// 它的方法在[`WebviewContainer::rpc_server()`]服务循环内被执行，所以服务循环需要被`spawn`。
+    /// 在`webview`控件就绪之前，代理脚本会在初始化时被注入；否则，它会被立即注入当前网页与之后被加载的网页。
+    /// 未启用`RPC`时，返回[`WebviewContainerError::RpcDisabled`]。
+    pub fn expose(&self, host_object: HostObject) -> Result<(), WebviewContainerError> {
+        if *self.is_closing.borrow() {
+            return Err(WebviewContainerError::AlreadyClosed);
+        }
+        if !self.rpc_enabled {
+            return Err(WebviewContainerError::RpcDisabled);
+        }
+        let proxy_script = host_object.register(&self.rpc);
+        let webview = self.webview.borrow();
+        let Some(webview) = webview.as_ref() else {
+            self.host_proxies.borrow_mut().push(proxy_script);
+            return Ok(());
+        };
+        let span = self.span.clone();
+        self.backend.add_script_to_execute_on_document_created(webview, &proxy_script, Box::new(move |id| {
+            let _span = span.enter();
+            match id {
+                Ok(id) => trace!("[WebviewContainer][expose]宿主对象代理脚本 {id}"),
+                Err(err) => error!("[WebviewContainer][expose]宿主对象代理脚本注入失败：{err}")
+            }
+        })).map_err(WebviewContainerError::ScriptInjection)?;
+        self.backend.execute_script(webview, &proxy_script, Box::new(|_| ())).map_err(WebviewContainerError::ScriptExecution)
+    }
+    /// `RPC`处理函数的执行时限。初始值来自`WebviewContainerBuilder::rpc()`。超时的调用会以`RpcError::TIMEOUT`错误码响应网页端。
+    /// 它不改变已被注入网页的`window.nwg.invoke()`垫片的默认超时。
+    pub fn set_rpc_timeout(&self, timeout: Duration) {
+        self.rpc.set_timeout(timeout);
+    }
+    /// 导航至`uri`。返回的`Future`先等待`webview`控件就绪，再在对应的`NavigationCompleted`事件发生时被决议。
+    /// 导航失败、被另一次导航取代或超时都会决议为[`WebviewContainerError::Navigation`]。
+    /// ```ignore
+    /// let NavigationOutcome { navigation_id, .. } = webview_container.navigate("https://app.local/").await?;
+    /// ```
+    /// 仅`#`锚点不同的导航不会产生导航事件，所以它会超时。
+    pub fn navigate(&self, uri: impl Into<String>) -> impl Future<Output = Result<NavigationOutcome, WebviewContainerError>> + 'static {
+        let (ready_fut, backend, navigations, uri) = (self.ready_fut(), Rc::clone(&self.backend), Rc::downgrade(&self.navigations), uri.into());
+        async move {
+            let WebviewHandles { webview, .. } = ready_fut?.await?;
+            let navigation = {
+                let navigations = navigations.upgrade().ok_or(WebviewContainerError::AlreadyClosed)?;
+                let navigation = navigations.track(&uri, timer::delay);
+                if let Err(err) = backend.navigate(&webview, &uri) {
+                    navigations.abandon();
+                    return Err(WebviewContainerError::NavigationRequest(err));
+                }
+                navigation
+            };
+            navigation.await
+        }
+    }
+    /// `navigate()`的超时。默认值是[`DEFAULT_NAVIGATION_TIMEOUT`](crate::DEFAULT_NAVIGATION_TIMEOUT)。
+    pub fn set_navigation_timeout(&self, timeout: Duration) {
+        self.navigations.set_timeout(timeout);
+    }
+    /// 在当前网页内执行脚本，并将其结果反序列化为`T`。返回的`Future`会先等待`webview`控件就绪。
+    /// 脚本抛出的异常决议为[`ScriptError::Exception`](crate::ScriptError::Exception)，而结果的反序列化失败决议为[`ScriptError::Deserialization`](crate::ScriptError::Deserialization)。
+    /// ```ignore
+    /// let title: String = webview_container.eval("document.title").await?;
+    /// ```
+    pub fn eval<T: DeserializeOwned + 'static>(&self, script: &str) -> impl Future<Output = Result<T, WebviewContainerError>> + 'static {
+        let (ready_fut, backend, script, timeout) = (self.ready_fut(), Rc::clone(&self.backend), script.to_string(), self.script_timeout.get());
+        async move {
+            let WebviewHandles { webview, .. } = ready_fut?.await?;
+            script::evaluate(&*backend, &webview, &script, timeout, timer::delay).await
+        }
+    }
+    /// 由`WebviewContainerBuilder::init_script()`与`init_script_file()`追加的第`index`个（从`0`起）初始化脚本的`id`。
+    /// 脚本注入是异步的，所以在注入完成之前（或在脚本被移除之后），它是`None`。
+    pub fn init_script_id(&self, index: usize) -> Option<String> {
+        self.init_script_ids.borrow().get(index).cloned().flatten()
+    }
+    /// 移除初始化脚本。它仅影响之后被加载的网页。
+    pub fn remove_init_script(&self, id: &str) -> Result<(), WebviewContainerError> {
+        if *self.is_closing.borrow() {
+            return Err(WebviewContainerError::AlreadyClosed);
+        }
+        let webview = self.webview.borrow();
+        let webview = webview.as_ref().ok_or(WebviewContainerError::NotReady)?;
+        init_script::remove(&*self.backend, webview, &self.init_script_ids, id)
+    }
+    /// `eval()`的超时。默认值是[`DEFAULT_SCRIPT_TIMEOUT`](crate::DEFAULT_SCRIPT_TIMEOUT)。
+    pub fn set_script_timeout(&self, timeout: Duration) {
+        self.script_timeout.set(timeout);
+    }
+    /// 订阅网页事件。事件流仅收到订阅之后发生的事件，并在`WebviewContainer`被析构之后结束。
+    /// ```ignore
+    /// let mut events = webview_container.events();
+    /// executor.spawner().spawn_local(async move {
+    ///     while let Some(event) = events.next().await { ... }
+    /// })?;
+    /// ```
+    pub fn events(&self) -> WebviewEvents {
+        self.events.subscribe()
+    }
+    /// 主窗体的当前状态。它由主窗体的`WM_SIZE`、`WM_SHOWWINDOW`与`WM_SYSCOMMAND`消息驱动。
+    pub fn window_state(&self) -> WindowState {
+        self.window_state.borrow().state()
+    }
+    /// `webview`控件光栅化缩放比的来源
+    pub fn scale_mode(&self) -> ScaleMode {
+        self.scale_mode.get()
+    }
+    /// 设置`webview`控件光栅化缩放比的来源。`ScaleMode::Auto`跟随主窗体所在显示器的`DPI`，而`ScaleMode::Fixed`不随显示器变化。
+    /// 在`webview`控件就绪之前被设置的缩放比会在其就绪时生效。
+    pub fn set_scale_mode(&self, scale_mode: ScaleMode) -> Result<(), WebviewContainerError> {
+        match scale_mode {
+            ScaleMode::Fixed(scale) if !scale_mode.is_valid() => return Err(WebviewContainerError::InvalidScale(scale.to_string())),
+            _ => self.scale_mode.set(scale_mode)
+        }
+        self.with_controller(|backend, controller, _| dpi::apply(backend, controller, scale_mode, self.dpi.get()))
+    }
+    /// `webview`控件是否已被挂起策略挂起了
+    pub fn is_suspended(&self) -> bool {
+        self.suspend.borrow().is_suspended()
+    }
+    /// `RPC`服务循环。它需要被`spawn`到`UI`线程的`LocalPool`内，并在`WebviewContainer`被析构之后结束。
+    /// ```ignore
+    /// executor.spawner().spawn_local(webview_container.rpc_server()?)?;
+    /// ```
+    /// 未启用`RPC`时，返回[`WebviewContainerError::RpcDisabled`]。
+    pub fn rpc_server(&self) -> Result<impl Future<Output = ()>, WebviewContainerError> {
+        if !self.rpc_enabled {
+            return Err(WebviewContainerError::RpcDisabled);
+        }
+        let requests = self.rpc_requests.borrow_mut().take().ok_or(WebviewContainerError::RpcServerTaken)?;
+        let bridge = self.bridge::<Value, RpcResponse>(rpc::RPC_CHANNEL);
+        let span = self.span.clone();
+        Ok(rpc::serve(Rc::clone(&self.rpc), requests, move |response| {
+            if let Err(err) = bridge.post(response) {
+                let _span = span.enter();
+                error!("[WebviewContainer][rpc_server]{err}");
+            }
+        }, timer::delay))
+    }
+}
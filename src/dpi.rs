@@ -0,0 +1,145 @@
+//! 逐显示器`DPI`感知：主窗体在`100%`与`150%`缩放比的显示器之间移动时，同步`webview`控件的光栅化缩放比与边界矩形。
+//!
+//! 收到`WM_DPICHANGED`消息后，
+//! 1. `webview`控件的边界矩形按容器`nwg::Frame`的最新客户区被同步；
+//! 2. `webview`控件的光栅化缩放比被更新为[`ScaleMode::rasterization_scale()`]。
+//!
+//! 将主窗体移至系统建议的矩形是主程序自己的事，这里不移动主窗体。
+use super::{backend::{Bounds, WebviewBackend}, error::WebviewContainerError};
+
+const WM_DPICHANGED: u32 = 0x02E0;
+/// `100%`缩放比的`DPI`
+pub const USER_DEFAULT_SCREEN_DPI: u32 = 96;
+/// `webview`控件光栅化缩放比的来源
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScaleMode {
+    /// 跟随主窗体所在显示器的`DPI`
+    #[default]
+    Auto,
+    /// 固定的缩放比。比如，`1.5`。它不随显示器变化。
+    Fixed(f64)
+}
+impl ScaleMode {
+    /// 在`dpi`显示器上的光栅化缩放比
+    pub fn rasterization_scale(&self, dpi: u32) -> f64 {
+        match self {
+            Self::Auto => scale_for_dpi(dpi),
+            Self::Fixed(scale) => *scale
+        }
+    }
+    /// 是否需要`webview`控件自己检测显示器缩放比的变化
+    pub fn detects_monitor_scale_changes(&self) -> bool {
+        matches!(self, Self::Auto)
+    }
+    /// 固定缩放比是否合法。它必须是正的有限数。
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Auto => true,
+            Self::Fixed(scale) => scale.is_finite() && *scale > 0.0
+        }
+    }
+}
+/// `dpi`对应的缩放比。比如，`144 DPI`对应于`1.5`。`0`被视为`100%`。
+pub fn scale_for_dpi(dpi: u32) -> f64 {
+    match dpi {
+        0 => 1.0,
+        dpi => f64::from(dpi) / f64::from(USER_DEFAULT_SCREEN_DPI)
+    }
+}
+/// 若原始的`Win32`消息是`WM_DPICHANGED`，则返回新的`DPI`。`wparam`的低位字与高位字分别是`X`轴与`Y`轴的`DPI`，且二者总是相等的。
+pub fn dpi_changed(msg: u32, wparam: usize) -> Option<u32> {
+    (msg == WM_DPICHANGED).then_some((wparam & 0xFFFF) as u32)
+}
+/// 按`DPI`换算长度。与`MulDiv`一样，四舍五入至最近的整数。
+pub fn scale_length(length: i32, from_dpi: u32, to_dpi: u32) -> i32 {
+    if from_dpi == 0 || from_dpi == to_dpi {
+        return length;
+    }
+    let scaled = (i64::from(length) * i64::from(to_dpi) * 2 + i64::from(from_dpi) * i64::from(length.signum())) / (i64::from(from_dpi) * 2);
+    scaled.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
+/// 按`DPI`换算矩形：左上角不动，而宽与高被等比缩放。
+pub fn scale_bounds(bounds: Bounds, from_dpi: u32, to_dpi: u32) -> Bounds {
+    Bounds {
+        right: bounds.left.saturating_add(scale_length(bounds.width(), from_dpi, to_dpi)),
+        bottom: bounds.top.saturating_add(scale_length(bounds.height(), from_dpi, to_dpi)),
+        ..bounds
+    }
+}
+/// 将缩放比同步给浏览器控件。`dpi`是主窗体所在显示器的`DPI`。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn apply<B: WebviewBackend>(backend: &B, controller: &B::Controller, scale_mode: ScaleMode, dpi: u32) -> Result<(), WebviewContainerError> {
+    backend.put_should_detect_monitor_scale_changes(controller, scale_mode.detects_monitor_scale_changes())
+        .and_then(|_| backend.put_rasterization_scale(controller, scale_mode.rasterization_scale(dpi)))
+        .map_err(WebviewContainerError::ScaleUpdate)
+}
+#[cfg(test)]
+mod tests {
+    use crate::backend::{BackendCall, RecordingBackend};
+    use super::*;
+
+    #[test]
+    fn scales_for_dpi() {
+        assert_eq!(scale_for_dpi(96), 1.0);
+        assert_eq!(scale_for_dpi(120), 1.25);
+        assert_eq!(scale_for_dpi(144), 1.5);
+        assert_eq!(scale_for_dpi(192), 2.0);
+        assert_eq!(scale_for_dpi(0), 1.0);
+        assert_eq!(ScaleMode::Auto.rasterization_scale(144), 1.5);
+        assert_eq!(ScaleMode::Fixed(1.25).rasterization_scale(144), 1.25);
+    }
+    #[test]
+    fn validates_fixed_scales() {
+        assert!(ScaleMode::Auto.is_valid());
+        assert!(ScaleMode::Fixed(0.5).is_valid());
+        for scale in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(!ScaleMode::Fixed(scale).is_valid(), "{scale}");
+        }
+    }
+    #[test]
+    fn scales_lengths() {
+        let table = [
+            (100, 96, 144, 150),
+            (150, 144, 96, 100),
+            (101, 96, 144, 152), // 151.5 四舍五入
+            (1, 144, 96, 1), // 0.67 四舍五入
+            (-100, 96, 144, -150),
+            (-101, 96, 144, -152), // 负数远离零取整，与 MulDiv 一致
+            (-150, 144, 96, -100),
+            (0, 96, 144, 0),
+            (100, 0, 144, 100), // DPI 为 0 时不换算
+            (100, 96, 0, 0),
+            (100, 144, 144, 100),
+            (i32::MAX, 96, 192, i32::MAX),
+            (i32::MIN, 96, 192, i32::MIN)
+        ];
+        for (length, from_dpi, to_dpi, scaled) in table {
+            assert_eq!(scale_length(length, from_dpi, to_dpi), scaled, "{length}: {from_dpi} -> {to_dpi}");
+        }
+    }
+    #[test]
+    fn scales_bounds_around_the_top_left_corner() {
+        let bounds = Bounds { left: -200, top: 40, right: 600, bottom: 640 };
+        assert_eq!(scale_bounds(bounds, 96, 144), Bounds { left: -200, top: 40, right: 1000, bottom: 940 });
+        assert_eq!(scale_bounds(scale_bounds(bounds, 96, 144), 144, 96), bounds);
+        let far = Bounds { left: i32::MAX - 10, top: 0, right: i32::MAX, bottom: 10 };
+        assert_eq!(scale_bounds(far, 96, 192).right, i32::MAX, "饱和而不溢出");
+    }
+    #[test]
+    fn recognizes_dpi_changes() {
+        assert_eq!(dpi_changed(WM_DPICHANGED, 0x0090_0090), Some(144));
+        assert_eq!(dpi_changed(0x0005, 0x0090_0090), None);
+    }
+    #[test]
+    fn applies_the_scale_mode() {
+        let backend = RecordingBackend::default();
+        apply(&backend, &1, ScaleMode::Auto, 144).unwrap();
+        apply(&backend, &1, ScaleMode::Fixed(2.0), 144).unwrap();
+        assert_eq!(backend.calls(), vec![
+            BackendCall::PutShouldDetectMonitorScaleChanges(true),
+            BackendCall::PutRasterizationScale(1.5),
+            BackendCall::PutShouldDetectMonitorScaleChanges(false),
+            BackendCall::PutRasterizationScale(2.0)
+        ]);
+    }
+}
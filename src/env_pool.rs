@@ -0,0 +1,223 @@
+//! 浏览器环境池：按缓存目录（`user_data_folder`）共享`webview2::Environment`实例。
+//!
+//! `WebView2`要求共用同一个缓存目录的浏览器环境都采用相同的配置项；否则，后构造的浏览器环境会莫名其妙地失败。
+//! [`EnvironmentPool`]按缓存目录缓存浏览器环境，并在同一缓存目录被配以不同的配置项时，立即报告[`WebviewContainerError::EnvironmentConflict`]。
+//! 配置项的归一化与冲突判定都由[`EnvironmentKey`]完成。
+use ::deferred_future::LocalDeferredFuture;
+use ::futures::{FutureExt, future::Shared};
+use ::std::{cell::RefCell, collections::HashMap, fmt::{self, Debug, Formatter}, path::Path, rc::Rc};
+use super::{backend::WebviewBackend, browser_args::BrowserArgs, config::EnvironmentConfig, error::WebviewContainerError, log::debug, runtime::BrowserVersion};
+
+/// 共享浏览器环境的就绪结果
+pub type EnvironmentResult<B> = Result<<B as WebviewBackend>::Environment, WebviewContainerError>;
+/// 共享浏览器环境的异步锁。无论初始化成功与否，它都一定会被决议。
+pub type EnvironmentFuture<B> = Shared<LocalDeferredFuture<EnvironmentResult<B>>>;
+/// 归一化之后的浏览器环境配置项。等价的配置项（比如，仅大小写或启动参数的顺序不同）有相等的`EnvironmentKey`。
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnvironmentKey {
+    user_data_folder: String,
+    browser_executable_folder: Option<String>,
+    additional_browser_arguments: Option<String>,
+    language: Option<String>,
+    target_compatible_browser_version: Option<String>,
+    allow_single_sign_on_using_osprimary_account: bool
+}
+impl EnvironmentKey {
+    /// 归一化浏览器环境配置项
+    pub fn new(config: &EnvironmentConfig) -> Self {
+        Self {
+            user_data_folder: config.user_data_folder.as_deref().map(normalize_folder).unwrap_or_default(),
+            browser_executable_folder: config.browser_executable_folder.as_deref().map(normalize_folder),
+            additional_browser_arguments: config.additional_browser_arguments.as_deref().map(normalize_arguments).filter(|arguments| !arguments.is_empty()),
+            language: config.language.as_deref().map(str::to_ascii_lowercase),
+            target_compatible_browser_version: config.target_compatible_browser_version.as_deref()
+                .map(|version| BrowserVersion::parse(version).map_or_else(|| version.to_string(), |version| version.to_string())),
+            allow_single_sign_on_using_osprimary_account: config.allow_single_sign_on_using_osprimary_account
+        }
+    }
+    /// 归一化之后的缓存目录。空字符串表示`WebView2`的默认缓存目录。
+    pub fn user_data_folder(&self) -> &str {
+        &self.user_data_folder
+    }
+    /// 与`other`不一致的配置项名。仅在两者的缓存目录相同时，才有意义。
+    pub fn conflicts(&self, other: &Self) -> Vec<&'static str> {
+        [
+            ("browser_executable_folder", self.browser_executable_folder == other.browser_executable_folder),
+            ("additional_browser_arguments", self.additional_browser_arguments == other.additional_browser_arguments),
+            ("language", self.language == other.language),
+            ("target_compatible_browser_version", self.target_compatible_browser_version == other.target_compatible_browser_version),
+            ("allow_single_sign_on_using_osprimary_account", self.allow_single_sign_on_using_osprimary_account == other.allow_single_sign_on_using_osprimary_account)
+        ].into_iter().filter_map(|(name, is_equal)| (!is_equal).then_some(name)).collect()
+    }
+}
+/// 缓存目录的归一化配置项，与其浏览器环境
+type PoolEntry<B> = (EnvironmentKey, EnvironmentFuture<B>);
+/// 浏览器环境池。比如，
+/// ```ignore
+/// let pool = EnvironmentPool::<Webview2Backend>::default();
+/// let webview_env = pool.environment(&config.environment)?.await?;
+/// WebviewContainer::builder().webview_env(webview_env).window(&window).parent(&window).build(&mut webview_container)?;
+/// ```
+pub struct EnvironmentPool<B: WebviewBackend> {
+    backend: B,
+    entries: Rc<RefCell<HashMap<String, PoolEntry<B>>>>
+}
+impl<B: WebviewBackend + Default> Default for EnvironmentPool<B> {
+    fn default() -> Self {
+        Self::new(B::default())
+    }
+}
+impl<B: WebviewBackend> Debug for EnvironmentPool<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvironmentPool")
+            .field("user_data_folders", &self.entries.borrow().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+impl<B: WebviewBackend> EnvironmentPool<B> {
+    /// 由`backend`构造浏览器环境
+    pub fn new(backend: B) -> Self {
+        Self { backend, entries: Rc::default() }
+    }
+    /// 取出缓存目录对应的浏览器环境。若还没有，则（异步地）构造之。
+    /// 若该缓存目录已被另一组配置项占用，则返回`WebviewContainerError::EnvironmentConflict`。
+    /// 构造失败的浏览器环境不会被缓存，所以下次调用会重试。
+    pub fn environment(&self, config: &EnvironmentConfig) -> Result<EnvironmentFuture<B>, WebviewContainerError> {
+        let key = EnvironmentKey::new(config);
+        if let Some((cached_key, environment)) = self.entries.borrow().get(key.user_data_folder()) {
+            let options = cached_key.conflicts(&key);
+            if !options.is_empty() {
+                return Err(WebviewContainerError::EnvironmentConflict { user_data_folder: key.user_data_folder.clone(), options });
+            }
+            return Ok(environment.clone());
+        }
+        let environment_ready_future = LocalDeferredFuture::<EnvironmentResult<B>>::default();
+        let defer = environment_ready_future.defer();
+        let environment = environment_ready_future.shared();
+        let user_data_folder = key.user_data_folder.clone();
+        self.entries.borrow_mut().insert(user_data_folder.clone(), (key, environment.clone()));
+        debug!("[EnvironmentPool][environment]构造缓存目录 {user_data_folder:?} 的浏览器环境");
+        let entries = Rc::downgrade(&self.entries);
+        let folder = user_data_folder.clone();
+        self.backend.create_environment(&config.options(), Box::new(move |result| {
+            let result = result.map_err(WebviewContainerError::EnvironmentCreation);
+            if let (Err(_), Some(entries)) = (&result, entries.upgrade()) {
+                entries.borrow_mut().remove(&folder);
+            }
+            defer.borrow_mut().complete(result);
+        })).map_err(|err| {
+            self.entries.borrow_mut().remove(&user_data_folder);
+            WebviewContainerError::EnvironmentCreation(err)
+        })?;
+        Ok(environment)
+    }
+    /// 移出缓存目录对应的浏览器环境。已由它构造的`webview`控件不受影响。
+    pub fn remove(&self, config: &EnvironmentConfig) -> bool {
+        self.entries.borrow_mut().remove(EnvironmentKey::new(config).user_data_folder()).is_some()
+    }
+    /// 被缓存的浏览器环境个数
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+    /// 是否没有被缓存的浏览器环境
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+}
+/// 归一化`Windows`目录：统一分隔符、去掉末尾的分隔符，且不区分大小写
+fn normalize_folder(folder: &Path) -> String {
+    let folder = folder.to_string_lossy().replace('/', "\\").to_ascii_lowercase();
+    match folder.trim_end_matches('\\') {
+        "" => folder,
+        trimmed => trimmed.to_string()
+    }
+}
+/// 归一化启动参数：特性列表与开关都按名排序。无法解析的启动参数被原样保留。
+fn normalize_arguments(arguments: &str) -> String {
+    let Ok(browser_args) = BrowserArgs::parse(arguments) else {
+        return arguments.trim().to_string();
+    };
+    let (mut enabled_features, mut disabled_features) = (browser_args.enabled_features().to_vec(), browser_args.disabled_features().to_vec());
+    enabled_features.sort();
+    disabled_features.sort();
+    let mut switches: Vec<_> = browser_args.switches().collect();
+    switches.sort();
+    let normalized = enabled_features.iter().fold(BrowserArgs::new(), |normalized, feature| normalized.enable_feature(feature));
+    let normalized = disabled_features.iter().fold(normalized, |normalized, feature| normalized.disable_feature(feature));
+    switches.into_iter().try_fold(normalized, |normalized, (name, value)| match value {
+        Some(value) => normalized.switch_value(name, value),
+        None => normalized.switch(name)
+    }).map_or_else(|_| arguments.trim().to_string(), |normalized| normalized.to_string())
+}
+#[cfg(test)]
+mod tests {
+    use crate::backend::{BackendCall, BackendError, RecordingBackend};
+    use super::*;
+
+    fn config(user_data_folder: &str) -> EnvironmentConfig {
+        EnvironmentConfig { user_data_folder: Some(user_data_folder.into()), ..EnvironmentConfig::default() }
+    }
+    fn creations(backend: &RecordingBackend) -> usize {
+        backend.calls().into_iter().filter(|call| *call == BackendCall::CreateEnvironment).count()
+    }
+    #[test]
+    fn equivalent_configs_have_equal_keys() {
+        let a = EnvironmentConfig {
+            additional_browser_arguments: Some(String::from("--disable-gpu --enable-features=B,A --lang=zh-CN")),
+            language: Some(String::from("zh-CN")),
+            target_compatible_browser_version: Some(String::from("120")),
+            ..config("C:/Data/WebView/")
+        };
+        let b = EnvironmentConfig {
+            additional_browser_arguments: Some(String::from(" --lang=\"zh-CN\"  --enable-features=A --enable-features=B --disable-gpu")),
+            language: Some(String::from("zh-cn")),
+            target_compatible_browser_version: Some(String::from("120.0.0.0")),
+            ..config("c:\\data\\webview")
+        };
+        assert_eq!(EnvironmentKey::new(&a), EnvironmentKey::new(&b));
+        assert_eq!(EnvironmentKey::new(&a).user_data_folder(), "c:\\data\\webview");
+        assert_eq!(EnvironmentKey::new(&EnvironmentConfig::default()).user_data_folder(), "");
+        let empty_arguments = EnvironmentConfig { additional_browser_arguments: Some(String::from("  ")), ..EnvironmentConfig::default() };
+        assert_eq!(EnvironmentKey::new(&empty_arguments), EnvironmentKey::new(&EnvironmentConfig::default()));
+    }
+    #[test]
+    fn lists_conflicting_options() {
+        let a = EnvironmentKey::new(&config("data"));
+        let b = EnvironmentKey::new(&EnvironmentConfig { language: Some(String::from("en-US")), allow_single_sign_on_using_osprimary_account: true, ..config("data") });
+        assert_eq!(a.conflicts(&b), vec!["language", "allow_single_sign_on_using_osprimary_account"]);
+        assert!(a.conflicts(&a).is_empty());
+    }
+    #[test]
+    fn shares_environments_per_folder() {
+        let backend = RecordingBackend::default();
+        let pool = EnvironmentPool::new(backend.clone());
+        let first = pool.environment(&config("data")).unwrap();
+        let second = pool.environment(&config("DATA\\")).unwrap();
+        assert!(pool.environment(&config("other")).is_ok());
+        assert_eq!((creations(&backend), pool.len()), (2, 2));
+        assert!(first.now_or_never().unwrap().is_ok() && second.now_or_never().unwrap().is_ok());
+        assert!(pool.remove(&config("Data")));
+        assert!(!pool.remove(&config("data")));
+        assert!(pool.environment(&config("data")).is_ok());
+        assert_eq!(creations(&backend), 3);
+    }
+    #[test]
+    fn reports_conflicts_immediately() {
+        let pool = EnvironmentPool::new(RecordingBackend::default());
+        assert!(pool.environment(&config("data")).is_ok());
+        let conflict = pool.environment(&EnvironmentConfig { language: Some(String::from("en-US")), ..config("data") }).unwrap_err();
+        assert_eq!(conflict, WebviewContainerError::EnvironmentConflict { user_data_folder: String::from("data"), options: vec!["language"] });
+        assert_eq!(pool.len(), 1);
+    }
+    #[test]
+    fn failed_environments_are_not_cached() {
+        let backend = RecordingBackend::default();
+        backend.fail_environment_creation(BackendError::new(-1, "构造失败"));
+        let pool = EnvironmentPool::new(backend.clone());
+        let environment = pool.environment(&config("data")).unwrap();
+        assert!(matches!(environment.now_or_never(), Some(Err(WebviewContainerError::EnvironmentCreation(_)))));
+        assert!(pool.is_empty());
+        assert!(pool.environment(&config("data")).is_ok());
+        assert_eq!(creations(&backend), 2);
+    }
+}
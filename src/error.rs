@@ -0,0 +1,222 @@
+use ::std::{error::Error, fmt::{self, Display, Formatter}, path::PathBuf};
+#[cfg(windows)]
+use ::nwg::NwgError;
+use super::{backend::BackendError, browser_args::BrowserArgsError, config::ConfigError, navigate::NavigationError, runtime::RuntimeStatus, script::ScriptError};
+
+/// `HRESULT_FROM_WIN32(ERROR_FILE_NOT_FOUND)`：本机未安装`WebView2 Runtime`时，构造浏览器环境所返回的错误码。
+const HRESULT_RUNTIME_NOT_FOUND: i32 = 0x8007_0002_u32 as i32;
+/// `WebviewContainer`与`WebviewContainerBuilder`的错误类型。
+///
+/// 应用程序可以对错误原因做模式匹配。比如，在[`WebviewContainerError::is_runtime_missing()`]时，展示【WebView2 Runtime 未安装】的引导界面。
+/// 在`NWG`的构造上下文中（比如，`nwd`派生宏生成的`build_ui()`），它会经由`?`被自动转换为`nwg::NwgError`。
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WebviewContainerError {
+    /// 未配置`window`主窗体。它是必填项
+    MissingWindow,
+    /// 主窗体不是有效的`Win32`窗体
+    InvalidWindowHandle,
+    /// 容器控件`nwg::Frame`初始化失败
+    FrameBuild(String),
+    /// 挂载主窗体的事件处理函数失败
+    EventBinding(String),
+    /// 浏览器环境`webview2::Environment`初始化失败
+    EnvironmentCreation(BackendError),
+    /// 浏览器控件`webview2::Controller`初始化失败
+    ControllerCreation(BackendError),
+    /// 调整浏览器控件的边界矩形失败
+    BoundsUpdate(BackendError),
+    /// 切换浏览器控件的可见性失败
+    VisibilityUpdate(BackendError),
+    /// 将键盘焦点移入浏览器控件失败
+    FocusChange(BackendError),
+    /// 浏览器控件还未被初始化
+    NotReady,
+    /// 浏览器控件已经被关闭了
+    AlreadyClosed,
+    /// 网页消息的序列化或反序列化失败
+    Serialization(String),
+    /// 发送网页消息失败
+    WebMessage(BackendError),
+    /// 注入网页脚本失败
+    ScriptInjection(BackendError),
+    /// 读取初始化脚本文件失败
+    InitScriptFile {
+        /// 脚本文件路径
+        path: PathBuf,
+        /// 失败原因
+        message: String
+    },
+    /// 移除初始化脚本失败
+    ScriptRemoval(BackendError),
+    /// 挂载网络请求拦截失败
+    ResourceInterception(BackendError),
+    /// 挂载导航事件处理函数失败
+    NavigationHook(BackendError),
+    /// 订阅网页事件失败
+    EventSubscription(BackendError),
+    /// 发起导航失败
+    NavigationRequest(BackendError),
+    /// 导航失败、被取代或超时了
+    Navigation(NavigationError),
+    /// 执行网页脚本失败
+    ScriptExecution(BackendError),
+    /// 网页脚本抛出了异常、结果反序列化失败或超时了
+    Script(ScriptError),
+    /// 更新光栅化缩放比失败
+    ScaleUpdate(BackendError),
+    /// 固定缩放比不是正的有限数。携带了该缩放比的文本
+    InvalidScale(String),
+    /// `RPC`服务循环已经被取走了。每个`WebviewContainer`仅有一个服务循环
+    RpcServerTaken,
+    /// `RPC`没有被启用。见`WebviewContainerBuilder::rpc()`
+    RpcDisabled,
+    /// 声明式配置不合法
+    Config(ConfigError),
+    /// 浏览器启动参数不合法
+    BrowserArguments(BrowserArgsError),
+    /// 同一个缓存目录`user_data_folder`被配以不同的浏览器环境配置项
+    EnvironmentConflict {
+        /// 归一化之后的缓存目录。空字符串表示默认缓存目录
+        user_data_folder: String,
+        /// 不一致的配置项名
+        options: Vec<&'static str>
+    },
+    /// `WebView2 Runtime`未安装或版本过低。容器内显示的是替代界面`RuntimeFallback`
+    RuntimeUnavailable(RuntimeStatus)
+}
+impl WebviewContainerError {
+    /// 是否因为本机未安装`WebView2 Runtime`而失败
+    pub fn is_runtime_missing(&self) -> bool {
+        matches!(self, Self::EnvironmentCreation(err) if err.code() == HRESULT_RUNTIME_NOT_FOUND) || matches!(self, Self::RuntimeUnavailable(RuntimeStatus::Missing))
+    }
+    /// 底层后端错误（若有）
+    pub fn backend_error(&self) -> Option<&BackendError> {
+        match self {
+            Self::EnvironmentCreation(err) | Self::ControllerCreation(err) | Self::BoundsUpdate(err) | Self::VisibilityUpdate(err) | Self::FocusChange(err) | Self::WebMessage(err) |
+            Self::ScriptInjection(err) | Self::ScriptRemoval(err) | Self::ScriptExecution(err) | Self::ResourceInterception(err) |
+            Self::NavigationHook(err) | Self::EventSubscription(err) | Self::NavigationRequest(err) | Self::ScaleUpdate(err) => Some(err),
+            _ => None
+        }
+    }
+}
+impl Display for WebviewContainerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingWindow => write!(f, "window 配置项代表了主窗体。它是必填项"),
+            Self::InvalidWindowHandle => write!(f, "主窗体不是有效的 Win32 COM 控件"),
+            Self::FrameBuild(message) => write!(f, "Webview 容器控件 Frame 初始化失败：{message}"),
+            Self::EventBinding(message) => write!(f, "主窗体事件处理函数挂载失败：{message}"),
+            Self::EnvironmentCreation(err) => write!(f, "Webview 浏览器环境初始化失败：{err}"),
+            Self::ControllerCreation(err) => write!(f, "Webview 控件初始化失败：{err}"),
+            Self::BoundsUpdate(err) => write!(f, "Webview 控件尺寸调整失败：{err}"),
+            Self::VisibilityUpdate(err) => write!(f, "Webview 控件可见性切换失败：{err}"),
+            Self::FocusChange(err) => write!(f, "Webview 控件聚焦失败：{err}"),
+            Self::NotReady => write!(f, "Webview 控件还未被初始化"),
+            Self::AlreadyClosed => write!(f, "Webview 控件已经被关闭了"),
+            Self::Serialization(message) => write!(f, "网页消息序列化失败：{message}"),
+            Self::WebMessage(err) => write!(f, "网页消息发送失败：{err}"),
+            Self::ScriptInjection(err) => write!(f, "网页脚本注入失败：{err}"),
+            Self::InitScriptFile { path, message } => write!(f, "初始化脚本 {} 读取失败：{message}", path.display()),
+            Self::ScriptRemoval(err) => write!(f, "初始化脚本移除失败：{err}"),
+            Self::ResourceInterception(err) => write!(f, "网络请求拦截挂载失败：{err}"),
+            Self::NavigationHook(err) => write!(f, "导航事件处理函数挂载失败：{err}"),
+            Self::EventSubscription(err) => write!(f, "网页事件订阅失败：{err}"),
+            Self::NavigationRequest(err) => write!(f, "导航发起失败：{err}"),
+            Self::Navigation(err) => write!(f, "{err}"),
+            Self::ScriptExecution(err) => write!(f, "网页脚本执行失败：{err}"),
+            Self::Script(err) => write!(f, "{err}"),
+            Self::ScaleUpdate(err) => write!(f, "光栅化缩放比更新失败：{err}"),
+            Self::InvalidScale(scale) => write!(f, "缩放比 {scale} 不是正的有限数"),
+            Self::RpcServerTaken => write!(f, "RPC 服务循环已经被取走了"),
+            Self::RpcDisabled => write!(f, "RPC 没有被启用：请先调用 WebviewContainerBuilder::rpc()"),
+            Self::Config(err) => write!(f, "{err}"),
+            Self::BrowserArguments(err) => write!(f, "{err}"),
+            Self::EnvironmentConflict { user_data_folder, options } => write!(f, "缓存目录 {user_data_folder:?} 已被另一组浏览器环境配置项占用了：{} 不一致", options.join("、")),
+            Self::RuntimeUnavailable(status) => write!(f, "{status}")
+        }
+    }
+}
+impl Error for WebviewContainerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Config(err) => Some(err),
+            Self::BrowserArguments(err) => Some(err),
+            Self::Navigation(err) => Some(err),
+            Self::Script(err) => Some(err),
+            _ => self.backend_error().map(|err| -> &(dyn Error + 'static) { err })
+        }
+    }
+}
+impl From<ConfigError> for WebviewContainerError {
+    fn from(value: ConfigError) -> Self {
+        Self::Config(value)
+    }
+}
+#[cfg(windows)]
+impl From<WebviewContainerError> for NwgError {
+    fn from(value: WebviewContainerError) -> Self {
+        match value {
+            WebviewContainerError::MissingWindow => NwgError::initialization(value.to_string()),
+            WebviewContainerError::EventBinding(_) => NwgError::events_binding(value.to_string()),
+            _ => NwgError::control_create(value.to_string())
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use ::std::time::Duration;
+    use super::*;
+
+    fn runtime_not_found() -> BackendError {
+        BackendError::new(HRESULT_RUNTIME_NOT_FOUND, "系统找不到指定的文件")
+    }
+    #[test]
+    fn displays_the_cause() {
+        assert_eq!(WebviewContainerError::NotReady.to_string(), "Webview 控件还未被初始化");
+        assert_eq!(WebviewContainerError::EnvironmentCreation(runtime_not_found()).to_string(), "Webview 浏览器环境初始化失败：系统找不到指定的文件(HRESULT = 0x80070002)");
+        assert_eq!(WebviewContainerError::InitScriptFile { path: PathBuf::from("ui/init.js"), message: String::from("拒绝访问") }.to_string(), "初始化脚本 ui/init.js 读取失败：拒绝访问");
+        let conflict = WebviewContainerError::EnvironmentConflict { user_data_folder: String::from(r"c:\cache"), options: vec!["language", "additional_browser_arguments"] };
+        assert_eq!(conflict.to_string(), r#"缓存目录 "c:\\cache" 已被另一组浏览器环境配置项占用了：language、additional_browser_arguments 不一致"#);
+        assert_eq!(WebviewContainerError::RuntimeUnavailable(RuntimeStatus::Missing).to_string(), "WebView2 Runtime 未安装");
+        assert_eq!(WebviewContainerError::Script(ScriptError::TimedOut(Duration::from_secs(1))).to_string(), "网页脚本执行超过了 1s", "透传内层错误的文本");
+    }
+    #[test]
+    fn detects_a_missing_runtime() {
+        assert!(WebviewContainerError::EnvironmentCreation(runtime_not_found()).is_runtime_missing());
+        assert!(WebviewContainerError::RuntimeUnavailable(RuntimeStatus::Missing).is_runtime_missing());
+        assert!(!WebviewContainerError::ControllerCreation(runtime_not_found()).is_runtime_missing(), "仅浏览器环境的构造失败才意味着未安装");
+        assert!(!WebviewContainerError::EnvironmentCreation(BackendError::new(-1, "未知错误")).is_runtime_missing());
+        assert!(!WebviewContainerError::NotReady.is_runtime_missing());
+    }
+    #[test]
+    fn exposes_the_source() {
+        let err = WebviewContainerError::BoundsUpdate(BackendError::new(-1, "尺寸调整失败"));
+        assert_eq!(err.source().map(ToString::to_string), Some(BackendError::new(-1, "尺寸调整失败").to_string()));
+        assert_eq!(err.backend_error(), Some(&BackendError::new(-1, "尺寸调整失败")));
+        let err = WebviewContainerError::from(ConfigError::Parse(String::from("第 1 行")));
+        assert_eq!(err, WebviewContainerError::Config(ConfigError::Parse(String::from("第 1 行"))));
+        assert_eq!(err.source().map(ToString::to_string), Some(ConfigError::Parse(String::from("第 1 行")).to_string()));
+        let err = WebviewContainerError::BrowserArguments(BrowserArgsError::UnterminatedQuote);
+        assert_eq!(err.source().map(ToString::to_string), Some(String::from("启动参数的双引号未闭合")));
+        assert!(WebviewContainerError::MissingWindow.source().is_none());
+        assert!(WebviewContainerError::MissingWindow.backend_error().is_none());
+    }
+    #[cfg(windows)]
+    #[test]
+    fn converts_into_nwg_errors() {
+        let convert = |err: WebviewContainerError| (err.to_string(), NwgError::from(err));
+        match convert(WebviewContainerError::MissingWindow) {
+            (message, NwgError::InitializationError(converted)) => assert_eq!(converted, message),
+            other => panic!("{other:?}")
+        }
+        match convert(WebviewContainerError::EventBinding(String::from("已被绑定"))) {
+            (message, NwgError::EventsBinding(converted)) => assert_eq!(converted, message),
+            other => panic!("{other:?}")
+        }
+        match convert(WebviewContainerError::NotReady) {
+            (message, NwgError::ControlCreationError(converted)) => assert_eq!(converted, message),
+            other => panic!("{other:?}")
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! 网页事件流：将`webview`控件的事件回调转换为`futures::Stream`。
+//!
+//! ```ignore
+//! let mut events = webview_container.events();
+//! while let Some(event) = events.next().await {
+//!     if let WebviewEvent::DocumentTitleChanged(title) = event { ... }
+//! }
+//! ```
+//! 每个订阅者都有自己的无界队列，且仅收到订阅之后发生的事件。`WebviewContainer`被析构之后，事件流随之结束。
+use ::futures::{Stream, channel::mpsc::{self, UnboundedReceiver, UnboundedSender}};
+use ::std::{cell::RefCell, pin::Pin, rc::{Rc, Weak}, task::{Context, Poll}};
+use super::backend::{BackendResult, WebviewBackend};
+
+/// 一次导航的开始。重定向也会产生该事件，且其导航序号不变。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NavigationStarting {
+    /// 导航序号
+    pub navigation_id: u64,
+    /// 目标地址
+    pub uri: String
+}
+/// 一次导航的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NavigationCompleted {
+    /// 导航序号。同一次导航的各个事件有相同的序号。
+    pub navigation_id: u64,
+    /// 是否导航成功
+    pub is_success: bool,
+    /// 导航失败的原因，即`COREWEBVIEW2_WEB_ERROR_STATUS`的枚举值
+    pub web_error_status: u32,
+    /// `HTTP`状态码。`WebView2 Runtime`过旧（不支持`ICoreWebView2NavigationCompletedEventArgs2`），或导航没有`HTTP`响应时，它是`None`。
+    pub http_status_code: Option<u16>
+}
+/// 浏览器子进程的失败类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessFailure {
+    /// 浏览器进程退出了。`webview`控件需要被重建。
+    BrowserProcessExited,
+    /// 渲染进程退出了。网页需要被重新加载。
+    RenderProcessExited,
+    /// 渲染进程无响应
+    RenderProcessUnresponsive,
+    /// 其它子进程（比如，子框架的渲染进程、工具进程）失败了。实参是`COREWEBVIEW2_PROCESS_FAILED_KIND`的原始值。
+    Other(u32)
+}
+/// 网页事件
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WebviewEvent {
+    /// 开始导航
+    NavigationStarting(NavigationStarting),
+    /// 导航结束
+    NavigationCompleted(NavigationCompleted),
+    /// 网页标题变化了。携带了新标题。
+    DocumentTitleChanged(String),
+    /// 网页发来一条消息。携带了`JSON`字符串。
+    WebMessage(String),
+    /// 浏览器子进程失败了
+    ProcessFailed(ProcessFailure),
+    /// 网页请求打开新窗体。携带了目标地址。
+    NewWindowRequested(String)
+}
+/// 网页事件的广播器。每次[`EventBroadcaster::subscribe()`]都会得到一个独立的事件流。
+#[derive(Debug, Default)]
+pub struct EventBroadcaster {
+    subscribers: RefCell<Vec<UnboundedSender<WebviewEvent>>>
+}
+impl EventBroadcaster {
+    /// 订阅之后发生的网页事件
+    pub fn subscribe(&self) -> WebviewEvents {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.borrow_mut().push(sender);
+        WebviewEvents { receiver }
+    }
+    /// 将事件广播给每个订阅者。已被丢弃的事件流会被顺便清理掉。返回值是接收到该事件的订阅者个数。
+    pub fn broadcast(&self, event: &WebviewEvent) -> usize {
+        let mut subscribers = self.subscribers.borrow_mut();
+        subscribers.retain(|subscriber| subscriber.unbounded_send(event.clone()).is_ok());
+        subscribers.len()
+    }
+    /// 仍存活的订阅者个数
+    pub fn subscriber_count(&self) -> usize {
+        let mut subscribers = self.subscribers.borrow_mut();
+        subscribers.retain(|subscriber| !subscriber.is_closed());
+        subscribers.len()
+    }
+}
+/// 网页事件流。在广播器被析构之后，它会结束（即，产出`None`）。
+#[derive(Debug)]
+pub struct WebviewEvents {
+    receiver: UnboundedReceiver<WebviewEvent>
+}
+impl Stream for WebviewEvents {
+    type Item = WebviewEvent;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<WebviewEvent>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.receiver.size_hint()
+    }
+}
+/// 将网页视图的各个事件转发给广播器。广播器仅被弱引用，所以它的析构不受网页视图的生命周期影响。
+///
+/// 导航与新窗体事件的处理函数既不取消导航，也不接管新窗体。所以，它们不干扰导航策略。
+pub fn forward_events<B: WebviewBackend>(backend: &B, webview: &B::WebView, broadcaster: &Rc<EventBroadcaster>) -> BackendResult<()> {
+    let weak = Rc::downgrade(broadcaster);
+    backend.observe_navigation_starting(webview, Box::new(move |starting| broadcast(&weak, WebviewEvent::NavigationStarting(starting.clone()))))?;
+    backend.add_navigation_completed(webview, Box::new(forwarder(broadcaster, WebviewEvent::NavigationCompleted)))?;
+    backend.add_document_title_changed(webview, Box::new(forwarder(broadcaster, WebviewEvent::DocumentTitleChanged)))?;
+    backend.add_web_message_received(webview, Box::new(forwarder(broadcaster, WebviewEvent::WebMessage)))?;
+    backend.add_process_failed(webview, Box::new(forwarder(broadcaster, WebviewEvent::ProcessFailed)))?;
+    let weak = Rc::downgrade(broadcaster);
+    backend.add_new_window_requested(webview, Box::new(move |uri| {
+        broadcast(&weak, WebviewEvent::NewWindowRequested(uri.to_string()));
+        false
+    }))
+}
+fn forwarder<T: 'static>(broadcaster: &Rc<EventBroadcaster>, to_event: fn(T) -> WebviewEvent) -> impl FnMut(T) + 'static {
+    let broadcaster = Rc::downgrade(broadcaster);
+    move |payload| broadcast(&broadcaster, to_event(payload))
+}
+fn broadcast(broadcaster: &Weak<EventBroadcaster>, event: WebviewEvent) {
+    if let Some(broadcaster) = broadcaster.upgrade() {
+        broadcaster.broadcast(&event);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use ::futures::{FutureExt, StreamExt};
+    use crate::backend::RecordingBackend;
+    use super::*;
+
+    /// 同步地取出事件流里已就绪的全部事件。外层`None`表示事件流已结束。
+    fn drain(events: &mut WebviewEvents) -> Option<Vec<WebviewEvent>> {
+        let mut drained = Vec::new();
+        loop {
+            match events.next().now_or_never() {
+                Some(Some(event)) => drained.push(event),
+                Some(None) => return None,
+                None => return Some(drained)
+            }
+        }
+    }
+    #[test]
+    fn every_subscriber_gets_its_own_stream() {
+        let broadcaster = EventBroadcaster::default();
+        let mut first = broadcaster.subscribe();
+        assert_eq!(broadcaster.broadcast(&WebviewEvent::WebMessage(String::from("1"))), 1);
+        let mut second = broadcaster.subscribe();
+        assert_eq!(broadcaster.broadcast(&WebviewEvent::WebMessage(String::from("2"))), 2);
+        assert_eq!(drain(&mut first), Some(vec![WebviewEvent::WebMessage(String::from("1")), WebviewEvent::WebMessage(String::from("2"))]));
+        assert_eq!(drain(&mut second), Some(vec![WebviewEvent::WebMessage(String::from("2"))]));
+    }
+    #[test]
+    fn dropped_subscribers_are_pruned() {
+        let broadcaster = EventBroadcaster::default();
+        let (first, mut second) = (broadcaster.subscribe(), broadcaster.subscribe());
+        assert_eq!(broadcaster.subscriber_count(), 2);
+        drop(first);
+        assert_eq!(broadcaster.subscriber_count(), 1);
+        assert_eq!(broadcaster.broadcast(&WebviewEvent::DocumentTitleChanged(String::from("标题"))), 1);
+        assert_eq!(drain(&mut second), Some(vec![WebviewEvent::DocumentTitleChanged(String::from("标题"))]));
+    }
+    #[test]
+    fn streams_end_with_the_broadcaster() {
+        let broadcaster = EventBroadcaster::default();
+        let mut events = broadcaster.subscribe();
+        broadcaster.broadcast(&WebviewEvent::ProcessFailed(ProcessFailure::RenderProcessExited));
+        drop(broadcaster);
+        assert_eq!(events.next().now_or_never(), Some(Some(WebviewEvent::ProcessFailed(ProcessFailure::RenderProcessExited))));
+        assert_eq!(drain(&mut events), None);
+    }
+    #[test]
+    fn forwards_backend_events() {
+        let backend = RecordingBackend::default();
+        let broadcaster = Rc::new(EventBroadcaster::default());
+        forward_events(&backend, &(), &broadcaster).unwrap();
+        let mut events = broadcaster.subscribe();
+        let completed = NavigationCompleted { navigation_id: 1, is_success: false, web_error_status: 7, http_status_code: Some(404) };
+        assert!(!backend.start_navigation("https://app.local/"));
+        backend.complete_navigation(completed);
+        backend.change_document_title("标题");
+        assert_eq!(backend.deliver_web_message(r#"{"a":1}"#), 1);
+        backend.fail_process(ProcessFailure::BrowserProcessExited);
+        assert!(!backend.request_new_window("https://example.com/"), "事件流不应接管新窗体");
+        assert_eq!(drain(&mut events), Some(vec![
+            WebviewEvent::NavigationStarting(NavigationStarting { navigation_id: 1, uri: String::from("https://app.local/") }),
+            WebviewEvent::NavigationCompleted(completed),
+            WebviewEvent::DocumentTitleChanged(String::from("标题")),
+            WebviewEvent::WebMessage(String::from(r#"{"a":1}"#)),
+            WebviewEvent::ProcessFailed(ProcessFailure::BrowserProcessExited),
+            WebviewEvent::NewWindowRequested(String::from("https://example.com/"))
+        ]));
+    }
+    #[test]
+    fn forwarding_outlives_the_broadcaster() {
+        let backend = RecordingBackend::default();
+        let broadcaster = Rc::new(EventBroadcaster::default());
+        forward_events(&backend, &(), &broadcaster).unwrap();
+        let mut events = broadcaster.subscribe();
+        drop(broadcaster);
+        backend.change_document_title("标题");
+        assert_eq!(drain(&mut events), None);
+    }
+}
@@ -0,0 +1,36 @@
+use ::nwg::{ControlHandle, Button, Frame, HTextAlign, Label, NwgError};
+use ::winapi::shared::windef::HWND;
+use super::runtime::{RuntimeFallback, RuntimeStatus};
+
+const LABEL_HEIGHT: u32 = 48;
+const BUTTON_SIZE: (u32, u32) = (200, 32);
+const SPACING: u32 = 8;
+/// `WebView2 Runtime`不可用时，被绘制于容器`nwg::Frame`内的提示文字与下载按钮
+pub(crate) struct FallbackUi {
+    label: Label,
+    button: Button,
+    download_url: String
+}
+impl FallbackUi {
+    pub(crate) fn build(frame: &Frame, fallback: RuntimeFallback, status: RuntimeStatus) -> Result<Self, NwgError> {
+        let mut fallback_ui = Self { label: Label::default(), button: Button::default(), download_url: fallback.download_url };
+        let message = format!("{}\r\n{status}", fallback.message);
+        Label::builder().text(&message).h_align(HTextAlign::Center).parent(frame).build(&mut fallback_ui.label)?;
+        Button::builder().text(&fallback.button_text).parent(frame).build(&mut fallback_ui.button)?;
+        let (width, height) = frame.size();
+        fallback_ui.layout(width, height);
+        Ok(fallback_ui)
+    }
+    /// 令提示文字与下载按钮居中于容器
+    pub(crate) fn layout(&self, width: u32, height: u32) {
+        let top = height.saturating_sub(LABEL_HEIGHT + SPACING + BUTTON_SIZE.1) / 2;
+        self.label.set_position(0, top as i32);
+        self.label.set_size(width, LABEL_HEIGHT);
+        self.button.set_position((width.saturating_sub(BUTTON_SIZE.0) / 2) as i32, (top + LABEL_HEIGHT + SPACING) as i32);
+        self.button.set_size(BUTTON_SIZE.0, BUTTON_SIZE.1);
+    }
+    /// 若`hwnd`是下载按钮，则返回下载地址
+    pub(crate) fn download_url(&self, hwnd: HWND) -> Option<&str> {
+        (ControlHandle::Hwnd(hwnd) == self.button.handle).then_some(self.download_url.as_str())
+    }
+}
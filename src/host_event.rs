@@ -0,0 +1,163 @@
+use super::{backend::{Bounds, BackendResult, WebviewBackend}, insets::Insets, window_state::WindowTransition};
+
+/// 宿主（即，主窗体与容器`nwg::Frame`）的事件。`WebviewContainer`先将`NWG`事件与原始`Win32`消息翻译为`HostEvent`，再由[`dispatch()`]转发给后端。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostEvent {
+    /// 主窗体被最小化
+    WindowMinimized,
+    /// 主窗体被隐藏了
+    WindowHidden,
+    /// 主窗体被还原
+    WindowRestored,
+    /// 主窗体或容器`nwg::Frame`被移动了
+    Moved,
+    /// 容器`nwg::Frame`被调整了大小。携带了`webview`控件的最新边界矩形。
+    Resized(Bounds),
+    /// 容器被关闭了
+    Closed
+}
+/// 将宿主事件转发给后端。
+/// 1. 当主窗体被最小化或隐藏时，隐藏`webview`控件，以减小空耗。
+/// 2. 当主窗体被还原或重新显示时，显示`webview`控件。
+/// 3. 当主窗体或容器被移动时，通知`webview`控件重新计算屏幕坐标。
+/// 4. 当容器被调整大小时，同步`webview`控件的边界矩形。
+/// 5. 当容器被关闭时，关闭`webview`控件。
+pub fn dispatch<B: WebviewBackend>(backend: &B, controller: &B::Controller, event: HostEvent) -> BackendResult<()> {
+    match event {
+        HostEvent::WindowMinimized | HostEvent::WindowHidden => backend.put_is_visible(controller, false),
+        HostEvent::WindowRestored => backend.put_is_visible(controller, true),
+        HostEvent::Moved => backend.notify_parent_window_position_changed(controller),
+        HostEvent::Resized(bounds) => backend.put_bounds(controller, bounds),
+        HostEvent::Closed => backend.close(controller)
+    }
+}
+/// 主窗体与容器`nwg::Frame`的窗体事件。`WebviewContainerBuilder`将`NWG`事件与原始`Win32`消息翻译为`WindowEvent`，再经由[`route()`]转发给后端。
+#[allow(variant_size_differences)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) enum WindowEvent {
+    /// 主窗体或容器被移动了
+    Moved,
+    /// 容器被调整了大小。携带了容器客户区（客户区坐标系）与留白。
+    Resized { client: Bounds, insets: Insets },
+    /// 主窗体的状态迁移了。`is_frame_visible`表示容器自己是否可见。
+    StateChanged { transition: WindowTransition, is_frame_visible: bool },
+    /// 容器被关闭了
+    Closed
+}
+/// 将窗体事件路由给`webview`控件。在`webview`控件就绪（即，`controller`为`Some`）之前，窗体事件都被忽略；而不产生宿主事件的状态迁移（比如，由常规到最大化）也不调用后端。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn route<B: WebviewBackend>(backend: &B, controller: Option<&B::Controller>, event: WindowEvent) -> BackendResult<()> {
+    let host_event = match event {
+        WindowEvent::Moved => Some(HostEvent::Moved),
+        WindowEvent::Resized { client, insets } => Some(HostEvent::Resized(insets.apply(client))),
+        WindowEvent::StateChanged { transition, is_frame_visible } => for_transition(transition, is_frame_visible),
+        WindowEvent::Closed => Some(HostEvent::Closed)
+    };
+    match (controller, host_event) {
+        (Some(controller), Some(host_event)) => dispatch(backend, controller, host_event),
+        _ => Ok(())
+    }
+}
+/// 主窗体状态迁移所对应的宿主事件。容器`nwg::Frame`自己被隐藏时（`is_frame_visible`为`false`），主窗体的还原不会重新显示`webview`控件。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn for_transition(transition: WindowTransition, is_frame_visible: bool) -> Option<HostEvent> {
+    transition.host_event().filter(|event| *event != HostEvent::WindowRestored || is_frame_visible)
+}
+#[cfg(test)]
+mod tests {
+    use crate::{backend::{BackendCall, RecordingBackend}, window_state::{WindowMessage, WindowState, WindowStateMachine}};
+    use super::*;
+
+    const CLIENT: Bounds = Bounds { left: 0, top: 0, right: 800, bottom: 600 };
+    #[test]
+    fn dispatch_maps_every_host_event_to_one_backend_call() {
+        let backend = RecordingBackend::default();
+        for (event, call) in [
+            (HostEvent::WindowMinimized, BackendCall::PutIsVisible(false)),
+            (HostEvent::WindowHidden, BackendCall::PutIsVisible(false)),
+            (HostEvent::WindowRestored, BackendCall::PutIsVisible(true)),
+            (HostEvent::Moved, BackendCall::NotifyParentWindowPositionChanged),
+            (HostEvent::Resized(CLIENT), BackendCall::PutBounds(CLIENT)),
+            (HostEvent::Closed, BackendCall::Close)
+        ] {
+            dispatch(&backend, &1, event).unwrap();
+            assert_eq!(backend.take_calls(), vec![call], "{event:?}");
+        }
+    }
+    #[test]
+    fn restore_is_skipped_while_the_frame_is_hidden() {
+        let restored = WindowTransition { from: WindowState::Minimized, to: WindowState::Normal };
+        let minimized = WindowTransition { from: WindowState::Normal, to: WindowState::Minimized };
+        assert_eq!(for_transition(restored, true), Some(HostEvent::WindowRestored));
+        assert_eq!(for_transition(restored, false), None);
+        assert_eq!(for_transition(minimized, false), Some(HostEvent::WindowMinimized));
+    }
+    #[test]
+    fn window_messages_drive_visibility_calls() {
+        let backend = RecordingBackend::default();
+        let mut machine = WindowStateMachine::default();
+        for message in [
+            WindowMessage::Size(WindowState::Maximized),
+            WindowMessage::SysCommand(WindowState::Minimized),
+            WindowMessage::Size(WindowState::Minimized),
+            WindowMessage::SysCommand(WindowState::Normal),
+            WindowMessage::ShowWindow(false),
+            WindowMessage::ShowWindow(true)
+        ] {
+            if let Some(event) = machine.apply(message).and_then(|transition| for_transition(transition, true)) {
+                dispatch(&backend, &1, event).unwrap();
+            }
+        }
+        assert_eq!(machine.state(), WindowState::Maximized);
+        assert_eq!(backend.calls(), vec![
+            BackendCall::PutIsVisible(false),
+            BackendCall::PutIsVisible(true),
+            BackendCall::PutIsVisible(false),
+            BackendCall::PutIsVisible(true)
+        ]);
+    }
+    #[test]
+    fn window_events_are_routed_to_the_controller() {
+        let backend = RecordingBackend::default();
+        let transition = |from, to| WindowEvent::StateChanged { transition: WindowTransition { from, to }, is_frame_visible: true };
+        for event in [
+            WindowEvent::Resized { client: CLIENT, insets: Insets { left: 0, top: 32, right: 0, bottom: 0 } },
+            transition(WindowState::Normal, WindowState::Minimized),
+            transition(WindowState::Minimized, WindowState::Normal),
+            transition(WindowState::Normal, WindowState::Maximized),
+            WindowEvent::Moved,
+            WindowEvent::Closed
+        ] {
+            route(&backend, Some(&1), event).unwrap();
+        }
+        assert_eq!(backend.calls(), vec![
+            BackendCall::PutBounds(Bounds { left: 0, top: 32, right: 800, bottom: 600 }),
+            BackendCall::PutIsVisible(false),
+            BackendCall::PutIsVisible(true),
+            BackendCall::NotifyParentWindowPositionChanged,
+            BackendCall::Close
+        ]);
+    }
+    #[test]
+    fn window_events_are_ignored_until_the_controller_is_ready() {
+        let backend = RecordingBackend::default();
+        for event in [WindowEvent::Moved, WindowEvent::Resized { client: CLIENT, insets: Insets::ZERO }, WindowEvent::Closed] {
+            route(&backend, None, event).unwrap();
+        }
+        assert_eq!(backend.calls(), Vec::new());
+        let restored = WindowEvent::StateChanged { transition: WindowTransition { from: WindowState::Minimized, to: WindowState::Normal }, is_frame_visible: false };
+        route(&backend, Some(&1), restored).unwrap();
+        assert_eq!(backend.calls(), Vec::new(), "容器自己被隐藏时，不恢复 webview 控件");
+    }
+    #[test]
+    fn resizes_subtract_insets_from_the_client_area() {
+        let backend = RecordingBackend::default();
+        route(&backend, Some(&1), WindowEvent::Resized { client: CLIENT, insets: Insets { left: 10, top: 40, right: 0, bottom: 20 } }).unwrap();
+        route(&backend, Some(&1), WindowEvent::Resized { client: CLIENT, insets: Insets::ZERO }).unwrap();
+        assert_eq!(backend.calls(), vec![
+            BackendCall::PutBounds(Bounds { left: 10, top: 40, right: 800, bottom: 580 }),
+            BackendCall::PutBounds(CLIENT)
+        ]);
+    }
+}
@@ -0,0 +1,152 @@
+//! 宿主对象：将`Rust`方法暴露给网页，成为`window.host.<name>.<method>(...args)`。
+//!
+//! 它不依赖`COM`接口`AddHostObjectToScript`，而是建立在`RPC`之上：
+//! * 为每个宿主对象生成一段`JS`代理脚本。代理对象的每个方法都返回`Promise`，并经由`window.nwg.invoke("host.<name>.<method>", args)`发起调用；
+//! * 网页端的实参数组被反序列化为`Rust`方法的形参类型（通常是元组），而其返回值被序列化为`Promise`的结果。
+//!
+//! ```ignore
+//! webview_container.expose(HostObject::new("calc")
+//!     .method("add", |(a, b): (i32, i32)| async move { Ok(a + b) })
+//!     .method("greet", |(name,): (String,)| async move { Ok(format!("你好，{name}")) }))?;
+//! // 网页端：await window.host.calc.add(1, 2) === 3
+//! ```
+use ::futures::{future::{self, LocalBoxFuture}, FutureExt};
+use ::serde::{Serialize, de::DeserializeOwned};
+use ::serde_json::Value;
+use ::std::{fmt::{self, Debug, Formatter}, future::Future};
+use super::rpc::{self, RpcDispatcher, RpcError, RpcHandler};
+
+/// 宿主对象方法在`RPC`里的方法名。比如，`host.calc.add`
+pub fn host_method(object: &str, method: &str) -> String {
+    format!("host.{object}.{method}")
+}
+/// 暴露给网页的宿主对象
+pub struct HostObject {
+    name: String,
+    methods: Vec<(String, RpcHandler)>
+}
+impl Debug for HostObject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostObject")
+            .field("name", &self.name)
+            .field("methods", &self.method_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+impl HostObject {
+    /// 构造`window.host.<name>`宿主对象
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), methods: Vec::new() }
+    }
+    /// 追加（或替换）一个方法。网页端的实参数组会被反序列化为`P`。所以，`P`通常是元组。比如，单参数方法的`P`是`(T,)`。
+    pub fn method<P, R, F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where P: DeserializeOwned,
+          R: Serialize,
+          F: Fn(P) -> Fut + 'static,
+          Fut: Future<Output = Result<R, RpcError>> + 'static {
+        let (name, handler) = (name.into(), rpc::typed_handler(handler));
+        match self.methods.iter_mut().find(|(method, _)| *method == name) {
+            Some((_, existing)) => *existing = handler,
+            None => self.methods.push((name, handler))
+        }
+        self
+    }
+    /// 对象名
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// 全部方法名
+    pub fn method_names(&self) -> impl Iterator<Item = &str> {
+        self.methods.iter().map(|(method, _)| method.as_str())
+    }
+    /// 以`JSON`实参数组调用一个方法。它与网页端的调用走同一条反序列化路径，所以可被用来测试方法的形参类型。
+    pub fn invoke(&self, method: &str, args: Value) -> LocalBoxFuture<'static, Result<Value, RpcError>> {
+        match self.methods.iter().find(|(name, _)| name == method) {
+            Some((_, handler)) => handler(args),
+            None => future::ready(Err(RpcError::new(RpcError::METHOD_NOT_FOUND, format!("宿主对象 {} 没有方法 {method}", self.name)))).boxed_local()
+        }
+    }
+    /// 生成`window.host.<name>`的代理脚本
+    pub fn proxy_script(&self) -> String {
+        let quote = |text: &str| Value::String(text.to_string()).to_string();
+        let methods = self.methods.iter().map(|(method, _)|
+            format!("        {}: async (...args) => window.nwg.invoke({}, args)", quote(method), quote(&host_method(&self.name, method)))
+        ).collect::<Vec<_>>().join(",\n");
+        format!(r#"(() => {{
+    const host = window.host = window.host || {{}};
+    host[{name}] = Object.freeze({{
+{methods}
+    }});
+}})();"#, name = quote(&self.name))
+    }
+    /// 将全部方法注册至`RPC`分发器，并返回代理脚本
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn register(self, dispatcher: &RpcDispatcher) -> String {
+        let proxy_script = self.proxy_script();
+        for (method, handler) in self.methods {
+            dispatcher.register_handler(host_method(&self.name, &method), handler);
+        }
+        proxy_script
+    }
+}
+#[cfg(test)]
+mod tests {
+    use ::futures::future;
+    use ::serde::{Deserialize, Serialize};
+    use ::serde_json::json;
+    use ::std::time::Duration;
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32
+    }
+    fn calc() -> HostObject {
+        HostObject::new("calc")
+            .method("add", |(a, b): (i32, i32)| async move { Ok(a + b) })
+            .method("greet", |(name,): (String,)| async move { Ok(format!("你好，{name}")) })
+            .method("mirror", |(point,): (Point,)| async move { Ok(Point { x: point.y, y: point.x }) })
+            .method("fail", |_: Value| async move { Err::<(), _>(RpcError::new(1, "失败")) })
+    }
+    fn invoke(object: &HostObject, method: &str, args: Value) -> Result<Value, RpcError> {
+        object.invoke(method, args).now_or_never().expect("宿主方法同步地完成")
+    }
+    #[test]
+    fn deserializes_the_arguments_and_serializes_the_result() {
+        let calc = calc();
+        assert_eq!(invoke(&calc, "add", json!([1, 2])), Ok(json!(3)));
+        assert_eq!(invoke(&calc, "greet", json!(["网页"])), Ok(json!("你好，网页")));
+        assert_eq!(invoke(&calc, "mirror", json!([{"x": 1, "y": 2}])), Ok(json!({"x": 2, "y": 1})));
+        assert_eq!(invoke(&calc, "fail", json!([])), Err(RpcError::new(1, "失败")));
+    }
+    #[test]
+    fn rejects_mismatched_arguments() {
+        let calc = calc();
+        for args in [json!(["1", 2]), json!([1]), json!([1, 2, 3]), json!({"a": 1, "b": 2}), json!([{"x": 1}])] {
+            let method = if args.as_array().is_some_and(|args| args.first().is_some_and(Value::is_object)) { "mirror" } else { "add" };
+            assert_eq!(invoke(&calc, method, args.clone()).map_err(|err| err.code), Err(RpcError::INVALID_PARAMS), "{method}({args})");
+        }
+        assert_eq!(invoke(&calc, "missing", json!([])).map_err(|err| err.code), Err(RpcError::METHOD_NOT_FOUND));
+    }
+    #[test]
+    fn later_methods_replace_earlier_ones() {
+        let calc = calc().method("add", |(a, b): (i32, i32)| async move { Ok(a * b) });
+        assert_eq!(calc.method_names().collect::<Vec<_>>(), vec!["add", "greet", "mirror", "fail"]);
+        assert_eq!(invoke(&calc, "add", json!([3, 4])), Ok(json!(12)));
+    }
+    #[test]
+    fn proxy_script_quotes_the_names() {
+        let script = HostObject::new(r#"a"b"#).method("go", |_: Value| async move { Ok(()) }).proxy_script();
+        assert!(script.contains(r#"host["a\"b"] = Object.freeze"#), "{script}");
+        assert!(script.contains(r#""go": async (...args) => window.nwg.invoke("host.a\"b.go", args)"#), "{script}");
+    }
+    #[test]
+    fn register_exposes_the_methods_through_rpc() {
+        let dispatcher = RpcDispatcher::default();
+        calc().register(&dispatcher);
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": host_method("calc", "add"), "params": [1, 2]});
+        let response = dispatcher.dispatch(request, |_: Duration| future::pending::<()>()).now_or_never().flatten().unwrap();
+        assert_eq!((response.id, response.outcome), (json!(1), rpc::RpcOutcome::Result(json!(3))));
+    }
+}
@@ -0,0 +1,145 @@
+use ::deferred_future::LocalDeferredFuture;
+use ::futures::{FutureExt, future::Shared};
+use ::std::{cell::RefCell, fmt::{self, Debug, Formatter}, rc::Rc};
+use super::{backend::{EnvironmentOptions, ParentWindow, WebviewBackend}, error::WebviewContainerError, log::error};
+
+/// `webview`控件初始化成功的返回值
+pub struct WebviewHandles<B: WebviewBackend> {
+    /// 浏览器环境。在多`TAB`场景下，可被传递给`WebviewContainerBuilder::webview_env()`以共享浏览器进程。
+    pub environment: B::Environment,
+    /// 浏览器控件
+    pub controller: B::Controller,
+    /// 网页视图
+    pub webview: B::WebView
+}
+impl<B: WebviewBackend> Clone for WebviewHandles<B> {
+    fn clone(&self) -> Self {
+        Self {
+            environment: self.environment.clone(),
+            controller: self.controller.clone(),
+            webview: self.webview.clone()
+        }
+    }
+}
+impl<B: WebviewBackend> Debug for WebviewHandles<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewHandles").finish_non_exhaustive()
+    }
+}
+/// `webview`控件初始化成功之后的回调。比如，`WebviewTabs`借此监听每个`TAB`的网页标题。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) type CreatedHook<B> = Box<dyn FnOnce(&Rc<B>, &WebviewHandles<B>) -> Result<(), WebviewContainerError>>;
+/// `webview`控件初始化的结果
+pub type ReadyResult<B> = Result<WebviewHandles<B>, WebviewContainerError>;
+/// `webview`控件初始化的异步锁。无论初始化成功与否，它都一定会被决议。
+pub type ReadyFuture<B> = Shared<LocalDeferredFuture<ReadyResult<B>>>;
+/// 已被决议的异步锁。比如，`WebView2 Runtime`不可用时，就不必再开启初始化流程了。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn settled<B: WebviewBackend>(result: ReadyResult<B>) -> ReadyFuture<B> {
+    let webview_ready_future = LocalDeferredFuture::<ReadyResult<B>>::default();
+    webview_ready_future.defer().borrow_mut().complete(result);
+    webview_ready_future.shared()
+}
+/// 开启`webview`控件的异步初始化流程。
+/// 1. 若未提供`env`，则先按`options`构造浏览器环境；
+/// 2. 构造浏览器控件，和获取网页视图；
+/// 3. 回调`on_created`（比如，对齐控件尺寸，和挂载网页事件）。若它失败了，则关闭浏览器控件；
+/// 4. 将浏览器控件与网页视图分别写入`webview_ctrl`与`webview`槽位。
+///
+/// 同步失败会被直接返回。异步失败则会决议返回的`ReadyFuture`，而不是令其永远挂起。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn initialize<B, F>(backend: &Rc<B>, env: Option<B::Environment>, options: &EnvironmentOptions<'_>, parent: ParentWindow,
+    webview_ctrl: &Rc<RefCell<Option<B::Controller>>>, webview: &Rc<RefCell<Option<B::WebView>>>, on_created: F) -> Result<ReadyFuture<B>, WebviewContainerError>
+where B: WebviewBackend,
+      F: FnOnce(&Rc<B>, &WebviewHandles<B>) -> Result<(), WebviewContainerError> + 'static {
+    let webview_ready_future = LocalDeferredFuture::<ReadyResult<B>>::default();
+    let defer = webview_ready_future.defer();
+    let build = {
+        let backend = Rc::clone(backend);
+        let webview_ctrl = Rc::clone(webview_ctrl);
+        let webview_slot = Rc::clone(webview);
+        let defer = Rc::clone(&defer);
+        move |environment: B::Environment| {
+            let backend2 = Rc::clone(&backend);
+            backend.create_controller(&environment.clone(), parent, Box::new(move |controller| {
+                let result: ReadyResult<B> = controller.and_then(|controller| {
+                    let webview = backend2.get_webview(&controller)?;
+                    Ok((controller, webview))
+                }).map_err(WebviewContainerError::ControllerCreation).and_then(|(controller, webview)| {
+                    let handles = WebviewHandles { environment, controller, webview };
+                    if let Err(err) = on_created(&backend2, &handles) { // 浏览器控件还未被写入槽位，所以 WebviewContainer 不会再析构它了
+                        backend2.close(&handles.controller).map_err(|err| error!("[initialize]{err}")).ok();
+                        return Err(err);
+                    }
+                    Ok(handles)
+                });
+                if let Ok(handles) = &result {
+                    webview_ctrl.borrow_mut().replace(handles.controller.clone());
+                    webview_slot.borrow_mut().replace(handles.webview.clone());
+                }
+                defer.borrow_mut().complete(result);
+            }))
+        }
+    };
+    if let Some(env) = env {
+        build(env).map_err(WebviewContainerError::ControllerCreation)
+    } else {
+        backend.create_environment(options, Box::new(move |env| {
+            let result = env.map_err(WebviewContainerError::EnvironmentCreation)
+                .and_then(|env| build(env).map_err(WebviewContainerError::ControllerCreation));
+            if let Err(err) = result {
+                defer.borrow_mut().complete(Err(err));
+            }
+        })).map_err(WebviewContainerError::EnvironmentCreation)
+    }.map(|_| webview_ready_future.shared())
+}
+#[cfg(test)]
+mod tests {
+    use ::std::ptr;
+    use crate::backend::{BackendCall, BackendError, RecordingBackend};
+    use super::*;
+
+    type Slots = (Rc<RefCell<Option<u32>>>, Rc<RefCell<Option<()>>>);
+    fn start<F>(backend: &RecordingBackend, on_created: F) -> (ReadyResult<RecordingBackend>, Slots)
+    where F: FnOnce(&Rc<RecordingBackend>, &WebviewHandles<RecordingBackend>) -> Result<(), WebviewContainerError> + 'static {
+        let slots: Slots = (Rc::default(), Rc::default());
+        let ready = initialize(&Rc::new(backend.clone()), None, &EnvironmentOptions::default(), ptr::null_mut(), &slots.0, &slots.1, on_created).unwrap();
+        (ready.now_or_never().expect("RecordingBackend 同步地完成初始化"), slots)
+    }
+    #[test]
+    fn resolves_to_handles_and_fills_the_slots() {
+        let backend = RecordingBackend::default();
+        let (result, (webview_ctrl, webview)) = start(&backend, |_, _| Ok(()));
+        assert_eq!(result.unwrap().controller, 1);
+        assert_eq!(*webview_ctrl.borrow(), Some(1));
+        assert_eq!(*webview.borrow(), Some(()));
+        assert_eq!(backend.calls(), vec![BackendCall::CreateEnvironment, BackendCall::CreateController, BackendCall::GetWebview]);
+    }
+    #[test]
+    fn resolves_to_err_when_the_environment_fails() {
+        let backend = RecordingBackend::default();
+        let err = BackendError::new(-1, "environment");
+        backend.fail_environment_creation(err.clone());
+        let (result, (webview_ctrl, _)) = start(&backend, |_, _| Ok(()));
+        assert_eq!(result.unwrap_err(), WebviewContainerError::EnvironmentCreation(err));
+        assert_eq!(*webview_ctrl.borrow(), None);
+        assert_eq!(backend.calls(), vec![BackendCall::CreateEnvironment]);
+    }
+    #[test]
+    fn resolves_to_err_when_the_controller_fails() {
+        let backend = RecordingBackend::default();
+        let err = BackendError::new(-2, "controller");
+        backend.fail_controller_creation(err.clone());
+        let (result, (webview_ctrl, _)) = start(&backend, |_, _| Ok(()));
+        assert_eq!(result.unwrap_err(), WebviewContainerError::ControllerCreation(err));
+        assert_eq!(*webview_ctrl.borrow(), None);
+    }
+    #[test]
+    fn closes_the_controller_when_on_created_fails() {
+        let backend = RecordingBackend::default();
+        let (result, (webview_ctrl, webview)) = start(&backend, |_, _| Err(WebviewContainerError::NotReady));
+        assert_eq!(result.unwrap_err(), WebviewContainerError::NotReady);
+        assert_eq!(backend.calls().last(), Some(&BackendCall::Close));
+        assert_eq!((*webview_ctrl.borrow(), *webview.borrow()), (None, None));
+    }
+}
@@ -0,0 +1,110 @@
+//! 初始化脚本：在每个网页的脚本执行之前被注入的脚本。
+//!
+//! 脚本文件的读取、脚本的注入与移除都不依赖`Win32`，所以在任何平台都能经由`RecordingBackend`被测试。
+use ::std::{cell::RefCell, fs, path::PathBuf, rc::Weak};
+use super::{backend::WebviewBackend, error::WebviewContainerError, log::{ContainerSpan, error, trace}};
+
+/// 初始化脚本的来源
+#[derive(Debug)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) enum InitScript {
+    Source(String),
+    File(PathBuf)
+}
+/// 各个初始化脚本被注入之后的`id`（按追加的次序）。在注入完成之前（或在脚本被移除之后），它是`None`。
+pub(crate) type InitScriptIds = RefCell<Vec<Option<String>>>;
+
+/// 读取初始化脚本的源码。任一脚本文件读取失败，都返回`WebviewContainerError::InitScriptFile`。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn load(init_scripts: Vec<InitScript>) -> Result<Vec<String>, WebviewContainerError> {
+    init_scripts.into_iter().map(|init_script| match init_script {
+        InitScript::Source(source) => Ok(source),
+        InitScript::File(path) => fs::read_to_string(&path).map_err(|err| WebviewContainerError::InitScriptFile { path, message: err.to_string() })
+    }).collect()
+}
+/// 按序注入初始化脚本。第`index`个脚本的`id`被异步地写入`init_script_ids`的第`index`个槽位。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn inject<B: WebviewBackend>(backend: &B, webview: &B::WebView, init_scripts: &[String], init_script_ids: &Weak<InitScriptIds>, span: &ContainerSpan) -> Result<(), WebviewContainerError> {
+    for (index, init_script) in init_scripts.iter().enumerate() {
+        let (init_script_ids, span) = (init_script_ids.clone(), span.clone());
+        backend.add_script_to_execute_on_document_created(webview, init_script, Box::new(move |id| {
+            let _span = span.enter();
+            match id {
+                Ok(id) => {
+                    trace!("[WebviewContainerBuilder][build]初始化脚本 {index} -> {id}");
+                    if let Some(init_script_ids) = init_script_ids.upgrade() {
+                        if let Some(slot) = init_script_ids.borrow_mut().get_mut(index) {
+                            slot.replace(id);
+                        }
+                    }
+                },
+                Err(err) => error!("[WebviewContainerBuilder][build]初始化脚本 {index} 注入失败：{err}")
+            }
+        })).map_err(WebviewContainerError::ScriptInjection)?;
+    }
+    Ok(())
+}
+/// 移除初始化脚本，并清空其`id`所在的槽位。它仅影响之后被加载的网页。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn remove<B: WebviewBackend>(backend: &B, webview: &B::WebView, init_script_ids: &InitScriptIds, id: &str) -> Result<(), WebviewContainerError> {
+    backend.remove_script_to_execute_on_document_created(webview, id).map_err(WebviewContainerError::ScriptRemoval)?;
+    init_script_ids.borrow_mut().iter_mut().filter(|slot| slot.as_deref() == Some(id)).for_each(|slot| *slot = None);
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use ::std::{env, process, rc::Rc};
+    use crate::backend::{BackendCall, RecordingBackend};
+    use super::*;
+
+    #[test]
+    fn loads_sources_and_files_in_order() {
+        let path = env::temp_dir().join(format!("nwg-webview-ctrl-init-script-{}.js", process::id()));
+        fs::write(&path, "window.fromFile = true;").unwrap();
+        let loaded = load(vec![InitScript::Source(String::from("window.a = 1;")), InitScript::File(path.clone())]);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), vec![String::from("window.a = 1;"), String::from("window.fromFile = true;")]);
+    }
+    #[test]
+    fn missing_files_fail_with_their_path() {
+        let path = env::temp_dir().join("nwg-webview-ctrl-missing").join("init.js");
+        match load(vec![InitScript::Source(String::from("window.a = 1;")), InitScript::File(path.clone())]) {
+            Err(WebviewContainerError::InitScriptFile { path: failed, message }) => {
+                assert_eq!(failed, path);
+                assert!(!message.is_empty());
+            },
+            other => panic!("{other:?}")
+        }
+    }
+    #[test]
+    fn injected_ids_are_mapped_to_their_indexes() {
+        let backend = RecordingBackend::default();
+        let init_script_ids = Rc::new(RefCell::new(vec![None; 2]));
+        let init_scripts = [String::from("window.a = 1;"), String::from("window.b = 2;")];
+        inject(&backend, &(), &init_scripts, &Rc::downgrade(&init_script_ids), &ContainerSpan::default()).unwrap();
+        assert_eq!(backend.calls(), vec![
+            BackendCall::AddScriptToExecuteOnDocumentCreated(String::from("window.a = 1;")),
+            BackendCall::AddScriptToExecuteOnDocumentCreated(String::from("window.b = 2;"))
+        ]);
+        assert_eq!(*init_script_ids.borrow(), vec![Some(String::from("script-1")), Some(String::from("script-2"))]);
+    }
+    #[test]
+    fn ids_are_dropped_once_the_container_is_gone() {
+        let backend = RecordingBackend::default();
+        let init_script_ids = Rc::new(RefCell::new(vec![None]));
+        let weak = Rc::downgrade(&init_script_ids);
+        drop(init_script_ids);
+        inject(&backend, &(), &[String::from("window.a = 1;")], &weak, &ContainerSpan::default()).unwrap();
+        assert_eq!(backend.calls().len(), 1);
+    }
+    #[test]
+    fn removal_clears_the_slot() {
+        let backend = RecordingBackend::default();
+        let init_script_ids = RefCell::new(vec![Some(String::from("script-1")), Some(String::from("script-2"))]);
+        remove(&backend, &(), &init_script_ids, "script-2").unwrap();
+        assert_eq!(backend.calls(), vec![BackendCall::RemoveScriptToExecuteOnDocumentCreated(String::from("script-2"))]);
+        assert_eq!(*init_script_ids.borrow(), vec![Some(String::from("script-1")), None]);
+        remove(&backend, &(), &init_script_ids, "script-3").unwrap();
+        assert_eq!(*init_script_ids.borrow(), vec![Some(String::from("script-1")), None], "未知的 id 不影响其它槽位");
+    }
+}
@@ -0,0 +1,100 @@
+//! 容器`nwg::Frame`内的留白：`webview`控件仅占据容器客户区扣除留白之后的部分，而留白处可被用来叠放原生控件。
+//!
+//! 留白是相对于容器客户区的。所以，对带`WebviewContainerFlags::BORDER`边框的容器而言，留白从边框内侧算起。
+use super::backend::Bounds;
+
+/// 上、下、左、右四边的留白（单位：物理像素）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Insets {
+    /// 左留白
+    pub left: u32,
+    /// 上留白
+    pub top: u32,
+    /// 右留白
+    pub right: u32,
+    /// 下留白
+    pub bottom: u32
+}
+impl Insets {
+    /// 无留白
+    pub const ZERO: Self = Self { left: 0, top: 0, right: 0, bottom: 0 };
+    /// 四边等宽的留白
+    pub fn uniform(inset: u32) -> Self {
+        Self { left: inset, top: inset, right: inset, bottom: inset }
+    }
+    /// 左右留白都是`horizontal`，而上下留白都是`vertical`
+    pub fn symmetric(horizontal: u32, vertical: u32) -> Self {
+        Self { left: horizontal, top: vertical, right: horizontal, bottom: vertical }
+    }
+    /// 从`bounds`扣除留白。若留白之和超过了宽（或高），则结果的宽（或高）被钳制为零，而不会是负数。
+    pub fn apply(&self, bounds: Bounds) -> Bounds {
+        let (left, right) = shrink(bounds.left, bounds.right, self.left, self.right);
+        let (top, bottom) = shrink(bounds.top, bounds.bottom, self.top, self.bottom);
+        Bounds { left, top, right, bottom }
+    }
+}
+fn shrink(start: i32, end: i32, start_inset: u32, end_inset: u32) -> (i32, i32) {
+    let inset = |inset: u32| i32::try_from(inset).unwrap_or(i32::MAX);
+    let start = start.saturating_add(inset(start_inset)).min(end.max(start));
+    let end = end.saturating_sub(inset(end_inset)).max(start);
+    (start, end)
+}
+/// 容器客户区在客户区坐标系内的边界矩形。`size`是容器的外部尺寸，而`border`是边框的宽与高。仅在`GetClientRect`失败时，才需要据此推算客户区。
+pub fn client_bounds(size: (u32, u32), border: (u32, u32)) -> Bounds {
+    let outer = Bounds {
+        right: i32::try_from(size.0).unwrap_or(i32::MAX),
+        bottom: i32::try_from(size.1).unwrap_or(i32::MAX),
+        ..Bounds::default()
+    };
+    let inner = Insets::symmetric(border.0, border.1).apply(outer);
+    Bounds { left: 0, top: 0, right: inner.width(), bottom: inner.height() } // 客户区坐标的原点是边框的内侧
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(width: i32, height: i32) -> Bounds {
+        Bounds { left: 0, top: 0, right: width, bottom: height }
+    }
+    #[test]
+    fn applies_insets() {
+        let insets = Insets { left: 10, top: 20, right: 30, bottom: 40 };
+        assert_eq!(insets.apply(bounds(800, 600)), Bounds { left: 10, top: 20, right: 770, bottom: 560 });
+        assert_eq!(Insets::ZERO.apply(bounds(800, 600)), bounds(800, 600));
+        assert_eq!(Insets::uniform(5), Insets::symmetric(5, 5));
+        let offset = Bounds { left: 100, top: 50, right: 300, bottom: 250 };
+        assert_eq!(Insets::symmetric(10, 20).apply(offset), Bounds { left: 110, top: 70, right: 290, bottom: 230 });
+    }
+    #[test]
+    fn clamps_insets_exceeding_the_size_to_zero() {
+        let table = [
+            (Insets { left: 500, top: 0, right: 500, bottom: 0 }, Bounds { left: 500, top: 0, right: 500, bottom: 600 }),
+            (Insets { left: 0, top: 400, right: 0, bottom: 400 }, Bounds { left: 0, top: 400, right: 800, bottom: 400 }),
+            (Insets { left: 0, top: 0, right: 900, bottom: 700 }, Bounds { left: 0, top: 0, right: 0, bottom: 0 }),
+            (Insets::uniform(400), Bounds { left: 400, top: 400, right: 400, bottom: 400 })
+        ];
+        for (insets, expected) in table {
+            let applied = insets.apply(bounds(800, 600));
+            assert_eq!(applied, expected, "{insets:?}");
+            assert!(applied.width() >= 0 && applied.height() >= 0, "{insets:?}");
+        }
+    }
+    #[test]
+    fn saturates_insets_above_i32_max() {
+        let huge = u32::try_from(i32::MAX).unwrap() + 1;
+        let applied = Insets { left: huge, top: u32::MAX, right: 0, bottom: 0 }.apply(bounds(800, 600));
+        assert_eq!((applied.width(), applied.height()), (0, 0));
+        let applied = Insets { left: 0, top: 0, right: u32::MAX, bottom: huge }.apply(bounds(800, 600));
+        assert_eq!(applied, Bounds { left: 0, top: 0, right: 0, bottom: 0 });
+        let edge = Bounds { left: i32::MAX - 10, top: i32::MIN, right: i32::MAX, bottom: i32::MIN + 10 };
+        let applied = Insets::uniform(u32::MAX).apply(edge);
+        assert_eq!((applied.width(), applied.height()), (0, 0), "不溢出");
+    }
+    #[test]
+    fn computes_client_bounds() {
+        assert_eq!(client_bounds((800, 600), (0, 0)), bounds(800, 600));
+        assert_eq!(client_bounds((800, 600), (1, 2)), bounds(798, 596), "客户区坐标的原点是边框的内侧");
+        assert_eq!(client_bounds((1, 1), (1, 1)), bounds(0, 0));
+        assert_eq!(client_bounds((u32::MAX, 600), (0, 0)), bounds(i32::MAX, 600));
+    }
+}
@@ -1,68 +1,75 @@
+//! 封装`Microsoft Edge WebView2`浏览器内核为`Native Windows GUI`（即，`nwg crate`）开发框架的`WebView`图形控件
+mod assets;
+mod backend;
+mod bridge;
+mod browser_args;
+mod config;
+#[cfg(windows)]
 mod builder;
-use ::deferred_future::LocalDeferredFuture;
-use ::futures::{future::Shared, executor};
-use ::nwg::{self as nwg, ControlHandle, EventHandler, Frame, NwgError, RawEventHandler};
-use ::std::{cell::RefCell, ops::Deref, rc::Rc};
-use ::webview2::{Controller, Environment, WebView};
+#[cfg(windows)]
+mod com;
+#[cfg(windows)]
+mod container;
+mod dpi;
+mod env_pool;
+mod error;
+mod events;
+#[cfg(windows)]
+mod fallback;
+mod host_event;
+mod host_object;
+mod init;
+mod init_script;
+mod insets;
+mod log;
+mod navigate;
+mod navigation;
+mod resize;
+mod rpc;
+mod runtime;
+mod script;
+mod suspend;
+mod tab_state;
+#[cfg(windows)]
+mod tabs;
+#[cfg(windows)]
+mod timer;
+mod window_state;
+#[cfg(windows)]
+use ::nwg::NwgError;
+#[cfg(feature = "zip")]
+pub use assets::ZipAssets;
+pub use assets::{etag, etag_matches, mime_type, normalize_path, parse_range, AssetProvider, AssetRequest, AssetResponse, AssetServer, ByteRange, DirectoryAssets, EmbeddedAssets, PathError};
+pub use backend::{BackendCall, BackendError, BackendResult, Bounds, DocumentTitleHandler, EnvironmentOptions, ParentWindow, ProcessFailedHandler, RecordingBackend, ScriptCompletedHandler, SuspendCompletedHandler, NavigationCompletedHandler, NavigationHandler, NavigationObserver, WebMessageHandler, WebResourceHandler, WebviewBackend};
+#[cfg(windows)]
+pub use backend::Webview2Backend;
+pub use bridge::{decode_envelope, decode_payload, encode_envelope, Envelope, WebviewBridge};
+pub use browser_args::{AutoplayPolicy, BrowserArgs, BrowserArgsError};
+pub use config::{ConfigError, EnvironmentConfig, FrameConfig, WebviewConfig};
+#[cfg(windows)]
 pub use builder::{WebviewContainerBuilder, WebviewContainerFlags};
+#[cfg(windows)]
+pub use container::WebviewContainer;
+pub use dpi::{dpi_changed, scale_bounds, scale_for_dpi, scale_length, ScaleMode, USER_DEFAULT_SCREEN_DPI};
+pub use env_pool::{EnvironmentFuture, EnvironmentKey, EnvironmentPool, EnvironmentResult};
+pub use error::WebviewContainerError;
+pub use events::{forward_events, EventBroadcaster, NavigationCompleted, NavigationStarting, ProcessFailure, WebviewEvent, WebviewEvents};
+pub use host_event::{dispatch as dispatch_host_event, HostEvent};
+pub use host_object::{host_method, HostObject};
+pub use insets::{client_bounds, Insets};
+pub use init::{ReadyFuture, ReadyResult, WebviewHandles};
+pub use navigate::{NavigationError, NavigationOutcome, DEFAULT_NAVIGATION_TIMEOUT};
+pub use navigation::{NavigationAction, NavigationMatcher, NavigationPolicy, NavigationRule};
+pub use resize::{is_exit_size_move, ResizeDecision, ResizePolicy, ResizeScheduler, DEFAULT_RESIZE_INTERVAL};
+pub use rpc::{invoke_shim, RpcError, RpcOutcome, RpcResponse, DEFAULT_RPC_TIMEOUT, RPC_CHANNEL};
+pub use runtime::{BrowserVersion, RuntimeFallback, RuntimeStatus, RUNTIME_DOWNLOAD_URL};
+pub use script::{decode_script_result, wrap_script, ScriptError, ScriptException, DEFAULT_SCRIPT_TIMEOUT};
+pub use suspend::{is_power_status_change, SuspendAction, SuspendPolicy, SuspendScheduler};
+pub use tab_state::{TabId, TabInfo, TabSet, TabSwitch};
+#[cfg(windows)]
+pub use tabs::{WebviewTabs, WebviewTabsBuilder};
+pub use window_state::{WindowMessage, WindowState, WindowStateMachine, WindowTransition};
 
+/// `nwg crate`接口的返回值
+#[cfg(windows)]
 pub type NwgResult<T> = Result<T, NwgError>;
-/// [webview2::Controller](https://docs.rs/webview2/0.1.4/webview2/struct.Controller.html)的[NWG](https://docs.rs/native-windows-gui/1.0.1/native_windows_gui/index.html)控件包装器。借助于该`WebviewContainer`包装器，`webview2::Controller`控件实例就能参与`NWG`的【网格布局】【弹性布局】与【动态布局】。
-/// # 原理：
-/// 1. `WebviewContainer`将`webview2::Controller`嵌套于[nwg::Frame](https://docs.rs/native-windows-gui/1.0.1/native_windows_gui/struct.Frame.html)控件内，以参与控件布局管理。
-/// 2. 监听主窗体的【最小化】事件。在主窗体被最小化之后，停止`webview2::Controller`控件对打开网页的帧刷新。即，将网页的`FPS`降到零。
-/// 3. 监听主窗体的【窗体恢复】事件。在主窗体非最小化状态，恢复`webview2::Controller`控件对打开网页的帧刷新。
-/// 4. 监听主窗体的【移动】事件。仅只透传窗体的最新屏幕坐标给底层的`webview2::Controller`控件。
-/// 5. 监听`nwg::Frame`控件的`OnResize`事件。时刻拉伸或压缩`webview2::Controller`的大小。
-/// # `webview2::Controller`的初始化
-/// `webview2::Controller`初始化是异步的。所以在[`WebviewContainerBuilder::build()`]被同步执行结束之后，仅只`nwg::Frame`布局占位控件被初始化好了。而，`webview2::Controller`的初始化就绪需要等待由[`WebviewContainer.ready_fut()`]成员方法返回的`Future`
-#[derive(Default)]
-pub struct WebviewContainer {
-    is_closing: Rc<RefCell<bool>>,
-    frame: Rc<RefCell<Frame>>,
-    webview_ctrl: Rc<RefCell<Option<Controller>>>,
-    ready_fut: Option<Shared<LocalDeferredFuture<(Environment, Controller, WebView)>>>,
-    event_handle: Option<EventHandler>,
-    raw_event_handle: Option<RawEventHandler>
-}
-impl PartialEq for WebviewContainer {
-    fn eq(&self, other: &Self) -> bool {
-        self.frame.borrow().eq(other.frame.borrow().deref())
-    }
-}
-impl Eq for WebviewContainer {}
-impl From<WebviewContainer> for ControlHandle {
-    fn from(value: WebviewContainer) -> Self {
-        value.frame.borrow().handle
-    }
-}
-impl From<&WebviewContainer> for ControlHandle {
-    fn from(value: &WebviewContainer) -> Self {
-        value.frame.borrow().handle
-    }
-}
-impl Drop for WebviewContainer {
-    fn drop(&mut self) {
-        #[cfg(debug_assertions)]
-        println!("[WebviewContainer][drop]");
-        *self.is_closing.borrow_mut() = true;
-        if self.raw_event_handle.as_ref().map(nwg::unbind_raw_event_handler).map_or(Ok(None), |r| r.map(Some)).is_ok() {
-            self.event_handle.as_ref().map(nwg::unbind_event_handler);
-        }
-        self.webview_ctrl.borrow().as_ref().map(|webview_ctrl| {
-            webview_ctrl.close().map_err(|err| eprintln!("[WebviewContainer][drop]{err}")).ok()
-        });
-        self.frame.borrow_mut().handle.destroy();
-    }
-}
-impl WebviewContainer {
-    pub fn builder<'a>() -> WebviewContainerBuilder<'a> {
-        WebviewContainerBuilder::default()
-    }
-    pub fn ready_fut(&self) -> NwgResult<Shared<LocalDeferredFuture<(Environment, Controller, WebView)>>> {
-        self.ready_fut.clone().ok_or(NwgError::control_create("Webview 控件初始化失败或还未被初始化"))
-    }
-    pub fn ready_block(&self) -> NwgResult<(Environment, Controller, WebView)> {
-        Ok(executor::block_on(self.ready_fut()?))
-    }
-}
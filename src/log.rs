@@ -0,0 +1,74 @@
+//! 诊断日志。开启`tracing`特性之后，日志与`span`被转发给`tracing crate`；否则，它们都是空操作（零开销）。
+#[cfg(feature = "tracing")]
+use ::tracing::{Span, span::Entered};
+#[cfg(not(feature = "tracing"))]
+use ::std::marker::PhantomData;
+use super::backend::ParentWindow;
+
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($($arg: tt)+) => { ::tracing::trace!($($arg)+) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($($arg: tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg: tt)+) => { ::tracing::debug!($($arg)+) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg: tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
+#[cfg(feature = "tracing")]
+macro_rules! error {
+    ($($arg: tt)+) => { ::tracing::error!($($arg)+) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! error {
+    ($($arg: tt)+) => { if false { let _ = format_args!($($arg)+); } };
+}
+pub(crate) use {debug, error, trace};
+
+/// 每个`WebviewContainer`实例一个`span`。其字段包括：容器序号`container_id`与容器`Frame`的窗体句柄`frame_hwnd`。
+#[derive(Clone, Debug)]
+pub(crate) struct ContainerSpan {
+    #[cfg(feature = "tracing")]
+    span: Span
+}
+impl Default for ContainerSpan {
+    /// 容器被构造之前，还没有`span`
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: Span::none()
+        }
+    }
+}
+impl ContainerSpan {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn new(container_id: usize, frame_hwnd: ParentWindow) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: ::tracing::debug_span!("webview_container", container_id, frame_hwnd = ?frame_hwnd)
+        }
+    }
+    /// 进入`span`。在返回的守卫被析构之前，所有日志都归属于该`span`。
+    pub(crate) fn enter(&self) -> SpanGuard<'_> {
+        SpanGuard {
+            #[cfg(feature = "tracing")]
+            _entered: self.span.enter(),
+            #[cfg(not(feature = "tracing"))]
+            _span: PhantomData
+        }
+    }
+}
+/// [`ContainerSpan::enter()`]的守卫
+pub(crate) struct SpanGuard<'a> {
+    #[cfg(feature = "tracing")]
+    _entered: Entered<'a>,
+    #[cfg(not(feature = "tracing"))]
+    _span: PhantomData<&'a ContainerSpan>
+}
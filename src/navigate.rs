@@ -0,0 +1,238 @@
+//! 异步导航：`WebviewContainer::navigate()`返回的`Future`在对应的`NavigationCompleted`事件发生时被决议。
+//!
+//! 导航按序号（`navigation_id`）关联：`navigate()`之后的首个`NavigationStarting`事件认领该导航的序号；重定向保持序号不变。
+//! 在导航结束之前，若另一次导航开始了（比如，再次调用`navigate()`，或网页自己跳转了），则该导航被视为被取代了。
+use ::futures::{channel::oneshot::{self, Sender}, future::{self, Either, LocalBoxFuture}, FutureExt};
+use ::std::{cell::{Cell, RefCell}, error::Error, fmt::{self, Debug, Display, Formatter}, future::Future, rc::{Rc, Weak}, time::Duration};
+use super::{backend::{BackendResult, WebviewBackend}, error::WebviewContainerError, events::{NavigationCompleted, NavigationStarting}, log::debug};
+
+/// `navigate()`的默认超时
+pub const DEFAULT_NAVIGATION_TIMEOUT: Duration = Duration::from_secs(30);
+/// 导航成功的结果
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NavigationOutcome {
+    /// 导航序号
+    pub navigation_id: u64,
+    /// 被请求的地址
+    pub uri: String,
+    /// `HTTP`状态码（若后端提供之）
+    pub http_status_code: Option<u16>
+}
+/// 导航失败的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationError {
+    /// 网页加载失败了
+    Failed(NavigationCompleted),
+    /// 在导航结束之前，另一次导航开始了
+    Superseded {
+        /// 取代它的导航序号。若它被另一次`navigate()`调用取代，则是`None`
+        by: Option<u64>
+    },
+    /// 导航超时了
+    TimedOut(Duration)
+}
+impl Display for NavigationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed(completed) => write!(f, "导航 {} 失败了：COREWEBVIEW2_WEB_ERROR_STATUS = {}", completed.navigation_id, completed.web_error_status),
+            Self::Superseded { by: Some(navigation_id) } => write!(f, "导航被导航 {navigation_id} 取代了"),
+            Self::Superseded { by: None } => write!(f, "导航被另一次 navigate() 调用取代了"),
+            Self::TimedOut(timeout) => write!(f, "导航超过了 {timeout:?}")
+        }
+    }
+}
+impl Error for NavigationError {}
+type NavigationResult = Result<NavigationOutcome, NavigationError>;
+/// 还未结束的导航
+struct PendingNavigation {
+    uri: String,
+    navigation_id: Option<u64>,
+    settle: Sender<NavigationResult>
+}
+/// 将`NavigationStarting`与`NavigationCompleted`事件关联至由`navigate()`发起的导航。同一时刻仅有一次被跟踪的导航。
+pub(crate) struct NavigationTracker {
+    pending: RefCell<Option<PendingNavigation>>,
+    timeout: Cell<Duration>
+}
+impl Default for NavigationTracker {
+    fn default() -> Self {
+        Self {
+            pending: RefCell::default(),
+            timeout: Cell::new(DEFAULT_NAVIGATION_TIMEOUT)
+        }
+    }
+}
+impl Debug for NavigationTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pending = self.pending.borrow();
+        f.debug_struct("NavigationTracker")
+            .field("pending", &pending.as_ref().map(|pending| (&pending.uri, pending.navigation_id)))
+            .field("timeout", &self.timeout.get())
+            .finish()
+    }
+}
+#[cfg_attr(not(windows), allow(dead_code))]
+impl NavigationTracker {
+    /// `navigate()`的超时
+    pub(crate) fn set_timeout(&self, timeout: Duration) {
+        self.timeout.set(timeout);
+    }
+    /// 跟踪一次即将被发起的导航。还未结束的上一次导航被视为被取代了。`timer`按超时构造超时`Future`。
+    ///
+    /// 若`NavigationTracker`先于导航结束被析构，则返回的`Future`决议为`WebviewContainerError::AlreadyClosed`。
+    pub(crate) fn track<T, TF>(&self, uri: &str, timer: T) -> LocalBoxFuture<'static, Result<NavigationOutcome, WebviewContainerError>>
+    where T: FnOnce(Duration) -> TF,
+          TF: Future<Output = ()> + 'static {
+        let (settle, settled) = oneshot::channel();
+        let superseded = self.pending.replace(Some(PendingNavigation { uri: uri.to_string(), navigation_id: None, settle }));
+        if let Some(superseded) = superseded {
+            Self::settle(superseded, Err(NavigationError::Superseded { by: None }));
+        }
+        let timeout = self.timeout.get();
+        let deadline = timer(timeout);
+        async move {
+            match future::select(settled, Box::pin(deadline)).await {
+                Either::Left((Ok(result), _)) => result.map_err(WebviewContainerError::Navigation),
+                Either::Left((Err(_), _)) => Err(WebviewContainerError::AlreadyClosed),
+                Either::Right(_) => Err(WebviewContainerError::Navigation(NavigationError::TimedOut(timeout)))
+            }
+        }.boxed_local()
+    }
+    /// 放弃跟踪。比如，后端拒绝了导航请求。
+    pub(crate) fn abandon(&self) {
+        self.pending.borrow_mut().take();
+    }
+    /// 处理`NavigationStarting`事件
+    pub(crate) fn on_starting(&self, starting: &NavigationStarting) {
+        let mut pending = self.pending.borrow_mut();
+        let Some(tracked) = pending.as_mut() else {
+            return;
+        };
+        match tracked.navigation_id {
+            None => tracked.navigation_id = Some(starting.navigation_id),
+            Some(navigation_id) if navigation_id != starting.navigation_id => if let Some(superseded) = pending.take() {
+                Self::settle(superseded, Err(NavigationError::Superseded { by: Some(starting.navigation_id) }));
+            },
+            _ => () // 重定向
+        }
+    }
+    /// 处理`NavigationCompleted`事件
+    pub(crate) fn on_completed(&self, completed: &NavigationCompleted) {
+        let mut pending = self.pending.borrow_mut();
+        if pending.as_ref().is_some_and(|pending| pending.navigation_id == Some(completed.navigation_id)) {
+            if let Some(finished) = pending.take() {
+                let result = if completed.is_success {
+                    Ok(NavigationOutcome { navigation_id: completed.navigation_id, uri: finished.uri.clone(), http_status_code: completed.http_status_code })
+                } else {
+                    Err(NavigationError::Failed(*completed))
+                };
+                Self::settle(finished, result);
+            }
+        }
+    }
+    fn settle(pending: PendingNavigation, result: NavigationResult) {
+        debug!("[NavigationTracker]{} -> {:?}", pending.uri, result.as_ref().map_err(ToString::to_string));
+        pending.settle.send(result).ok();
+    }
+}
+/// 将网页视图的导航事件转发给`NavigationTracker`
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn attach<B: WebviewBackend>(backend: &B, webview: &B::WebView, tracker: &Rc<NavigationTracker>) -> BackendResult<()> {
+    let weak = Rc::downgrade(tracker);
+    backend.observe_navigation_starting(webview, Box::new(move |starting| with_tracker(&weak, |tracker| tracker.on_starting(starting))))?;
+    let weak = Rc::downgrade(tracker);
+    backend.add_navigation_completed(webview, Box::new(move |completed| with_tracker(&weak, |tracker| tracker.on_completed(&completed))))
+}
+fn with_tracker(tracker: &Weak<NavigationTracker>, handle: impl FnOnce(&NavigationTracker)) {
+    if let Some(tracker) = tracker.upgrade() {
+        handle(&tracker);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::backend::RecordingBackend;
+    use super::*;
+
+    type Tracked = LocalBoxFuture<'static, Result<NavigationOutcome, WebviewContainerError>>;
+    fn track(tracker: &NavigationTracker, uri: &str) -> Tracked {
+        tracker.track(uri, |_| future::pending())
+    }
+    fn starting(navigation_id: u64) -> NavigationStarting {
+        NavigationStarting { navigation_id, uri: String::from("https://app.local/") }
+    }
+    fn completed(navigation_id: u64, is_success: bool) -> NavigationCompleted {
+        NavigationCompleted { navigation_id, is_success, web_error_status: if is_success {0} else {7}, http_status_code: is_success.then_some(200) }
+    }
+    #[test]
+    fn resolves_on_the_claimed_completion() {
+        let tracker = NavigationTracker::default();
+        let mut navigation = track(&tracker, "https://app.local/");
+        tracker.on_starting(&starting(3));
+        tracker.on_starting(&starting(3)); // 重定向
+        tracker.on_completed(&completed(2, true)); // 无关的导航
+        assert!((&mut navigation).now_or_never().is_none());
+        tracker.on_completed(&completed(3, true));
+        assert_eq!(navigation.now_or_never(), Some(Ok(NavigationOutcome { navigation_id: 3, uri: String::from("https://app.local/"), http_status_code: Some(200) })));
+    }
+    #[test]
+    fn failed_navigations_are_errors() {
+        let tracker = NavigationTracker::default();
+        let navigation = track(&tracker, "https://app.local/");
+        tracker.on_starting(&starting(1));
+        tracker.on_completed(&completed(1, false));
+        assert_eq!(navigation.now_or_never(), Some(Err(WebviewContainerError::Navigation(NavigationError::Failed(completed(1, false))))));
+    }
+    #[test]
+    fn a_new_navigate_call_supersedes_the_pending_one() {
+        let tracker = NavigationTracker::default();
+        let first = track(&tracker, "https://app.local/a");
+        tracker.on_starting(&starting(1));
+        let second = track(&tracker, "https://app.local/b");
+        assert_eq!(first.now_or_never(), Some(Err(WebviewContainerError::Navigation(NavigationError::Superseded { by: None }))));
+        tracker.on_starting(&starting(2));
+        tracker.on_completed(&completed(1, true)); // 被取代的导航结束得晚了
+        tracker.on_completed(&completed(2, true));
+        assert_eq!(second.now_or_never().map(|outcome| outcome.map(|outcome| outcome.uri)), Some(Ok(String::from("https://app.local/b"))));
+    }
+    #[test]
+    fn a_page_initiated_navigation_supersedes_the_pending_one() {
+        let tracker = NavigationTracker::default();
+        let navigation = track(&tracker, "https://app.local/");
+        tracker.on_starting(&starting(1));
+        tracker.on_starting(&starting(2));
+        assert_eq!(navigation.now_or_never(), Some(Err(WebviewContainerError::Navigation(NavigationError::Superseded { by: Some(2) }))));
+    }
+    #[test]
+    fn times_out_with_the_configured_timeout() {
+        let tracker = NavigationTracker::default();
+        tracker.set_timeout(Duration::from_secs(5));
+        let timeout = Cell::new(None);
+        let navigation = tracker.track("https://app.local/", |duration| {
+            timeout.set(Some(duration));
+            future::ready(())
+        });
+        assert_eq!(navigation.now_or_never(), Some(Err(WebviewContainerError::Navigation(NavigationError::TimedOut(Duration::from_secs(5))))));
+        assert_eq!(timeout.get(), Some(Duration::from_secs(5)));
+    }
+    #[test]
+    fn abandoned_or_dropped_trackers_close_the_future() {
+        let tracker = NavigationTracker::default();
+        let navigation = track(&tracker, "https://app.local/");
+        tracker.abandon();
+        assert_eq!(navigation.now_or_never(), Some(Err(WebviewContainerError::AlreadyClosed)));
+        let navigation = track(&tracker, "https://app.local/");
+        drop(tracker);
+        assert_eq!(navigation.now_or_never(), Some(Err(WebviewContainerError::AlreadyClosed)));
+    }
+    #[test]
+    fn attaches_to_backend_events() {
+        let backend = RecordingBackend::default();
+        let tracker = Rc::new(NavigationTracker::default());
+        attach(&backend, &(), &tracker).unwrap();
+        let navigation = track(&tracker, "https://app.local/");
+        backend.start_navigation("https://app.local/");
+        let navigation_id = backend.last_navigation_id().unwrap();
+        backend.complete_navigation(NavigationCompleted { navigation_id, is_success: true, web_error_status: 0, http_status_code: None });
+        assert_eq!(navigation.now_or_never(), Some(Ok(NavigationOutcome { navigation_id, uri: String::from("https://app.local/"), http_status_code: None })));
+    }
+}
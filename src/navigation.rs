@@ -0,0 +1,290 @@
+//! 导航策略：按【源】与【协议】规则，放行、拒绝网页导航，或将其转交给系统默认浏览器。
+//!
+//! 规则按被添加的顺序逐条匹配，首条命中的规则决定导航的处置方式；若无规则命中，则采用默认处置。
+//! 空白页`about:blank`永远不会被转交给系统默认浏览器。
+use ::std::fmt::{self, Debug, Formatter};
+#[cfg(feature = "regex")]
+use ::regex::Regex;
+
+/// 导航的处置方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationAction {
+    /// 在`webview`内继续导航
+    Allow,
+    /// 取消导航
+    Deny,
+    /// 取消导航，并用系统默认浏览器（或协议关联程序）打开该地址
+    OpenExternal
+}
+/// 规则的匹配条件
+#[derive(Clone)]
+pub enum NavigationMatcher {
+    /// 源的通配符模式。`*`匹配任意个字符，`?`匹配单个字符。
+    /// 含`://`的模式匹配完整的源（比如，`https://*.example.com`）；否则，仅匹配主机名（比如，`*.example.com`）。
+    OriginGlob(String),
+    /// 源的正则表达式。它被用来匹配形如`https://app.example.com:8443`的完整源
+    #[cfg(feature = "regex")]
+    OriginRegex(Regex),
+    /// 协议名（不区分大小写）。比如，`mailto`
+    Scheme(String)
+}
+impl Debug for NavigationMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OriginGlob(glob) => f.debug_tuple("OriginGlob").field(glob).finish(),
+            #[cfg(feature = "regex")]
+            Self::OriginRegex(regex) => f.debug_tuple("OriginRegex").field(&regex.as_str()).finish(),
+            Self::Scheme(scheme) => f.debug_tuple("Scheme").field(scheme).finish()
+        }
+    }
+}
+impl NavigationMatcher {
+    /// 地址是否命中该条件
+    pub fn matches(&self, uri: &str) -> bool {
+        let Some(target) = NavigationTarget::parse(uri) else {
+            return false;
+        };
+        match self {
+            Self::OriginGlob(glob) => {
+                let glob = glob.to_ascii_lowercase();
+                match (glob.contains("://"), target.origin()) {
+                    (true, Some(origin)) => glob_matches(&glob, &origin),
+                    (false, Some(_)) => glob_matches(&glob, &target.host),
+                    (_, None) => false
+                }
+            },
+            #[cfg(feature = "regex")]
+            Self::OriginRegex(regex) => target.origin().is_some_and(|origin| regex.is_match(&origin)),
+            Self::Scheme(scheme) => scheme.eq_ignore_ascii_case(&target.scheme)
+        }
+    }
+}
+/// 一条导航规则
+#[derive(Clone, Debug)]
+pub struct NavigationRule {
+    /// 匹配条件
+    pub matcher: NavigationMatcher,
+    /// 命中之后的处置方式
+    pub action: NavigationAction
+}
+/// 导航策略。比如，
+/// ```ignore
+/// NavigationPolicy::new(NavigationAction::OpenExternal)
+///     .allow_origin("https://app.local")
+///     .allow_origin("*.example.com")
+///     .scheme("javascript", NavigationAction::Deny)
+/// ```
+#[derive(Clone, Debug)]
+pub struct NavigationPolicy {
+    rules: Vec<NavigationRule>,
+    default_action: NavigationAction
+}
+impl Default for NavigationPolicy {
+    /// 放行全部导航
+    fn default() -> Self {
+        Self::new(NavigationAction::Allow)
+    }
+}
+impl NavigationPolicy {
+    /// 无规则命中时，采用`default_action`处置
+    pub fn new(default_action: NavigationAction) -> Self {
+        Self { rules: Vec::new(), default_action }
+    }
+    /// 追加一条规则
+    pub fn rule(mut self, matcher: NavigationMatcher, action: NavigationAction) -> Self {
+        self.rules.push(NavigationRule { matcher, action });
+        self
+    }
+    /// 追加一条源的通配符规则
+    pub fn origin(self, glob: impl Into<String>, action: NavigationAction) -> Self {
+        self.rule(NavigationMatcher::OriginGlob(glob.into()), action)
+    }
+    /// 放行匹配`glob`的源
+    pub fn allow_origin(self, glob: impl Into<String>) -> Self {
+        self.origin(glob, NavigationAction::Allow)
+    }
+    /// 拒绝匹配`glob`的源
+    pub fn deny_origin(self, glob: impl Into<String>) -> Self {
+        self.origin(glob, NavigationAction::Deny)
+    }
+    /// 用系统默认浏览器打开匹配`glob`的源
+    pub fn open_external_origin(self, glob: impl Into<String>) -> Self {
+        self.origin(glob, NavigationAction::OpenExternal)
+    }
+    /// 追加一条源的正则表达式规则
+    #[cfg(feature = "regex")]
+    pub fn origin_regex(self, regex: &str, action: NavigationAction) -> Result<Self, ::regex::Error> {
+        Ok(self.rule(NavigationMatcher::OriginRegex(Regex::new(regex)?), action))
+    }
+    /// 追加一条协议规则
+    pub fn scheme(self, scheme: impl Into<String>, action: NavigationAction) -> Self {
+        self.rule(NavigationMatcher::Scheme(scheme.into()), action)
+    }
+    /// 全部规则
+    pub fn rules(&self) -> &[NavigationRule] {
+        &self.rules
+    }
+    /// 无规则命中时的处置方式
+    pub fn default_action(&self) -> NavigationAction {
+        self.default_action
+    }
+    /// 决定地址`uri`的处置方式。若`about:blank`被判定为`OpenExternal`，则改为放行：它是新窗体与`iframe`的初始地址，在外部浏览器里打开它毫无意义。
+    pub fn decide(&self, uri: &str) -> NavigationAction {
+        let action = self.rules.iter().find(|rule| rule.matcher.matches(uri)).map_or(self.default_action, |rule| rule.action);
+        if action == NavigationAction::OpenExternal && is_about_blank(uri) {
+            return NavigationAction::Allow;
+        }
+        action
+    }
+}
+/// 从地址中解析出的协议、主机与端口
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct NavigationTarget {
+    scheme: String,
+    host: String,
+    port: Option<u16>
+}
+impl NavigationTarget {
+    fn parse(uri: &str) -> Option<Self> {
+        let (scheme, rest) = uri.trim().split_once(':')?;
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+            return None;
+        }
+        let scheme = scheme.to_ascii_lowercase();
+        let authority = if is_special_scheme(&scheme) { // 同浏览器一样，特殊协议的 \ 等同于 /。否则，https://evil.com\@allowed.com/ 的主机会被误判为 allowed.com
+            rest.trim_start_matches(['/', '\\']).split(['/', '\\', '?', '#']).next()
+        } else if let Some(rest) = rest.strip_prefix("//") {
+            rest.split(['/', '?', '#']).next()
+        } else { // 非层级地址。比如，about:blank 与 mailto:someone@example.com
+            return Some(Self { scheme, host: String::new(), port: None });
+        }.unwrap_or_default();
+        let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, if port.is_empty() { None } else { Some(port.parse().ok()?) }),
+            _ => (authority, None)
+        };
+        let port = port.filter(|port| default_port(&scheme) != Some(*port));
+        Some(Self { scheme, host: host.to_ascii_lowercase(), port })
+    }
+    /// 层级地址的源。比如，`https://app.example.com:8443`
+    fn origin(&self) -> Option<String> {
+        if self.host.is_empty() {
+            return None;
+        }
+        Some(match self.port {
+            Some(port) => format!("{}://{}:{port}", self.scheme, self.host),
+            None => format!("{}://{}", self.scheme, self.host)
+        })
+    }
+}
+/// 浏览器按层级地址解析的【特殊】协议
+fn is_special_scheme(scheme: &str) -> bool {
+    matches!(scheme, "http" | "https" | "ws" | "wss" | "ftp" | "file")
+}
+/// 是否是空白页`about:blank`（可带`?query`与`#fragment`）
+fn is_about_blank(uri: &str) -> bool {
+    uri.trim().split(['?', '#']).next().is_some_and(|uri| uri.eq_ignore_ascii_case("about:blank"))
+}
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None
+    }
+}
+/// 通配符匹配。`*`匹配任意个字符，`?`匹配单个字符。
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let (glob, text): (Vec<char>, Vec<char>) = (glob.chars().collect(), text.chars().collect());
+    let (mut g, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            },
+            Some(c) if *c == '?' || *c == text[t] => {
+                g += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => { // 令上一个 * 多吞掉一个字符
+                    backtrack = Some((star, matched + 1));
+                    g = star + 1;
+                    t = matched + 1;
+                },
+                None => return false
+            }
+        }
+    }
+    glob[g..].iter().all(|c| *c == '*')
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(uri: &str) -> Option<String> {
+        NavigationTarget::parse(uri).map(|target| target.host)
+    }
+    #[test]
+    fn parses_targets() {
+        assert_eq!(NavigationTarget::parse("HTTPS://User:pw@App.Example.com:443/path?q#f"), Some(NavigationTarget { scheme: String::from("https"), host: String::from("app.example.com"), port: None }));
+        assert_eq!(NavigationTarget::parse("http://localhost:8080"), Some(NavigationTarget { scheme: String::from("http"), host: String::from("localhost"), port: Some(8080) }));
+        assert_eq!(NavigationTarget::parse("https://[::1]/").map(|target| (target.host, target.port)), Some((String::from("[::1]"), None)));
+        assert_eq!(NavigationTarget::parse("mailto:someone@example.com").map(|target| (target.origin(), target.scheme)), Some((None, String::from("mailto"))));
+        assert_eq!(NavigationTarget::parse("https://host:bad/"), None);
+        assert_eq!(NavigationTarget::parse("not a uri"), None);
+    }
+    #[test]
+    fn backslashes_terminate_special_authorities() {
+        assert_eq!(host("https://evil.com\\@allowed.com/"), Some(String::from("evil.com")));
+        assert_eq!(host("https:\\\\evil.com\\path"), Some(String::from("evil.com")));
+        assert_eq!(host("http:/evil.com"), Some(String::from("evil.com")));
+        assert_eq!(host("custom://user\\@app.local/"), Some(String::from("app.local")));
+    }
+    #[test]
+    fn backslash_bypass_is_not_allowed() {
+        let policy = NavigationPolicy::new(NavigationAction::Deny).allow_origin("https://allowed.com");
+        assert_eq!(policy.decide("https://evil.com\\@allowed.com/"), NavigationAction::Deny);
+        assert_eq!(policy.decide("https://user@allowed.com/"), NavigationAction::Allow);
+    }
+    #[test]
+    fn matches_globs() {
+        let glob = |glob: &str, uri: &str| NavigationMatcher::OriginGlob(glob.to_string()).matches(uri);
+        assert!(glob("*.example.com", "https://app.example.com/index.html"));
+        assert!(!glob("*.example.com", "https://example.com/"));
+        assert!(glob("https://*.Example.com", "https://a.b.example.com/"));
+        assert!(!glob("https://*.example.com", "http://app.example.com/"));
+        assert!(glob("https://app.local", "https://app.local:443/"));
+        assert!(!glob("https://app.local", "https://app.local:8443/"));
+        assert!(glob("http://localhost:80?0", "http://localhost:8080/"));
+        assert!(!glob("*", "mailto:someone@example.com"));
+        assert!(glob_matches("a*b*c", "axxbyyc") && !glob_matches("a*b*c", "axxbyy"));
+    }
+    #[test]
+    fn matches_schemes() {
+        let scheme = NavigationMatcher::Scheme(String::from("MailTo"));
+        assert!(scheme.matches("mailto:someone@example.com"));
+        assert!(!scheme.matches("https://example.com"));
+    }
+    #[test]
+    fn first_matching_rule_wins() {
+        let policy = NavigationPolicy::new(NavigationAction::OpenExternal)
+            .deny_origin("ads.example.com")
+            .allow_origin("*.example.com")
+            .scheme("javascript", NavigationAction::Deny);
+        assert_eq!(policy.decide("https://ads.example.com/"), NavigationAction::Deny);
+        assert_eq!(policy.decide("https://www.example.com/"), NavigationAction::Allow);
+        assert_eq!(policy.decide("javascript:alert(1)"), NavigationAction::Deny);
+        assert_eq!(policy.decide("https://other.com/"), NavigationAction::OpenExternal);
+    }
+    #[test]
+    fn about_blank_is_never_opened_externally() {
+        let policy = NavigationPolicy::new(NavigationAction::OpenExternal);
+        assert_eq!(policy.decide("about:blank"), NavigationAction::Allow);
+        assert_eq!(policy.decide("About:Blank#top"), NavigationAction::Allow);
+        assert_eq!(policy.decide("about:srcdoc"), NavigationAction::OpenExternal);
+        let policy = policy.scheme("about", NavigationAction::Deny);
+        assert_eq!(policy.decide("about:blank"), NavigationAction::Deny);
+    }
+}
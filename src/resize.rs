@@ -0,0 +1,162 @@
+//! 合并拖拽缩放期间的尺寸调整：避免每个`OnResize`事件都同步地调用`GetClientRect`与`put_bounds`。
+//!
+//! 默认的[`ResizePolicy::Immediate`]策略逐一同步每个事件。而[`ResizePolicy::Coalesced`]策略下，
+//! 1. 一串尺寸调整的第一个事件被立即同步（前沿）；
+//! 2. 之后，每个时间间隔（默认是一帧）至多同步一次。同步时，总是读取容器的最新尺寸，所以中间的尺寸都被丢弃了；
+//! 3. 拖拽缩放结束（`WM_EXITSIZEMOVE`）时，还未同步的尺寸被立即同步。
+//!
+//! [`ResizeScheduler`]不读取系统时钟，而由调用方传入当前时刻。所以，它的合并逻辑能以模拟的事件序列被测试。
+use ::std::time::{Duration, Instant};
+
+const WM_EXITSIZEMOVE: u32 = 0x0232;
+/// 默认的合并间隔：`60 FPS`的一帧
+pub const DEFAULT_RESIZE_INTERVAL: Duration = Duration::from_millis(16);
+/// 如何同步`webview`控件的边界矩形
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ResizePolicy {
+    /// 每个`OnResize`事件都立即同步
+    #[default]
+    Immediate,
+    /// 每个时间间隔至多同步一次，并在拖拽缩放结束时补齐最后一次
+    Coalesced(Duration)
+}
+/// 尺寸调整事件的处理决定
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResizeDecision {
+    /// 立即同步
+    ApplyNow,
+    /// 预约在该时刻同步。届时，调用[`ResizeScheduler::on_timer()`]。
+    ApplyAt(Instant),
+    /// 已有预约。该事件被合并了
+    Coalesced
+}
+/// 判断原始的`Win32`消息是否是拖拽缩放（或移动）的结束（`WM_EXITSIZEMOVE`）
+pub fn is_exit_size_move(msg: u32) -> bool {
+    msg == WM_EXITSIZEMOVE
+}
+/// 尺寸调整的合并调度器
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResizeScheduler {
+    policy: ResizePolicy,
+    last_applied: Option<Instant>,
+    deadline: Option<Instant>
+}
+impl ResizeScheduler {
+    /// 以`policy`为策略
+    pub fn new(policy: ResizePolicy) -> Self {
+        Self { policy, last_applied: None, deadline: None }
+    }
+    /// 合并策略
+    pub fn policy(&self) -> ResizePolicy {
+        self.policy
+    }
+    /// 是否有还未同步的尺寸调整
+    pub fn is_pending(&self) -> bool {
+        self.deadline.is_some()
+    }
+    /// 容器的尺寸变化了
+    pub fn on_resize(&mut self, now: Instant) -> ResizeDecision {
+        let ResizePolicy::Coalesced(interval) = self.policy else {
+            return ResizeDecision::ApplyNow;
+        };
+        if self.deadline.is_some() {
+            return ResizeDecision::Coalesced;
+        }
+        match self.last_applied.map(|last_applied| last_applied + interval).filter(|next| *next > now) {
+            Some(next) => {
+                self.deadline = Some(next);
+                ResizeDecision::ApplyAt(next)
+            },
+            None => {
+                self.last_applied = Some(now);
+                ResizeDecision::ApplyNow
+            }
+        }
+    }
+    /// 预约的时刻到了。返回值表示是否需要同步。
+    pub fn on_timer(&mut self, now: Instant) -> bool {
+        self.flush(now)
+    }
+    /// 拖拽缩放结束了。立即同步还未同步的尺寸调整，并取消预约。返回值表示是否需要同步。
+    pub fn flush(&mut self, now: Instant) -> bool {
+        let is_pending = self.deadline.take().is_some();
+        if is_pending {
+            self.last_applied = Some(now);
+        }
+        is_pending
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = DEFAULT_RESIZE_INTERVAL;
+    fn coalesced() -> ResizeScheduler {
+        ResizeScheduler::new(ResizePolicy::Coalesced(FRAME))
+    }
+    #[test]
+    fn immediate_is_the_default() {
+        let start = Instant::now();
+        let mut scheduler = ResizeScheduler::default();
+        assert_eq!(scheduler.policy(), ResizePolicy::Immediate);
+        for offset in 0..3 {
+            assert_eq!(scheduler.on_resize(start + Duration::from_millis(offset)), ResizeDecision::ApplyNow);
+        }
+        assert!(!scheduler.is_pending());
+    }
+    #[test]
+    fn applies_the_leading_edge_at_once() {
+        let start = Instant::now();
+        let mut scheduler = coalesced();
+        assert_eq!(scheduler.on_resize(start), ResizeDecision::ApplyNow);
+        assert!(!scheduler.is_pending());
+        assert_eq!(scheduler.on_resize(start + FRAME), ResizeDecision::ApplyNow, "间隔已过，又是前沿");
+    }
+    #[test]
+    fn coalesces_a_burst() {
+        let start = Instant::now();
+        let mut scheduler = coalesced();
+        assert_eq!(scheduler.on_resize(start), ResizeDecision::ApplyNow);
+        assert_eq!(scheduler.on_resize(start + Duration::from_millis(1)), ResizeDecision::ApplyAt(start + FRAME));
+        for offset in 2..10 {
+            assert_eq!(scheduler.on_resize(start + Duration::from_millis(offset)), ResizeDecision::Coalesced);
+        }
+        assert!(scheduler.is_pending());
+    }
+    #[test]
+    fn applies_at_the_deadline_and_then_waits_an_interval() {
+        let start = Instant::now();
+        let mut scheduler = coalesced();
+        scheduler.on_resize(start);
+        let ResizeDecision::ApplyAt(deadline) = scheduler.on_resize(start + Duration::from_millis(5)) else {
+            panic!("应当预约同步");
+        };
+        assert!(scheduler.on_timer(deadline));
+        assert!(!scheduler.is_pending());
+        assert!(!scheduler.on_timer(deadline + FRAME), "没有预约时，定时器不触发同步");
+        assert_eq!(scheduler.on_resize(deadline + Duration::from_millis(1)), ResizeDecision::ApplyAt(deadline + FRAME));
+    }
+    #[test]
+    fn flushes_the_pending_resize() {
+        let start = Instant::now();
+        let mut scheduler = coalesced();
+        scheduler.on_resize(start);
+        scheduler.on_resize(start + Duration::from_millis(1));
+        assert!(scheduler.flush(start + Duration::from_millis(2)));
+        assert!(!scheduler.is_pending());
+        assert!(!scheduler.on_timer(start + FRAME), "被补齐之后，预约被取消了");
+    }
+    #[test]
+    fn flush_without_a_pending_resize_is_a_no_op() {
+        let start = Instant::now();
+        let mut scheduler = coalesced();
+        assert!(!scheduler.flush(start));
+        assert_eq!(scheduler.on_resize(start), ResizeDecision::ApplyNow, "空的补齐不推迟下一次前沿");
+        assert!(!scheduler.flush(start + Duration::from_millis(1)));
+    }
+    #[test]
+    fn recognizes_the_end_of_size_move() {
+        assert!(is_exit_size_move(WM_EXITSIZEMOVE));
+        assert!(!is_exit_size_move(0x0231)); // WM_ENTERSIZEMOVE
+    }
+}
@@ -0,0 +1,384 @@
+//! 基于网页消息的`JSON-RPC 2.0`调用。
+//!
+//! * 网页端：`await window.nwg.invoke("add", [1, 2], {timeout: 5000})`。返回值是`Promise`。
+//! * `Rust`端：`webview_container.rpc_handler("add", |(a, b): (i32, i32)| async move { Ok(a + b) })`。
+//!
+//! 请求与响应都经由`nwg.rpc`频道的消息信封传递，并按`id`关联。网页端的调用超时之后，会发送`$/cancelRequest`通知，以中止`Rust`端还在执行的处理函数。
+use ::futures::{channel::mpsc::UnboundedReceiver, future::{self, AbortHandle, Either, LocalBoxFuture}, stream::{FuturesUnordered, StreamExt}, FutureExt};
+use ::serde::{Deserialize, Serialize, de::DeserializeOwned};
+use ::serde_json::Value;
+use ::std::{cell::{Cell, RefCell}, collections::HashMap, error::Error, fmt::{self, Debug, Display, Formatter}, future::Future, rc::Rc, task::Poll, time::Duration};
+use super::log::debug;
+
+/// `RPC`消息所使用的频道名
+pub const RPC_CHANNEL: &str = "nwg.rpc";
+/// 网页端与`Rust`端共同的默认超时
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+const CANCEL_METHOD: &str = "$/cancelRequest";
+/// `JSON-RPC 2.0`的错误对象
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RpcError {
+    /// 错误码
+    pub code: i64,
+    /// 错误描述
+    pub message: String,
+    /// 附加数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>
+}
+impl RpcError {
+    /// 请求不是合法的`JSON`
+    pub const PARSE_ERROR: i64 = -32700;
+    /// 请求不是合法的`JSON-RPC 2.0`请求对象
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// 没有该方法的处理函数
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// 实参无法被反序列化为处理函数的形参类型
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// 处理函数的返回值无法被序列化
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// 处理函数执行超时
+    pub const TIMEOUT: i64 = -32000;
+    /// 请求被网页端取消了
+    pub const REQUEST_CANCELLED: i64 = -32800;
+    /// 构造错误对象
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+    /// 附加数据
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data.replace(data);
+        self
+    }
+}
+impl Display for RpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]{}", self.code, self.message)
+    }
+}
+impl Error for RpcError {}
+/// 处理函数的执行结果
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOutcome {
+    /// 执行成功
+    Result(Value),
+    /// 执行失败
+    Error(RpcError)
+}
+/// `JSON-RPC 2.0`的响应对象
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RpcResponse {
+    /// 协议版本号。总是`2.0`
+    pub jsonrpc: String,
+    /// 请求`id`。无法解析出请求`id`时，它是`null`。
+    pub id: Value,
+    /// 执行结果
+    #[serde(flatten)]
+    pub outcome: RpcOutcome
+}
+impl RpcResponse {
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        Self {
+            jsonrpc: String::from("2.0"),
+            id,
+            outcome: match outcome {
+                Ok(value) => RpcOutcome::Result(value),
+                Err(err) => RpcOutcome::Error(err)
+            }
+        }
+    }
+}
+/// 没有`id`的请求是通知，不需要响应。
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value
+}
+#[derive(Deserialize)]
+struct CancelParams {
+    id: Value
+}
+/// 生成被注入网页的`window.nwg.invoke()`垫片
+pub fn invoke_shim(default_timeout: Duration) -> String {
+    format!(r#"(() => {{
+    const CHANNEL = "{RPC_CHANNEL}", DEFAULT_TIMEOUT = {timeout};
+    const webview = window.chrome && window.chrome.webview;
+    if (!webview || (window.nwg && window.nwg.invoke)) {{
+        return;
+    }}
+    const pending = new Map();
+    let nextId = 1;
+    const settle = (id, settler) => {{
+        const call = pending.get(id);
+        if (call) {{
+            pending.delete(id);
+            clearTimeout(call.timer);
+            settler(call);
+        }}
+    }};
+    webview.addEventListener("message", ({{data}}) => {{
+        if (!data || data.channel !== CHANNEL || !data.payload) {{
+            return;
+        }}
+        const {{id, result, error}} = data.payload;
+        settle(id, call => error ? call.reject(Object.assign(new Error(error.message), error)) : call.resolve(result));
+    }});
+    window.nwg = Object.assign(window.nwg || {{}}, {{
+        invoke(method, params, {{timeout = DEFAULT_TIMEOUT}} = {{}}) {{
+            return new Promise((resolve, reject) => {{
+                const id = nextId++;
+                const timer = setTimeout(() => settle(id, call => {{
+                    webview.postMessage({{channel: CHANNEL, payload: {{jsonrpc: "2.0", method: "{CANCEL_METHOD}", params: {{id}}}}}});
+                    call.reject(Object.assign(new Error(`${{method}} timed out`), {{code: {timeout_code}}}));
+                }}), timeout);
+                pending.set(id, {{resolve, reject, timer}});
+                webview.postMessage({{channel: CHANNEL, payload: {{jsonrpc: "2.0", id, method, params}}}});
+            }});
+        }}
+    }});
+}})();"#, timeout = default_timeout.as_millis(), timeout_code = RpcError::TIMEOUT)
+}
+/// 类型擦除之后的处理函数：`JSON`实参进，`JSON`结果出
+pub(crate) type RpcHandler = Rc<dyn Fn(Value) -> LocalBoxFuture<'static, Result<Value, RpcError>>>;
+/// 将类型化的处理函数包装为`RpcHandler`。实参无法被反序列化为`P`时，响应`RpcError::INVALID_PARAMS`。
+pub(crate) fn typed_handler<P, R, F, Fut>(handler: F) -> RpcHandler
+where P: DeserializeOwned,
+      R: Serialize,
+      F: Fn(P) -> Fut + 'static,
+      Fut: Future<Output = Result<R, RpcError>> + 'static {
+    Rc::new(move |params| match ::serde_json::from_value(params) {
+        Ok(params) => handler(params).map(|result| result.and_then(|value|
+            ::serde_json::to_value(value).map_err(|err| RpcError::new(RpcError::INTERNAL_ERROR, err.to_string()))
+        )).boxed_local(),
+        Err(err) => future::ready(Err(RpcError::new(RpcError::INVALID_PARAMS, err.to_string()))).boxed_local()
+    })
+}
+/// 按方法名分发`RPC`请求，并按请求`id`记录执行中的调用。
+pub(crate) struct RpcDispatcher {
+    handlers: RefCell<HashMap<String, RpcHandler>>,
+    in_flight: Rc<RefCell<HashMap<String, AbortHandle>>>,
+    timeout: Cell<Duration>
+}
+impl Default for RpcDispatcher {
+    fn default() -> Self {
+        Self {
+            handlers: RefCell::default(),
+            in_flight: Rc::default(),
+            timeout: Cell::new(DEFAULT_RPC_TIMEOUT)
+        }
+    }
+}
+impl Debug for RpcDispatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcDispatcher")
+            .field("methods", &self.handlers.borrow().keys().collect::<Vec<_>>())
+            .field("in_flight", &self.in_flight.borrow().len())
+            .field("timeout", &self.timeout.get())
+            .finish()
+    }
+}
+#[cfg_attr(not(windows), allow(dead_code))]
+impl RpcDispatcher {
+    /// 注册（或替换）方法的处理函数
+    pub(crate) fn register<P, R, F, Fut>(&self, method: String, handler: F)
+    where P: DeserializeOwned,
+          R: Serialize,
+          F: Fn(P) -> Fut + 'static,
+          Fut: Future<Output = Result<R, RpcError>> + 'static {
+        self.register_handler(method, typed_handler(handler));
+    }
+    /// 注册（或替换）方法的类型擦除处理函数
+    pub(crate) fn register_handler(&self, method: String, handler: RpcHandler) {
+        self.handlers.borrow_mut().insert(method, handler);
+    }
+    /// 注销方法的处理函数
+    pub(crate) fn unregister(&self, method: &str) {
+        self.handlers.borrow_mut().remove(method);
+    }
+    /// 处理函数的执行时限
+    pub(crate) fn set_timeout(&self, timeout: Duration) {
+        self.timeout.set(timeout);
+    }
+    /// 分发一条请求。`timer`按执行时限构造超时`Future`。返回的`Future`决议为响应对象；若请求是通知，则决议为`None`。
+    pub(crate) fn dispatch<T, TF>(&self, payload: Value, timer: T) -> LocalBoxFuture<'static, Option<RpcResponse>>
+    where T: FnOnce(Duration) -> TF,
+          TF: Future<Output = ()> + 'static {
+        let request = match ::serde_json::from_value::<RpcRequest>(payload) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(request) => return Self::reject(request.id, RpcError::new(RpcError::INVALID_REQUEST, format!("不支持的协议版本 {}", request.jsonrpc))),
+            Err(err) => return Self::reject(Some(Value::Null), RpcError::new(RpcError::INVALID_REQUEST, err.to_string()))
+        };
+        if request.method == CANCEL_METHOD {
+            if let Ok(CancelParams { id }) = ::serde_json::from_value(request.params) {
+                self.cancel(&id);
+            }
+            return future::ready(None).boxed_local();
+        }
+        let Some(handler) = self.handlers.borrow().get(&request.method).cloned() else {
+            return Self::reject(request.id, RpcError::new(RpcError::METHOD_NOT_FOUND, format!("方法 {} 没有处理函数", request.method)));
+        };
+        let Some(id) = request.id else { // 通知：执行处理函数，但丢弃其结果
+            return handler(request.params).map(|_| None).boxed_local();
+        };
+        let key = id.to_string();
+        if self.in_flight.borrow().contains_key(&key) {
+            return Self::reject(Some(id), RpcError::new(RpcError::INVALID_REQUEST, format!("请求 id {key} 重复了")));
+        }
+        let (call, abort_handle) = future::abortable(handler(request.params));
+        self.in_flight.borrow_mut().insert(key.clone(), abort_handle);
+        let in_flight = Rc::downgrade(&self.in_flight);
+        let timeout = self.timeout.get();
+        let deadline = timer(timeout);
+        async move {
+            let outcome = match future::select(call, Box::pin(deadline)).await {
+                Either::Left((Ok(outcome), _)) => outcome,
+                Either::Left((Err(_), _)) => Err(RpcError::new(RpcError::REQUEST_CANCELLED, "请求被取消了")),
+                Either::Right(_) => Err(RpcError::new(RpcError::TIMEOUT, format!("处理函数执行超过了 {timeout:?}")))
+            };
+            if let Some(in_flight) = in_flight.upgrade() {
+                in_flight.borrow_mut().remove(&key);
+            }
+            Some(RpcResponse::new(id, outcome))
+        }.boxed_local()
+    }
+    /// 中止执行中的调用。返回值表示该调用是否存在。
+    pub(crate) fn cancel(&self, id: &Value) -> bool {
+        let abort_handle = self.in_flight.borrow_mut().remove(&id.to_string());
+        abort_handle.map(|abort_handle| abort_handle.abort()).is_some()
+    }
+    /// 中止全部执行中的调用
+    pub(crate) fn cancel_all(&self) {
+        self.in_flight.borrow_mut().drain().for_each(|(_, abort_handle)| abort_handle.abort());
+    }
+    fn reject(id: Option<Value>, err: RpcError) -> LocalBoxFuture<'static, Option<RpcResponse>> {
+        debug!("[RpcDispatcher][dispatch]{err}");
+        future::ready(id.map(|id| RpcResponse::new(id, Err(err)))).boxed_local()
+    }
+}
+/// `RPC`服务循环：并发地执行`requests`里的每条请求，并经由`post`回送响应。在`requests`被关闭、且全部调用都结束之后，它才会被决议。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn serve<P, T, TF>(dispatcher: Rc<RpcDispatcher>, mut requests: UnboundedReceiver<Value>, post: P, timer: T) -> impl Future<Output = ()>
+where P: Fn(&RpcResponse),
+      T: Fn(Duration) -> TF,
+      TF: Future<Output = ()> + 'static {
+    let mut calls = FuturesUnordered::new();
+    let mut is_closed = false;
+    future::poll_fn(move |cx| {
+        while !is_closed {
+            match requests.poll_next_unpin(cx) {
+                Poll::Ready(Some(payload)) => calls.push(dispatcher.dispatch(payload, &timer)),
+                Poll::Ready(None) => is_closed = true,
+                Poll::Pending => break
+            }
+        }
+        while let Poll::Ready(Some(response)) = calls.poll_next_unpin(cx) {
+            if let Some(response) = response {
+                post(&response);
+            }
+        }
+        if is_closed && calls.is_empty() {
+            dispatcher.cancel_all();
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+}
+#[cfg(test)]
+mod tests {
+    use ::futures::channel::mpsc;
+    use ::serde_json::json;
+    use super::*;
+
+    fn dispatcher() -> RpcDispatcher {
+        let dispatcher = RpcDispatcher::default();
+        dispatcher.register(String::from("add"), |(a, b): (i32, i32)| async move { Ok::<_, RpcError>(a + b) });
+        dispatcher.register(String::from("hang"), |_: Value| future::pending::<Result<(), RpcError>>());
+        dispatcher
+    }
+    fn never(_: Duration) -> future::Pending<()> {
+        future::pending()
+    }
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+    }
+    fn error_code(response: Option<Option<RpcResponse>>) -> i64 {
+        match response.flatten().map(|response| response.outcome) {
+            Some(RpcOutcome::Error(err)) => err.code,
+            outcome => panic!("不是错误响应：{outcome:?}")
+        }
+    }
+    #[test]
+    fn dispatches_to_the_typed_handler() {
+        let response = dispatcher().dispatch(request(1, "add", json!([1, 2])), never).now_or_never().flatten().unwrap();
+        assert_eq!(response, RpcResponse::new(json!(1), Ok(json!(3))));
+        assert_eq!(::serde_json::to_value(&response).unwrap(), json!({"jsonrpc": "2.0", "id": 1, "result": 3}));
+    }
+    #[test]
+    fn rejects_bad_requests() {
+        let dispatcher = dispatcher();
+        assert_eq!(error_code(dispatcher.dispatch(request(1, "missing", json!([])), never).now_or_never()), RpcError::METHOD_NOT_FOUND);
+        assert_eq!(error_code(dispatcher.dispatch(request(2, "add", json!(["a", 2])), never).now_or_never()), RpcError::INVALID_PARAMS);
+        assert_eq!(error_code(dispatcher.dispatch(json!({"jsonrpc": "1.0", "id": 3, "method": "add"}), never).now_or_never()), RpcError::INVALID_REQUEST);
+        assert_eq!(error_code(dispatcher.dispatch(json!("add"), never).now_or_never()), RpcError::INVALID_REQUEST);
+    }
+    #[test]
+    fn notifications_are_not_answered() {
+        let response = dispatcher().dispatch(json!({"jsonrpc": "2.0", "method": "add", "params": [1, 2]}), never).now_or_never();
+        assert_eq!(response, Some(None));
+    }
+    #[test]
+    fn duplicate_ids_are_rejected_while_in_flight() {
+        let dispatcher = dispatcher();
+        let mut first = dispatcher.dispatch(request(7, "hang", json!(null)), never);
+        assert!((&mut first).now_or_never().is_none());
+        assert_eq!(error_code(dispatcher.dispatch(request(7, "add", json!([1, 2])), never).now_or_never()), RpcError::INVALID_REQUEST);
+        assert!(dispatcher.cancel(&json!(7)));
+        assert_eq!(error_code(first.now_or_never()), RpcError::REQUEST_CANCELLED);
+        let reused = dispatcher.dispatch(request(7, "add", json!([1, 2])), never).now_or_never().flatten().unwrap();
+        assert_eq!(reused.outcome, RpcOutcome::Result(json!(3)));
+    }
+    #[test]
+    fn cancel_request_notification_aborts_the_call() {
+        let dispatcher = dispatcher();
+        let call = dispatcher.dispatch(request(9, "hang", json!(null)), never);
+        let cancel = dispatcher.dispatch(json!({"jsonrpc": "2.0", "method": CANCEL_METHOD, "params": {"id": 9}}), never);
+        assert_eq!(cancel.now_or_never(), Some(None));
+        assert_eq!(error_code(call.now_or_never()), RpcError::REQUEST_CANCELLED);
+        assert!(!dispatcher.cancel(&json!(9)));
+    }
+    #[test]
+    fn times_out_with_the_configured_timeout() {
+        let dispatcher = dispatcher();
+        dispatcher.set_timeout(Duration::from_millis(250));
+        let timeout = Rc::new(Cell::new(None));
+        let record = Rc::clone(&timeout);
+        let response = dispatcher.dispatch(request(3, "hang", json!(null)), move |duration| {
+            record.set(Some(duration));
+            future::ready(())
+        }).now_or_never();
+        assert_eq!(error_code(response), RpcError::TIMEOUT);
+        assert_eq!(timeout.get(), Some(Duration::from_millis(250)));
+    }
+    #[test]
+    fn serve_answers_every_request_until_the_queue_closes() {
+        let (sender, receiver) = mpsc::unbounded();
+        sender.unbounded_send(request(1, "add", json!([1, 2]))).unwrap();
+        sender.unbounded_send(request(2, "missing", json!([]))).unwrap();
+        drop(sender);
+        let posted = RefCell::new(Vec::new());
+        assert_eq!(serve(Rc::new(dispatcher()), receiver, |response| posted.borrow_mut().push(response.id.clone()), never).now_or_never(), Some(()));
+        assert_eq!(posted.into_inner(), vec![json!(1), json!(2)]);
+    }
+    #[test]
+    fn shim_uses_the_given_timeout() {
+        let shim = invoke_shim(Duration::from_secs(5));
+        assert!(shim.contains("DEFAULT_TIMEOUT = 5000;"));
+        assert!(shim.contains(RPC_CHANNEL));
+    }
+}
@@ -0,0 +1,166 @@
+//! `WebView2 Runtime`的可用性探测：已安装、未安装，或版本低于`target_compatible_browser_version`。
+use ::std::{cmp::Ordering, fmt::{self, Display, Formatter}};
+use super::backend::{EnvironmentOptions, WebviewBackend};
+
+/// `WebView2 Runtime`的官方下载页（`Evergreen Bootstrapper`）
+pub const RUNTIME_DOWNLOAD_URL: &str = "https://go.microsoft.com/fwlink/p/?LinkId=2124703";
+/// 浏览器版本号。比如，`120.0.2210.91`。缺省的段被视为`0`，所以`120`与`120.0.0.0`相等。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BrowserVersion([u32; 4]);
+impl BrowserVersion {
+    /// 由四段版本号构造
+    pub fn new(major: u32, minor: u32, build: u32, patch: u32) -> Self {
+        Self([major, minor, build, patch])
+    }
+    /// 解析`get_available_browser_version_string()`的返回值。
+    /// 预览通道的版本号带有通道名后缀（比如，`121.0.2277.4 canary`），它会被忽略。
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.split_whitespace().next()?;
+        let mut parts = [0; 4];
+        for (index, part) in version.split('.').enumerate() {
+            if index == parts.len() || part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            parts[index] = part.parse().ok()?;
+        }
+        Some(Self(parts))
+    }
+    /// 主版本号
+    pub fn major(&self) -> u32 {
+        self.0[0]
+    }
+}
+impl Ord for BrowserVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+impl PartialOrd for BrowserVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Display for BrowserVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let [major, minor, build, patch] = self.0;
+        write!(f, "{major}.{minor}.{build}.{patch}")
+    }
+}
+/// `WebView2 Runtime`的可用性
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimeStatus {
+    /// 已安装，且满足版本要求
+    Installed(BrowserVersion),
+    /// 未安装
+    Missing,
+    /// 已安装，但版本低于`target_compatible_browser_version`
+    TooOld {
+        /// 已安装的版本
+        installed: BrowserVersion,
+        /// 要求的最低版本
+        required: BrowserVersion
+    }
+}
+impl RuntimeStatus {
+    /// 由已安装的版本号（`None`表示未安装）与要求的最低版本号（`None`表示不限）判定可用性。
+    /// 无法解析的已安装版本号被视为未安装；无法解析的最低版本号被视为不限。
+    pub fn evaluate(installed: Option<&str>, required: Option<&str>) -> Self {
+        let Some(installed) = installed.and_then(BrowserVersion::parse) else {
+            return Self::Missing;
+        };
+        match required.and_then(BrowserVersion::parse) {
+            Some(required) if installed < required => Self::TooOld { installed, required },
+            _ => Self::Installed(installed)
+        }
+    }
+    /// 是否可以构造`webview`控件
+    pub fn is_available(&self) -> bool {
+        matches!(self, Self::Installed(_))
+    }
+}
+impl Display for RuntimeStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Installed(version) => write!(f, "WebView2 Runtime {version} 已安装"),
+            Self::Missing => write!(f, "WebView2 Runtime 未安装"),
+            Self::TooOld { installed, required } => write!(f, "WebView2 Runtime 版本过低：已安装 {installed}，至少需要 {required}")
+        }
+    }
+}
+/// `WebView2 Runtime`不可用时，在容器`nwg::Frame`内替代网页显示的原生界面
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeFallback {
+    /// 提示文字。它后面会被追加[`RuntimeStatus`]的描述。
+    pub message: String,
+    /// 下载按钮的文字
+    pub button_text: String,
+    /// 点击下载按钮之后，由系统默认浏览器打开的地址
+    pub download_url: String
+}
+impl Default for RuntimeFallback {
+    fn default() -> Self {
+        Self {
+            message: String::from("该页面需要 Microsoft Edge WebView2 Runtime 才能显示。"),
+            button_text: String::from("下载 WebView2 Runtime"),
+            download_url: String::from(RUNTIME_DOWNLOAD_URL)
+        }
+    }
+}
+/// 经由后端探测`WebView2 Runtime`的可用性
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn probe<B: WebviewBackend>(backend: &B, options: &EnvironmentOptions<'_>) -> RuntimeStatus {
+    let installed = backend.get_available_browser_version_string(options).ok();
+    RuntimeStatus::evaluate(installed.as_deref(), options.target_compatible_browser_version)
+}
+#[cfg(test)]
+mod tests {
+    use crate::backend::{BackendError, RecordingBackend};
+    use super::*;
+
+    fn options(required: Option<&str>) -> EnvironmentOptions<'_> {
+        EnvironmentOptions { target_compatible_browser_version: required, ..EnvironmentOptions::default() }
+    }
+    #[test]
+    fn parses_versions() {
+        assert_eq!(BrowserVersion::parse("120.0.2210.91"), Some(BrowserVersion::new(120, 0, 2210, 91)));
+        assert_eq!(BrowserVersion::parse("120"), Some(BrowserVersion::new(120, 0, 0, 0)));
+        assert_eq!(BrowserVersion::parse(" 121.0.2277.4 canary"), Some(BrowserVersion::new(121, 0, 2277, 4)));
+        for version in ["", "canary", "120.", ".120", "1.2.3.4.5", "120.0-beta", "99999999999"] {
+            assert_eq!(BrowserVersion::parse(version), None, "{version:?}");
+        }
+    }
+    #[test]
+    fn compares_versions_numerically() {
+        let parse = |version| BrowserVersion::parse(version).unwrap();
+        assert!(parse("120.0.2210.91") < parse("120.0.10000.0"));
+        assert!(parse("99.9") < parse("100"));
+        assert_eq!(parse("120"), parse("120.0.0.0"));
+        assert_eq!(parse("120.1").major(), 120);
+        assert_eq!(parse("120.1").to_string(), "120.1.0.0");
+    }
+    #[test]
+    fn evaluates_status() {
+        let installed = BrowserVersion::new(120, 0, 2210, 91);
+        assert_eq!(RuntimeStatus::evaluate(Some("120.0.2210.91"), None), RuntimeStatus::Installed(installed));
+        assert_eq!(RuntimeStatus::evaluate(Some("120.0.2210.91"), Some("120.0.2210.91")), RuntimeStatus::Installed(installed));
+        assert_eq!(RuntimeStatus::evaluate(Some("120.0.2210.91"), Some("121")), RuntimeStatus::TooOld { installed, required: BrowserVersion::new(121, 0, 0, 0) });
+        assert_eq!(RuntimeStatus::evaluate(Some("120.0.2210.91"), Some("latest")), RuntimeStatus::Installed(installed));
+        assert_eq!(RuntimeStatus::evaluate(Some("garbage"), None), RuntimeStatus::Missing);
+        assert_eq!(RuntimeStatus::evaluate(None, Some("121")), RuntimeStatus::Missing);
+        assert!(RuntimeStatus::Installed(installed).is_available());
+        assert!(!RuntimeStatus::Missing.is_available());
+        assert_eq!(RuntimeStatus::evaluate(Some("120"), Some("121")).to_string(), "WebView2 Runtime 版本过低：已安装 120.0.0.0，至少需要 121.0.0.0");
+    }
+    #[test]
+    fn probes_the_backend() {
+        let backend = RecordingBackend::default();
+        assert_eq!(probe(&backend, &options(None)), RuntimeStatus::Installed(BrowserVersion::new(0, 0, 0, 0)));
+        backend.set_browser_version("120.0.2210.91 beta");
+        assert_eq!(probe(&backend, &options(Some("119"))), RuntimeStatus::Installed(BrowserVersion::new(120, 0, 2210, 91)));
+        assert!(matches!(probe(&backend, &options(Some("121"))), RuntimeStatus::TooOld { .. }));
+        backend.uninstall_runtime();
+        assert_eq!(probe(&backend, &options(None)), RuntimeStatus::Missing);
+        backend.fail_browser_version(BackendError::new(-1, "探测失败"));
+        assert_eq!(probe(&backend, &options(None)), RuntimeStatus::Missing);
+    }
+}
@@ -0,0 +1,167 @@
+//! 异步执行网页脚本，并将其结果反序列化为`Rust`数据类型。
+//!
+//! 被执行的脚本先被包装于`try/catch`之内：
+//! * 脚本的结果被编码为`{"ok": ...}`；
+//! * 脚本抛出的异常被编码为`{"error": {"name": ..., "message": ..., "stack": ...}}`。
+//!
+//! 于是，`JS`异常（[`ScriptError::Exception`]）与结果的反序列化失败（[`ScriptError::Deserialization`]）就能被区分开。
+use ::futures::{channel::oneshot, future::{self, Either, LocalBoxFuture}, FutureExt};
+use ::serde::{Deserialize, de::DeserializeOwned};
+use ::serde_json::Value;
+use ::std::{error::Error, fmt::{self, Display, Formatter}, future::Future, time::Duration};
+use super::{backend::WebviewBackend, error::WebviewContainerError};
+
+/// `eval()`的默认超时
+pub const DEFAULT_SCRIPT_TIMEOUT: Duration = Duration::from_secs(30);
+/// 网页脚本抛出的异常
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ScriptException {
+    /// 异常类型名。比如，`TypeError`
+    pub name: String,
+    /// 异常描述
+    pub message: String,
+    /// 调用栈（若有）
+    #[serde(default)]
+    pub stack: Option<String>
+}
+impl Display for ScriptException {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+/// 网页脚本执行失败的原因
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    /// 脚本抛出了异常
+    Exception(ScriptException),
+    /// 脚本的结果无法被反序列化为目标数据类型
+    Deserialization(String),
+    /// 脚本执行超时了
+    TimedOut(Duration)
+}
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exception(exception) => write!(f, "网页脚本抛出了异常：{exception}"),
+            Self::Deserialization(message) => write!(f, "网页脚本的结果反序列化失败：{message}"),
+            Self::TimedOut(timeout) => write!(f, "网页脚本执行超过了 {timeout:?}")
+        }
+    }
+}
+impl Error for ScriptError {}
+/// 包装之后的脚本结果
+#[derive(Deserialize)]
+struct ScriptOutcome {
+    #[serde(default)]
+    ok: Value,
+    #[serde(default)]
+    error: Option<ScriptException>
+}
+/// 将脚本包装为捕获异常、且将结果编码为`JSON`字符串的表达式。
+///
+/// 脚本经由间接`eval`在全局作用域内被执行，所以它既可以是表达式，也可以是语句序列（结果是最后一条语句的值）。
+/// `undefined`结果被编码为`null`。`Promise`结果不会被等待。
+pub fn wrap_script(script: &str) -> String {
+    let source = Value::String(script.to_string());
+    format!(r#"(() => {{
+    try {{
+        const ok = (0, eval)({source});
+        return JSON.stringify({{ok: ok === undefined ? null : ok}});
+    }} catch (e) {{
+        const error = e instanceof Error ? {{name: e.name, message: e.message, stack: e.stack}} : {{name: "Error", message: String(e)}};
+        return JSON.stringify({{error}});
+    }}
+}})()"#)
+}
+/// 解码被包装脚本的执行结果。`json`是`execute_script`回调的实参，即`JSON`字符串字面量。
+pub fn decode_script_result<T: DeserializeOwned>(json: &str) -> Result<T, ScriptError> {
+    let deserialization = |err: ::serde_json::Error| ScriptError::Deserialization(err.to_string());
+    let outcome: String = ::serde_json::from_str(json).map_err(deserialization)?;
+    let ScriptOutcome { ok, error } = ::serde_json::from_str(&outcome).map_err(deserialization)?;
+    match error {
+        Some(exception) => Err(ScriptError::Exception(exception)),
+        None => ::serde_json::from_value(ok).map_err(deserialization)
+    }
+}
+/// 执行被包装的脚本，并等待其结果。`timer`按超时构造超时`Future`。
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn evaluate<B, T, TF>(backend: &B, webview: &B::WebView, script: &str, timeout: Duration, timer: impl FnOnce(Duration) -> TF) -> LocalBoxFuture<'static, Result<T, WebviewContainerError>>
+where B: WebviewBackend,
+      T: DeserializeOwned + 'static,
+      TF: Future<Output = ()> + 'static {
+    let (settle, settled) = oneshot::channel();
+    if let Err(err) = backend.execute_script(webview, &wrap_script(script), Box::new(move |result| {
+        settle.send(result).ok();
+    })) {
+        return future::ready(Err(WebviewContainerError::ScriptExecution(err))).boxed_local();
+    }
+    let deadline = timer(timeout);
+    async move {
+        match future::select(settled, Box::pin(deadline)).await {
+            Either::Left((Ok(Ok(json)), _)) => decode_script_result(&json).map_err(WebviewContainerError::Script),
+            Either::Left((Ok(Err(err)), _)) => Err(WebviewContainerError::ScriptExecution(err)),
+            Either::Left((Err(_), _)) => Err(WebviewContainerError::AlreadyClosed),
+            Either::Right(_) => Err(WebviewContainerError::Script(ScriptError::TimedOut(timeout)))
+        }
+    }.boxed_local()
+}
+#[cfg(test)]
+mod tests {
+    use ::serde::Deserialize;
+    use crate::backend::{BackendCall, BackendError, RecordingBackend};
+    use super::*;
+
+    /// `execute_script`回调收到的`JSON`字符串字面量
+    fn literal(outcome: &str) -> String {
+        Value::String(outcome.to_string()).to_string()
+    }
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32
+    }
+    #[test]
+    fn decodes_results() {
+        assert_eq!(decode_script_result::<Point>(&literal(r#"{"ok":{"x":1,"y":2}}"#)), Ok(Point { x: 1, y: 2 }));
+        assert_eq!(decode_script_result::<Option<i32>>(&literal(r#"{"ok":null}"#)), Ok(None));
+        assert_eq!(decode_script_result::<()>(&literal(r#"{}"#)), Ok(()));
+        assert_eq!(decode_script_result::<String>(&literal(r#"{"ok":"中文 \"引号\""}"#)), Ok(String::from("中文 \"引号\"")));
+    }
+    #[test]
+    fn decodes_exceptions() {
+        let error = decode_script_result::<Value>(&literal(r#"{"error":{"name":"TypeError","message":"x is undefined","stack":"at <anonymous>:1:1"}}"#));
+        assert_eq!(error, Err(ScriptError::Exception(ScriptException { name: String::from("TypeError"), message: String::from("x is undefined"), stack: Some(String::from("at <anonymous>:1:1")) })));
+        let error = decode_script_result::<Value>(&literal(r#"{"error":{"name":"Error","message":"42"}}"#)).unwrap_err();
+        assert_eq!(error.to_string(), "网页脚本抛出了异常：Error: 42");
+    }
+    #[test]
+    fn separates_deserialization_failures() {
+        assert!(matches!(decode_script_result::<Point>(&literal(r#"{"ok":{"x":"1"}}"#)), Err(ScriptError::Deserialization(_))));
+        assert!(matches!(decode_script_result::<Value>(r#"{"ok":1}"#), Err(ScriptError::Deserialization(_))), "结果应是字符串字面量");
+        assert!(matches!(decode_script_result::<Value>(&literal("not json")), Err(ScriptError::Deserialization(_))));
+        assert!(matches!(decode_script_result::<Value>("null"), Err(ScriptError::Deserialization(_))));
+    }
+    #[test]
+    fn wraps_scripts_as_string_literals() {
+        let wrapped = wrap_script("document.title = \"a\"; `b`\n");
+        assert!(wrapped.contains(r#"(0, eval)("document.title = \"a\"; `b`\n")"#));
+        assert!(wrapped.starts_with("(() => {") && wrapped.ends_with("})()"));
+    }
+    #[test]
+    fn evaluates_through_the_backend() {
+        let backend = RecordingBackend::default();
+        let result = evaluate::<_, i32, _>(&backend, &(), "1 + 1", DEFAULT_SCRIPT_TIMEOUT, |_| future::pending());
+        assert_eq!(backend.calls(), vec![BackendCall::ExecuteScript(wrap_script("1 + 1"))]);
+        assert!(backend.finish_script(Ok(&literal(r#"{"ok":2}"#))));
+        assert_eq!(result.now_or_never(), Some(Ok(2)));
+        let result = evaluate::<_, i32, _>(&backend, &(), "1 + 1", DEFAULT_SCRIPT_TIMEOUT, |_| future::pending());
+        backend.finish_script(Err(BackendError::new(-1, "执行失败")));
+        assert_eq!(result.now_or_never(), Some(Err(WebviewContainerError::ScriptExecution(BackendError::new(-1, "执行失败")))));
+    }
+    #[test]
+    fn times_out() {
+        let backend = RecordingBackend::default();
+        let result = evaluate::<_, Value, _>(&backend, &(), "new Promise(() => {})", Duration::from_millis(10), |_| future::ready(()));
+        assert_eq!(result.now_or_never(), Some(Err(WebviewContainerError::Script(ScriptError::TimedOut(Duration::from_millis(10))))));
+    }
+}
@@ -0,0 +1,203 @@
+//! 隐藏容器的挂起策略：在`webview`控件不可见时，经由`TrySuspend`挂起网页的渲染进程与定时器，以节省内存与电量。
+//!
+//! [`SuspendScheduler`]仅根据可见性、电源状态与【当前时刻】做决定，而不读取系统时钟。
+//! 于是，它的每个决定都能以伪造的时钟（即，手工构造的`Instant`序列）被测试：
+//! ```ignore
+//! let start = Instant::now();
+//! let mut scheduler = SuspendScheduler::new(SuspendPolicy::AfterHidden(Duration::from_secs(60)));
+//! assert_eq!(scheduler.on_visibility(false, start), None);
+//! assert_eq!(scheduler.next_deadline(), Some(start + Duration::from_secs(60)));
+//! assert_eq!(scheduler.poll(start + Duration::from_secs(60)), Some(SuspendAction::Suspend));
+//! assert_eq!(scheduler.on_visibility(true, start + Duration::from_secs(90)), Some(SuspendAction::Resume));
+//! ```
+use ::std::time::{Duration, Instant};
+
+const WM_POWERBROADCAST: u32 = 0x0218;
+const PBT_APMPOWERSTATUSCHANGE: usize = 0x000A;
+/// 何时挂起不可见的`webview`控件
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SuspendPolicy {
+    /// 从不挂起。`webview`控件仅被隐藏。
+    #[default]
+    Never,
+    /// 主窗体被最小化（或隐藏）时，立即挂起
+    OnMinimize,
+    /// 主窗体被最小化（或隐藏）超过给定时长之后，才挂起
+    AfterHidden(Duration),
+    /// 主窗体被最小化（或隐藏），且计算机由电池供电时，才挂起。接通电源之后，即便主窗体仍不可见，也恢复
+    OnBattery
+}
+/// 挂起调度器的决定
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuspendAction {
+    /// 挂起`webview`控件
+    Suspend,
+    /// 恢复`webview`控件
+    Resume
+}
+/// 判断原始的`Win32`消息是否是电源状态变化的通知（`WM_POWERBROADCAST` + `PBT_APMPOWERSTATUSCHANGE`）
+pub fn is_power_status_change(msg: u32, wparam: usize) -> bool {
+    (msg, wparam) == (WM_POWERBROADCAST, PBT_APMPOWERSTATUSCHANGE)
+}
+/// 挂起调度器。它的每个输入都返回一个决定（若有），且同一决定不会被连续给出两次。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SuspendScheduler {
+    policy: SuspendPolicy,
+    hidden_since: Option<Instant>,
+    is_on_battery: bool,
+    is_suspended: bool,
+    /// 在本次不可见期间，挂起失败了。重新可见之前，不再尝试挂起。
+    has_failed: bool
+}
+impl SuspendScheduler {
+    /// 以`policy`为策略。初始时，`webview`控件可见，且计算机接通了电源。
+    pub fn new(policy: SuspendPolicy) -> Self {
+        Self { policy, hidden_since: None, is_on_battery: false, is_suspended: false, has_failed: false }
+    }
+    /// 挂起策略
+    pub fn policy(&self) -> SuspendPolicy {
+        self.policy
+    }
+    /// `webview`控件是否（被认为）已被挂起了
+    pub fn is_suspended(&self) -> bool {
+        self.is_suspended
+    }
+    /// `webview`控件的可见性变化了
+    pub fn on_visibility(&mut self, is_visible: bool, now: Instant) -> Option<SuspendAction> {
+        if is_visible {
+            self.hidden_since = None;
+            self.has_failed = false;
+        } else if self.hidden_since.is_none() {
+            self.hidden_since = Some(now);
+        }
+        self.poll(now)
+    }
+    /// 电源状态变化了
+    pub fn on_power(&mut self, is_on_battery: bool, now: Instant) -> Option<SuspendAction> {
+        self.is_on_battery = is_on_battery;
+        self.poll(now)
+    }
+    /// 后端拒绝了挂起。在`webview`控件重新可见之前，不再尝试挂起。
+    pub fn on_suspend_failed(&mut self) {
+        if self.hidden_since.is_some() {
+            self.is_suspended = false;
+            self.has_failed = true;
+        }
+    }
+    /// 在`now`时刻重新做决定。定时器到期时，调用它。
+    pub fn poll(&mut self, now: Instant) -> Option<SuspendAction> {
+        let should_suspend = self.should_suspend(now);
+        (should_suspend != self.is_suspended).then(|| {
+            self.is_suspended = should_suspend;
+            if should_suspend { SuspendAction::Suspend } else { SuspendAction::Resume }
+        })
+    }
+    /// 下一次需要调用[`SuspendScheduler::poll()`]的时刻（若有）。仅`AfterHidden`策略需要定时器。
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match (self.policy, self.hidden_since) {
+            (SuspendPolicy::AfterHidden(delay), Some(hidden_since)) if !self.is_suspended && !self.has_failed => Some(hidden_since + delay),
+            _ => None
+        }
+    }
+    fn should_suspend(&self, now: Instant) -> bool {
+        let Some(hidden_since) = self.hidden_since.filter(|_| !self.has_failed) else {
+            return false;
+        };
+        match self.policy {
+            SuspendPolicy::Never => false,
+            SuspendPolicy::OnMinimize => true,
+            SuspendPolicy::AfterHidden(delay) => now.saturating_duration_since(hidden_since) >= delay,
+            SuspendPolicy::OnBattery => self.is_on_battery
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+    #[test]
+    fn never_suspends() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::Never);
+        assert_eq!(scheduler.on_visibility(false, start), None);
+        assert_eq!(scheduler.on_power(true, start + MINUTE), None);
+        assert_eq!(scheduler.poll(start + MINUTE * 60), None);
+        assert_eq!(scheduler.next_deadline(), None);
+    }
+    #[test]
+    fn on_minimize_suspends_at_once_and_resumes_once() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::OnMinimize);
+        assert_eq!(scheduler.on_visibility(false, start), Some(SuspendAction::Suspend));
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE), None, "同一决定不会被连续给出两次");
+        assert!(scheduler.is_suspended());
+        assert_eq!(scheduler.next_deadline(), None);
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE * 2), Some(SuspendAction::Resume));
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE * 3), None);
+    }
+    #[test]
+    fn after_hidden_waits_for_the_deadline() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::AfterHidden(MINUTE));
+        assert_eq!(scheduler.on_visibility(false, start), None);
+        assert_eq!(scheduler.next_deadline(), Some(start + MINUTE));
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE / 2), None);
+        assert_eq!(scheduler.next_deadline(), Some(start + MINUTE), "再次隐藏不重新计时");
+        assert_eq!(scheduler.poll(start + MINUTE - Duration::from_millis(1)), None);
+        assert_eq!(scheduler.poll(start + MINUTE), Some(SuspendAction::Suspend));
+        assert_eq!(scheduler.next_deadline(), None);
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE * 2), Some(SuspendAction::Resume));
+        assert_eq!(scheduler.next_deadline(), None);
+    }
+    #[test]
+    fn after_hidden_is_cancelled_by_showing() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::AfterHidden(MINUTE));
+        scheduler.on_visibility(false, start);
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE / 2), None);
+        assert_eq!(scheduler.poll(start + MINUTE), None);
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE * 2), None);
+        assert_eq!(scheduler.next_deadline(), Some(start + MINUTE * 3), "重新隐藏之后重新计时");
+    }
+    #[test]
+    fn on_battery_follows_the_power_status() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::OnBattery);
+        assert_eq!(scheduler.on_visibility(false, start), None);
+        assert_eq!(scheduler.on_power(true, start + MINUTE), Some(SuspendAction::Suspend));
+        assert_eq!(scheduler.on_power(false, start + MINUTE * 2), Some(SuspendAction::Resume), "接通电源之后，即便仍不可见，也恢复");
+        assert_eq!(scheduler.on_power(true, start + MINUTE * 3), Some(SuspendAction::Suspend));
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE * 4), Some(SuspendAction::Resume));
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE * 5), Some(SuspendAction::Suspend), "仍由电池供电");
+    }
+    #[test]
+    fn suspend_failures_are_not_retried_until_shown() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::AfterHidden(MINUTE));
+        scheduler.on_visibility(false, start);
+        assert_eq!(scheduler.poll(start + MINUTE), Some(SuspendAction::Suspend));
+        scheduler.on_suspend_failed();
+        assert!(!scheduler.is_suspended());
+        assert_eq!(scheduler.next_deadline(), None);
+        assert_eq!(scheduler.poll(start + MINUTE * 2), None);
+        assert_eq!(scheduler.on_visibility(true, start + MINUTE * 3), None, "没被挂起，也就不必恢复");
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE * 4), None);
+        assert_eq!(scheduler.next_deadline(), Some(start + MINUTE * 5));
+    }
+    #[test]
+    fn late_failures_while_visible_are_ignored() {
+        let start = Instant::now();
+        let mut scheduler = SuspendScheduler::new(SuspendPolicy::OnMinimize);
+        scheduler.on_visibility(false, start);
+        scheduler.on_visibility(true, start + MINUTE);
+        scheduler.on_suspend_failed(); // 挂起的异步结果晚于恢复到达
+        assert_eq!(scheduler.on_visibility(false, start + MINUTE * 2), Some(SuspendAction::Suspend));
+    }
+    #[test]
+    fn recognizes_power_status_changes() {
+        assert!(is_power_status_change(WM_POWERBROADCAST, PBT_APMPOWERSTATUSCHANGE));
+        assert!(!is_power_status_change(WM_POWERBROADCAST, 0x0012)); // PBT_APMRESUMEAUTOMATIC
+        assert!(!is_power_status_change(0x0005, PBT_APMPOWERSTATUSCHANGE));
+    }
+}
@@ -0,0 +1,208 @@
+//! 多`TAB`控件的簿记：`TAB`的顺序、标题、图标与当前激活项。
+use ::std::{fmt::{self, Display, Formatter}, mem};
+
+/// `TAB`的标识。它在同一个[`TabSet`]内唯一，且不会被复用。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TabId(u64);
+impl Display for TabId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "tab#{}", self.0)
+    }
+}
+/// 单个`TAB`的状态
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TabInfo {
+    /// 标识
+    pub id: TabId,
+    /// 网页标题
+    pub title: String,
+    /// 网页图标的地址
+    pub favicon: Option<String>
+}
+/// 激活项的切换
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TabSwitch {
+    /// 之前的激活项
+    pub from: Option<TabId>,
+    /// 之后的激活项
+    pub to: Option<TabId>
+}
+impl TabSwitch {
+    /// 激活项是否真的变了
+    pub fn is_changed(&self) -> bool {
+        self.from != self.to
+    }
+}
+/// 全部`TAB`的有序集合
+#[derive(Clone, Debug, Default)]
+pub struct TabSet {
+    tabs: Vec<TabInfo>,
+    active: Option<TabId>,
+    next_id: u64
+}
+impl TabSet {
+    /// 在末尾追加一个`TAB`。首个`TAB`总是会被激活。
+    pub fn open(&mut self, activate: bool) -> (TabId, TabSwitch) {
+        self.insert(self.tabs.len(), activate)
+    }
+    /// 在`index`处插入一个`TAB`。`index`超出范围时，追加于末尾。
+    pub fn insert(&mut self, index: usize, activate: bool) -> (TabId, TabSwitch) {
+        self.next_id += 1;
+        let id = TabId(self.next_id);
+        self.tabs.insert(index.min(self.tabs.len()), TabInfo { id, title: String::new(), favicon: None });
+        let switch = if activate || self.active.is_none() {
+            self.switch_to(Some(id))
+        } else {
+            self.switch_to(self.active)
+        };
+        (id, switch)
+    }
+    /// 关闭一个`TAB`。若它是激活项，则激活其右侧的`TAB`；若它是最后一个，则激活其左侧的`TAB`。
+    /// 返回`None`表示没有该`TAB`。
+    pub fn close(&mut self, id: TabId) -> Option<TabSwitch> {
+        let index = self.index_of(id)?;
+        self.tabs.remove(index);
+        if self.active != Some(id) {
+            return Some(self.switch_to(self.active));
+        }
+        let next = self.tabs.get(index).or_else(|| index.checked_sub(1).and_then(|index| self.tabs.get(index))).map(|tab| tab.id);
+        Some(self.switch_to(next))
+    }
+    /// 激活一个`TAB`。返回`None`表示没有该`TAB`。
+    pub fn activate(&mut self, id: TabId) -> Option<TabSwitch> {
+        self.index_of(id)?;
+        Some(self.switch_to(Some(id)))
+    }
+    /// 激活第`index`个`TAB`
+    pub fn activate_index(&mut self, index: usize) -> Option<TabSwitch> {
+        let id = self.tabs.get(index)?.id;
+        self.activate(id)
+    }
+    /// 将`TAB`挪至`index`处。`index`超出范围时，挪至末尾。
+    pub fn move_to(&mut self, id: TabId, index: usize) -> bool {
+        let Some(from) = self.index_of(id) else {
+            return false;
+        };
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(index.min(self.tabs.len()), tab);
+        true
+    }
+    /// 更新标题。返回值表示标题是否变了。
+    pub fn set_title(&mut self, id: TabId, title: impl Into<String>) -> bool {
+        let title = title.into();
+        self.tab_mut(id).filter(|tab| tab.title != title).map(|tab| tab.title = title).is_some()
+    }
+    /// 更新图标。返回值表示图标是否变了。
+    pub fn set_favicon(&mut self, id: TabId, favicon: Option<String>) -> bool {
+        self.tab_mut(id).filter(|tab| tab.favicon != favicon).map(|tab| tab.favicon = favicon).is_some()
+    }
+    /// 当前激活项
+    pub fn active(&self) -> Option<TabId> {
+        self.active
+    }
+    /// 当前激活项的下标
+    pub fn active_index(&self) -> Option<usize> {
+        self.active.and_then(|id| self.index_of(id))
+    }
+    /// `TAB`的下标
+    pub fn index_of(&self, id: TabId) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.id == id)
+    }
+    /// `TAB`的状态
+    pub fn get(&self, id: TabId) -> Option<&TabInfo> {
+        self.tabs.iter().find(|tab| tab.id == id)
+    }
+    /// 按顺序遍历全部`TAB`
+    pub fn iter(&self) -> impl Iterator<Item = &TabInfo> {
+        self.tabs.iter()
+    }
+    /// `TAB`的个数
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+    /// 是否没有`TAB`
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+    fn tab_mut(&mut self, id: TabId) -> Option<&mut TabInfo> {
+        self.tabs.iter_mut().find(|tab| tab.id == id)
+    }
+    fn switch_to(&mut self, to: Option<TabId>) -> TabSwitch {
+        TabSwitch { from: mem::replace(&mut self.active, to), to }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(tabs: &TabSet) -> Vec<TabId> {
+        tabs.iter().map(|tab| tab.id).collect()
+    }
+    #[test]
+    fn first_tab_is_always_activated() {
+        let mut tabs = TabSet::default();
+        let (first, switch) = tabs.open(false);
+        assert_eq!(switch, TabSwitch { from: None, to: Some(first) });
+        let (second, switch) = tabs.open(false);
+        assert!(!switch.is_changed());
+        assert_eq!(tabs.active(), Some(first));
+        let (third, switch) = tabs.open(true);
+        assert_eq!(switch, TabSwitch { from: Some(first), to: Some(third) });
+        assert_eq!(ids(&tabs), vec![first, second, third]);
+        assert_eq!(tabs.active_index(), Some(2));
+    }
+    #[test]
+    fn closing_the_active_tab_activates_its_neighbour() {
+        let mut tabs = TabSet::default();
+        let [a, b, c] = [tabs.open(false).0, tabs.open(false).0, tabs.open(false).0];
+        tabs.activate(b);
+        assert_eq!(tabs.close(b), Some(TabSwitch { from: Some(b), to: Some(c) })); // 右侧
+        assert_eq!(tabs.close(c), Some(TabSwitch { from: Some(c), to: Some(a) })); // 最后一个则左侧
+        assert_eq!(tabs.close(a), Some(TabSwitch { from: Some(a), to: None }));
+        assert!(tabs.is_empty());
+        assert_eq!(tabs.close(a), None);
+    }
+    #[test]
+    fn closing_an_inactive_tab_keeps_the_active_one() {
+        let mut tabs = TabSet::default();
+        let [a, b] = [tabs.open(false).0, tabs.open(false).0];
+        let switch = tabs.close(b).unwrap();
+        assert!(!switch.is_changed());
+        assert_eq!(tabs.active(), Some(a));
+    }
+    #[test]
+    fn ids_are_never_reused() {
+        let mut tabs = TabSet::default();
+        let (a, _) = tabs.open(true);
+        tabs.close(a);
+        let (b, _) = tabs.open(true);
+        assert_ne!(a, b);
+        assert_eq!(tabs.activate(a), None);
+    }
+    #[test]
+    fn inserts_moves_and_activates_by_index() {
+        let mut tabs = TabSet::default();
+        let [a, b] = [tabs.open(false).0, tabs.open(false).0];
+        let (c, _) = tabs.insert(0, false);
+        let (d, _) = tabs.insert(99, false);
+        assert_eq!(ids(&tabs), vec![c, a, b, d]);
+        assert!(tabs.move_to(c, 99));
+        assert!(tabs.move_to(d, 0));
+        assert_eq!(ids(&tabs), vec![d, a, b, c]);
+        assert_eq!(tabs.activate_index(3), Some(TabSwitch { from: Some(a), to: Some(c) }));
+        assert_eq!(tabs.activate_index(4), None);
+        assert_eq!(tabs.index_of(b), Some(2));
+    }
+    #[test]
+    fn reports_only_real_changes() {
+        let mut tabs = TabSet::default();
+        let (a, _) = tabs.open(true);
+        assert!(tabs.set_title(a, "标题"));
+        assert!(!tabs.set_title(a, "标题"));
+        assert!(tabs.set_favicon(a, Some(String::from("https://app.local/favicon.ico"))));
+        assert!(!tabs.set_favicon(a, Some(String::from("https://app.local/favicon.ico"))));
+        assert!(!tabs.set_title(TabId(99), "无"));
+        assert_eq!(tabs.get(a), Some(&TabInfo { id: a, title: String::from("标题"), favicon: Some(String::from("https://app.local/favicon.ico")) }));
+        assert_eq!(tabs.len(), 1);
+    }
+}
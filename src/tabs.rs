@@ -0,0 +1,322 @@
+use ::deferred_future::LocalDeferredFuture;
+use ::futures::FutureExt;
+use ::nwg::{self as nwg, ControlHandle, Event as NwgEvent, EventHandler, Frame, FrameBuilder, FrameFlags};
+use ::serde_json::Value;
+use ::std::{cell::RefCell, collections::HashMap, fmt::{self, Debug, Formatter}, rc::{Rc, Weak}};
+use super::{WebviewContainer, backend::{Webview2Backend, WebviewBackend}, builder::{WebviewContainerBuilder, WebviewContainerFlags}, config::EnvironmentConfig, env_pool::{EnvironmentFuture, EnvironmentResult},
+    error::WebviewContainerError, log::{debug, error}, tab_state::{TabId, TabInfo, TabSet, TabSwitch}};
+
+/// 网页图标经由该频道的网页消息上报
+const FAVICON_CHANNEL: &str = "nwg.favicon";
+/// 在`DOMContentLoaded`之后上报网页图标的地址。网页未声明图标时，采用站点根目录下的`favicon.ico`。
+const FAVICON_SCRIPT: &str = r#"(() => {
+    const webview = window.chrome && window.chrome.webview;
+    if (!webview) {
+        return;
+    }
+    const post = () => {
+        const link = document.querySelector('link[rel~="icon"]');
+        const href = link ? link.href : location.protocol.startsWith("http") ? new URL("/favicon.ico", location.href).href : null;
+        webview.postMessage({channel: "nwg.favicon", payload: href});
+    };
+    document.readyState === "loading" ? document.addEventListener("DOMContentLoaded", post) : post();
+})();"#;
+type TabObserver = Box<dyn FnMut(&TabInfo)>;
+/// 多`TAB`控件。全部`TAB`都共用同一个浏览器环境`webview2::Environment`（即，同一组浏览器进程与缓存目录），并被叠放于同一个`nwg::Frame`内。任一时刻仅有激活的`TAB`可见。
+/// ```ignore
+/// tabs.ready_fut()?.await?;
+/// let tab = tabs.open_tab(true)?;
+/// let WebviewHandles { webview, .. } = tabs.tab(tab).unwrap().ready_fut()?.await?;
+/// webview.navigate("https://www.minxing365.com")?;
+/// ```
+pub struct WebviewTabs<B: WebviewBackend = Webview2Backend> {
+    window: Option<ControlHandle>,
+    backend: B,
+    frame: Rc<RefCell<Frame>>,
+    environment: Rc<RefCell<Option<B::Environment>>>,
+    ready_fut: Option<EnvironmentFuture<B>>,
+    state: Rc<RefCell<TabSet>>,
+    tabs: Rc<RefCell<HashMap<TabId, Rc<WebviewContainer<B>>>>>,
+    observer: Rc<RefCell<Option<TabObserver>>>,
+    event_handle: Option<EventHandler>
+}
+impl<B: WebviewBackend + Default> Default for WebviewTabs<B> {
+    fn default() -> Self {
+        Self {
+            window: None,
+            backend: B::default(),
+            frame: Rc::default(),
+            environment: Rc::default(),
+            ready_fut: None,
+            state: Rc::default(),
+            tabs: Rc::default(),
+            observer: Rc::default(),
+            event_handle: None
+        }
+    }
+}
+impl<B: WebviewBackend> Debug for WebviewTabs<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewTabs")
+            .field("frame", &self.frame.borrow().handle)
+            .field("state", &self.state.borrow())
+            .finish_non_exhaustive()
+    }
+}
+impl<B: WebviewBackend> From<&WebviewTabs<B>> for ControlHandle {
+    fn from(value: &WebviewTabs<B>) -> Self {
+        value.frame.borrow().handle
+    }
+}
+impl<B: WebviewBackend> Drop for WebviewTabs<B> {
+    fn drop(&mut self) {
+        debug!("[WebviewTabs][drop]");
+        self.event_handle.as_ref().map(nwg::unbind_event_handler);
+        self.tabs.borrow_mut().clear();
+        self.frame.borrow_mut().handle.destroy();
+    }
+}
+impl<B: WebviewBackend + Clone + Default> WebviewTabs<B> {
+    /// 多`TAB`控件的构造器
+    pub fn builder() -> WebviewTabsBuilder<B> {
+        WebviewTabsBuilder::default()
+    }
+    /// 共享浏览器环境的异步锁。在它被决议之前，还不能打开`TAB`。
+    pub fn ready_fut(&self) -> Result<EnvironmentFuture<B>, WebviewContainerError> {
+        self.ready_fut.clone().ok_or(WebviewContainerError::NotReady)
+    }
+    /// 打开一个新`TAB`。`activate`表示是否立即切换至该`TAB`。首个`TAB`总是会被激活。
+    pub fn open_tab(&self, activate: bool) -> Result<TabId, WebviewContainerError> {
+        let window = self.window.ok_or(WebviewContainerError::MissingWindow)?;
+        let environment = self.environment.borrow().clone().ok_or(WebviewContainerError::NotReady)?;
+        let (id, switch) = self.state.borrow_mut().open(activate);
+        let frame = self.frame.borrow();
+        let (width, height) = frame.size();
+        let mut container = WebviewContainer::with_backend(self.backend.clone());
+        let state = Rc::downgrade(&self.state);
+        let observer = Rc::downgrade(&self.observer);
+        let result = WebviewContainerBuilder::default()
+            .backend(self.backend.clone())
+            .window(window)
+            .parent(frame.handle)
+            .position((0, 0))
+            .size((width as i32, height as i32))
+            .flags(if switch.to == Some(id) { WebviewContainerFlags::VISIBLE } else { WebviewContainerFlags::NONE })
+            .webview_env(environment)
+            .on_created(Box::new(move |backend, handles| {
+                backend.add_document_title_changed(&handles.webview, Box::new(move |title| {
+                    update_tab(&state, &observer, id, |state| state.set_title(id, title));
+                })).map_err(WebviewContainerError::EventSubscription)?;
+                backend.add_script_to_execute_on_document_created(&handles.webview, FAVICON_SCRIPT, Box::new(|result| {
+                    if let Err(err) = result {
+                        error!("[WebviewTabs][favicon]{err}");
+                    }
+                })).map_err(WebviewContainerError::ScriptInjection)
+            }))
+            .build(&mut container);
+        drop(frame);
+        if let Err(err) = result {
+            self.state.borrow_mut().close(id);
+            return Err(err);
+        }
+        let (state, observer) = (Rc::downgrade(&self.state), Rc::downgrade(&self.observer));
+        container.router.set_handler(String::from(FAVICON_CHANNEL), Box::new(move |payload| {
+            let favicon = match payload {
+                Value::String(favicon) => Some(favicon),
+                _ => None
+            };
+            update_tab(&state, &observer, id, |state| state.set_favicon(id, favicon));
+        }));
+        self.tabs.borrow_mut().insert(id, Rc::new(container));
+        debug!("[WebviewTabs][open_tab]{id}");
+        self.apply(switch);
+        Ok(id)
+    }
+    /// 关闭一个`TAB`，并析构它的`webview`控件（若调用方还持有`tab()`返回的`Rc`，则推迟至其被释放时）。返回值表示是否有该`TAB`。
+    pub fn close_tab(&self, id: TabId) -> bool {
+        let Some(switch) = self.state.borrow_mut().close(id) else {
+            return false;
+        };
+        let container = self.tabs.borrow_mut().remove(&id);
+        drop(container);
+        debug!("[WebviewTabs][close_tab]{id}");
+        self.apply(switch);
+        true
+    }
+    /// 切换至一个`TAB`。返回值表示是否有该`TAB`。
+    pub fn activate_tab(&self, id: TabId) -> bool {
+        let switch = self.state.borrow_mut().activate(id);
+        switch.map(|switch| self.apply(switch)).is_some()
+    }
+    /// 将`TAB`挪至`index`处
+    pub fn move_tab(&self, id: TabId, index: usize) -> bool {
+        self.state.borrow_mut().move_to(id, index)
+    }
+    /// 当前激活的`TAB`
+    pub fn active_tab(&self) -> Option<TabId> {
+        self.state.borrow().active()
+    }
+    /// 当前激活的`TAB`的下标
+    pub fn active_index(&self) -> Option<usize> {
+        self.state.borrow().active_index()
+    }
+    /// 按顺序排列的全部`TAB`的状态（标题与图标）
+    pub fn tab_infos(&self) -> Vec<TabInfo> {
+        self.state.borrow().iter().cloned().collect()
+    }
+    /// 单个`TAB`的状态
+    pub fn tab_info(&self, id: TabId) -> Option<TabInfo> {
+        self.state.borrow().get(id).cloned()
+    }
+    /// `TAB`的个数
+    pub fn len(&self) -> usize {
+        self.state.borrow().len()
+    }
+    /// 是否没有`TAB`
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().is_empty()
+    }
+    /// `TAB`内的`WebviewContainer`。返回`Rc`而不是借用，所以它可以跨`.await`持有，且不会阻塞`TAB`的打开与关闭。
+    pub fn tab(&self, id: TabId) -> Option<Rc<WebviewContainer<B>>> {
+        self.tabs.borrow().get(&id).cloned()
+    }
+    /// 监听`TAB`标题与图标的变化。比如，借此重绘`TAB`标签栏。
+    pub fn on_tab_updated<F: FnMut(&TabInfo) + 'static>(&self, observer: F) {
+        self.observer.borrow_mut().replace(Box::new(observer));
+    }
+    /// 隐藏之前的激活项，和显示之后的激活项
+    fn apply(&self, switch: TabSwitch) {
+        if !switch.is_changed() {
+            return;
+        }
+        let tabs = self.tabs.borrow();
+        let (from, to) = (switch.from.and_then(|id| tabs.get(&id)), switch.to.and_then(|id| tabs.get(&id)));
+        for (container, is_visible) in from.map(|container| (container, false)).into_iter().chain(to.map(|container| (container, true))) {
+            if let Err(err) = container.set_visible(is_visible) {
+                error!("[WebviewTabs][activate_tab]{err}");
+            }
+        }
+    }
+}
+/// 更新`TAB`的簿记，并在它真的变了时通知监听者
+fn update_tab<F>(state: &Weak<RefCell<TabSet>>, observer: &Weak<RefCell<Option<TabObserver>>>, id: TabId, update: F)
+where F: FnOnce(&mut TabSet) -> bool {
+    let Some(state) = state.upgrade() else {
+        return;
+    };
+    if !update(&mut state.borrow_mut()) {
+        return;
+    }
+    let tab = state.borrow().get(id).cloned();
+    if let (Some(tab), Some(observer)) = (tab, observer.upgrade()) {
+        if let Some(observer) = observer.borrow_mut().as_mut() {
+            observer(&tab);
+        }
+    }
+}
+/// [`WebviewTabs`]的构造器。`nwg::Frame`的配置项与`WebviewContainerBuilder`相同；全部`TAB`都由同一份`webview2::EnvironmentBuilder`配置项构造。
+pub struct WebviewTabsBuilder<B: WebviewBackend = Webview2Backend> {
+    window: Option<ControlHandle>,
+    backend: B,
+    webview_env: Option<B::Environment>,
+    environment: EnvironmentConfig,
+    frame_builder: FrameBuilder
+}
+impl<B: WebviewBackend> Debug for WebviewTabsBuilder<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebviewTabsBuilder")
+            .field("window", &self.window)
+            .field("has_webview_env", &self.webview_env.is_some())
+            .field("environment", &self.environment)
+            .finish_non_exhaustive()
+    }
+}
+impl<B: WebviewBackend + Default> Default for WebviewTabsBuilder<B> {
+    fn default() -> Self {
+        Self {
+            window: None,
+            backend: B::default(),
+            webview_env: None,
+            environment: EnvironmentConfig::default(),
+            frame_builder: Frame::builder().flags(FrameFlags::VISIBLE)
+        }
+    }
+}
+impl<B: WebviewBackend + Clone + Default> WebviewTabsBuilder<B> {
+    /// nwg::FrameBuilder 的配置项
+    pub fn size(mut self, size: (i32, i32)) -> Self {
+        self.frame_builder = self.frame_builder.size(size);
+        self
+    }
+    /// nwg::FrameBuilder 的配置项
+    pub fn position(mut self, pos: (i32, i32)) -> Self {
+        self.frame_builder = self.frame_builder.position(pos);
+        self
+    }
+    /// nwg::FrameBuilder 的配置项
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> Self {
+        self.frame_builder = self.frame_builder.parent(p);
+        self
+    }
+    /// 主窗体。它是必填项
+    pub fn window<C: Into<ControlHandle>>(mut self, window: C) -> Self {
+        self.window = Some(window.into());
+        self
+    }
+    /// 复用既有的浏览器环境。否则，由`environment`构造新的浏览器环境。
+    pub fn webview_env<E: Into<B::Environment>>(mut self, webview_env: E) -> Self {
+        self.webview_env = Some(webview_env.into());
+        self
+    }
+    /// webview2::EnvironmentBuilder 的全部配置项
+    pub fn environment(mut self, environment: EnvironmentConfig) -> Self {
+        self.environment = environment;
+        self
+    }
+    /// 替换后端实例。每个`TAB`都使用它的克隆。
+    pub fn backend(mut self, backend: B) -> Self {
+        self.backend = backend;
+        self
+    }
+    /// 构造`nwg::Frame`，并异步地构造共享的浏览器环境
+    pub fn build(self, webview_tabs: &mut WebviewTabs<B>) -> Result<(), WebviewContainerError> {
+        let window_handle = self.window.ok_or(WebviewContainerError::MissingWindow)?;
+        window_handle.hwnd().ok_or(WebviewContainerError::InvalidWindowHandle)?;
+        self.frame_builder.build(&mut webview_tabs.frame.borrow_mut()).map_err(|err| WebviewContainerError::FrameBuild(err.to_string()))?;
+        let frame_hwnd = webview_tabs.frame.borrow().handle.hwnd().ok_or(WebviewContainerError::FrameBuild(String::from("Frame 不是有效的 Win32 窗体")))?;
+        webview_tabs.window = Some(window_handle);
+        webview_tabs.backend = self.backend;
+        // 共享浏览器环境的异步锁
+        let environment_ready_future = LocalDeferredFuture::<EnvironmentResult<B>>::default();
+        let defer = environment_ready_future.defer();
+        if let Some(environment) = self.webview_env {
+            webview_tabs.environment.borrow_mut().replace(environment.clone());
+            defer.borrow_mut().complete(Ok(environment));
+        } else {
+            let environment = Rc::downgrade(&webview_tabs.environment);
+            webview_tabs.backend.create_environment(&self.environment.options(), Box::new(move |result| {
+                let result = result.map_err(WebviewContainerError::EnvironmentCreation);
+                if let (Ok(env), Some(environment)) = (&result, environment.upgrade()) {
+                    environment.borrow_mut().replace(env.clone());
+                }
+                defer.borrow_mut().complete(result);
+            })).map_err(WebviewContainerError::EnvironmentCreation)?;
+        }
+        webview_tabs.ready_fut.replace(environment_ready_future.shared());
+        webview_tabs.event_handle.replace({ // 令每个 TAB 的 Frame 都铺满 WebviewTabs 的 Frame
+            let frame = Rc::downgrade(&webview_tabs.frame);
+            let tabs = Rc::downgrade(&webview_tabs.tabs);
+            nwg::full_bind_event_handler(&window_handle, move |event, _data, handle| {
+                if !matches!((event, handle), (NwgEvent::OnResize, ControlHandle::Hwnd(hwnd)) if hwnd == frame_hwnd) {
+                    return;
+                }
+                if let (Some(frame), Some(tabs)) = (frame.upgrade(), tabs.upgrade()) {
+                    let (width, height) = frame.borrow().size();
+                    tabs.borrow().values().for_each(|container| container.frame.borrow().set_size(width, height));
+                }
+            })
+        });
+        debug!("[WebviewTabsBuilder][build]同步执行结束");
+        Ok(())
+    }
+}
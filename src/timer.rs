@@ -0,0 +1,73 @@
+//! 基于`Win32 SetTimer`的单次定时器。定时回调经由当前线程的消息循环被派发，所以它仅适用于`UI`线程。
+use ::std::{cell::RefCell, collections::HashMap, future::Future, pin::Pin, ptr, rc::{Rc, Weak}, task::{Context, Poll, Waker}, time::Duration};
+use ::winapi::{shared::{basetsd::UINT_PTR, minwindef::{DWORD, UINT}, windef::HWND}, um::winuser::{KillTimer, SetTimer}};
+
+#[derive(Default)]
+struct TimerState {
+    is_fired: bool,
+    waker: Option<Waker>,
+    callback: Option<Box<dyn FnOnce()>>
+}
+thread_local! {
+    static TIMERS: RefCell<HashMap<UINT_PTR, Weak<RefCell<TimerState>>>> = RefCell::default();
+}
+unsafe extern "system" fn on_timer(_hwnd: HWND, _msg: UINT, id: UINT_PTR, _time: DWORD) {
+    unsafe { KillTimer(ptr::null_mut(), id) };
+    if let Some(state) = TIMERS.with(|timers| timers.borrow_mut().remove(&id)).and_then(|state| state.upgrade()) {
+        let (waker, callback) = {
+            let mut state = state.borrow_mut();
+            state.is_fired = true;
+            (state.waker.take(), state.callback.take())
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        if let Some(callback) = callback { // 回调函数可能会析构（甚至重新预约）该定时器，所以先释放借用。
+            callback();
+        }
+    }
+}
+/// 在`duration`之后被决议的`Future`。它在被析构时会注销还未触发的定时器。
+pub(crate) struct Delay {
+    id: UINT_PTR,
+    state: Rc<RefCell<TimerState>>
+}
+impl Future for Delay {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.borrow_mut();
+        if state.is_fired {
+            return Poll::Ready(());
+        }
+        state.waker.replace(cx.waker().clone());
+        Poll::Pending
+    }
+}
+impl Drop for Delay {
+    fn drop(&mut self) {
+        if !self.state.borrow().is_fired {
+            unsafe { KillTimer(ptr::null_mut(), self.id) };
+            TIMERS.with(|timers| timers.borrow_mut().remove(&self.id));
+        }
+    }
+}
+/// 构造单次定时器。若`Win32`定时器资源耗尽，则它会被立即决议。
+pub(crate) fn delay(duration: Duration) -> Delay {
+    let state = Rc::new(RefCell::new(TimerState::default()));
+    let millis = UINT::try_from(duration.as_millis()).unwrap_or(UINT::MAX);
+    let id = unsafe { SetTimer(ptr::null_mut(), 0, millis, Some(on_timer)) };
+    if id == 0 {
+        state.borrow_mut().is_fired = true;
+    } else {
+        TIMERS.with(|timers| timers.borrow_mut().insert(id, Rc::downgrade(&state)));
+    }
+    Delay { id, state }
+}
+/// 预约在`duration`之后执行`callback`。它无需被`poll`，而析构返回的`Delay`就会取消预约。若`Win32`定时器资源耗尽，则`callback`不会被执行。
+pub(crate) fn schedule(duration: Duration, callback: impl FnOnce() + 'static) -> Delay {
+    let delay = delay(duration);
+    if !delay.state.borrow().is_fired {
+        delay.state.borrow_mut().callback.replace(Box::new(callback));
+    }
+    delay
+}
@@ -0,0 +1,206 @@
+//! 主窗体的状态机：由`WM_SIZE`、`WM_SHOWWINDOW`与`WM_SYSCOMMAND`消息驱动，决定`webview`控件何时被隐藏与恢复。
+//!
+//! 无论主窗体是经由【还原】菜单、任务栏按钮、`ShowWindow()`，还是由最小化直接最大化而被恢复的，`webview`控件都会被恢复显示。
+//! 状态迁移仅在【可见性】变化时才产生宿主事件，所以`put_is_visible`的调用是确定的：每次隐藏与恢复各一次。
+use super::host_event::HostEvent;
+
+const WM_SIZE: u32 = 0x0005;
+const WM_SHOWWINDOW: u32 = 0x0018;
+const WM_SYSCOMMAND: u32 = 0x0112;
+const SIZE_RESTORED: usize = 0;
+const SIZE_MINIMIZED: usize = 1;
+const SIZE_MAXIMIZED: usize = 2;
+const SC_MINIMIZE: usize = 0xF020;
+const SC_MAXIMIZE: usize = 0xF030;
+const SC_RESTORE: usize = 0xF120;
+/// 主窗体的状态
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    /// 普通（即，非最小化且非最大化）
+    #[default]
+    Normal,
+    /// 最小化
+    Minimized,
+    /// 最大化
+    Maximized,
+    /// 被隐藏了
+    Hidden
+}
+impl WindowState {
+    /// 在该状态下，`webview`控件是否应该可见
+    pub fn is_visible(&self) -> bool {
+        matches!(self, Self::Normal | Self::Maximized)
+    }
+}
+/// 驱动状态机的主窗体消息
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMessage {
+    /// `WM_SIZE`。携带了新的尺寸类型：`Normal`、`Minimized`或`Maximized`
+    Size(WindowState),
+    /// `WM_SHOWWINDOW`。携带了主窗体是否被显示
+    ShowWindow(bool),
+    /// `WM_SYSCOMMAND`的`SC_MINIMIZE`、`SC_MAXIMIZE`与`SC_RESTORE`。分别对应于`Minimized`、`Maximized`与`Normal`
+    SysCommand(WindowState)
+}
+impl WindowMessage {
+    /// 翻译原始的`Win32`消息。与状态机无关的消息被翻译为`None`。
+    pub fn from_raw(msg: u32, wparam: usize) -> Option<Self> {
+        match msg {
+            WM_SIZE => match wparam {
+                SIZE_RESTORED => Some(Self::Size(WindowState::Normal)),
+                SIZE_MINIMIZED => Some(Self::Size(WindowState::Minimized)),
+                SIZE_MAXIMIZED => Some(Self::Size(WindowState::Maximized)),
+                _ => None // SIZE_MAXSHOW 与 SIZE_MAXHIDE 描述的是其它窗体
+            },
+            WM_SHOWWINDOW => Some(Self::ShowWindow(wparam != 0)),
+            WM_SYSCOMMAND => match wparam & 0xFFF0 { // 低四位被系统内部使用
+                SC_MINIMIZE => Some(Self::SysCommand(WindowState::Minimized)),
+                SC_MAXIMIZE => Some(Self::SysCommand(WindowState::Maximized)),
+                SC_RESTORE => Some(Self::SysCommand(WindowState::Normal)),
+                _ => None
+            },
+            _ => None
+        }
+    }
+}
+/// 一次状态迁移
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowTransition {
+    /// 迁移之前的状态
+    pub from: WindowState,
+    /// 迁移之后的状态
+    pub to: WindowState
+}
+impl WindowTransition {
+    /// `webview`控件的可见性变化（若有）
+    pub fn visibility(&self) -> Option<bool> {
+        (self.from.is_visible() != self.to.is_visible()).then_some(self.to.is_visible())
+    }
+    /// 需要被转发给后端的宿主事件（若有）。仅可见性变化才产生宿主事件。
+    pub fn host_event(&self) -> Option<HostEvent> {
+        self.visibility().map(|is_visible| match (is_visible, self.to) {
+            (true, _) => HostEvent::WindowRestored,
+            (false, WindowState::Minimized) => HostEvent::WindowMinimized,
+            (false, _) => HostEvent::WindowHidden
+        })
+    }
+}
+/// 主窗体的状态机
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowStateMachine {
+    state: WindowState,
+    /// 最近一次的可见状态。从最小化还原时，回到该状态。
+    last_shown: WindowState,
+    /// 被隐藏之前的状态。重新显示时，回到该状态。
+    before_hidden: WindowState
+}
+impl WindowStateMachine {
+    /// 以`initial`为初始状态。若`initial`是`Hidden`，则重新显示时回到`Normal`。
+    pub fn new(initial: WindowState) -> Self {
+        let last_shown = if initial.is_visible() { initial } else { WindowState::Normal };
+        let before_hidden = if initial == WindowState::Hidden { last_shown } else { initial };
+        Self { state: initial, last_shown, before_hidden }
+    }
+    /// 当前状态
+    pub fn state(&self) -> WindowState {
+        self.state
+    }
+    /// 由`message`驱动一次状态迁移。状态不变时，返回`None`。
+    pub fn apply(&mut self, message: WindowMessage) -> Option<WindowTransition> {
+        let to = match (self.state, message) {
+            (WindowState::Hidden, WindowMessage::ShowWindow(true)) => self.before_hidden,
+            (WindowState::Hidden, WindowMessage::Size(state)) => { // 隐藏期间的尺寸变化仅决定重新显示时的状态
+                self.before_hidden = state;
+                self.remember_shown(state);
+                WindowState::Hidden
+            },
+            (WindowState::Hidden, WindowMessage::SysCommand(_) | WindowMessage::ShowWindow(false)) | (_, WindowMessage::ShowWindow(true)) => self.state,
+            (from, WindowMessage::ShowWindow(false)) => {
+                self.before_hidden = from;
+                WindowState::Hidden
+            },
+            (WindowState::Minimized, WindowMessage::SysCommand(WindowState::Normal)) => self.last_shown,
+            (_, WindowMessage::Size(state) | WindowMessage::SysCommand(state)) => state
+        };
+        self.remember_shown(to);
+        let transition = WindowTransition { from: self.state, to };
+        self.state = to;
+        (transition.from != transition.to).then_some(transition)
+    }
+    fn remember_shown(&mut self, state: WindowState) {
+        if state.is_visible() {
+            self.last_shown = state;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WindowMessage::{ShowWindow, Size, SysCommand};
+    use WindowState::{Hidden, Maximized, Minimized, Normal};
+
+    /// 一步：消息，之后的状态，与可见性变化
+    type Step = (WindowMessage, WindowState, Option<bool>);
+    /// 自`initial`起逐一施加消息，并比对每一步之后的状态与可见性变化
+    fn run(initial: WindowState, steps: &[Step]) {
+        let mut machine = WindowStateMachine::new(initial);
+        for (index, (message, state, visibility)) in steps.iter().enumerate() {
+            let transition = machine.apply(*message);
+            assert_eq!((machine.state(), transition.and_then(|transition| transition.visibility())), (*state, *visibility), "第 {index} 步：{message:?}");
+        }
+    }
+    #[test]
+    fn restores_from_minimized_to_the_last_shown_state() {
+        let table: &[(WindowState, &[Step])] = &[
+            (Maximized, &[(SysCommand(Minimized), Minimized, Some(false)), (SysCommand(Normal), Maximized, Some(true))]),
+            (Normal, &[(Size(Maximized), Maximized, None), (Size(Minimized), Minimized, Some(false)), (SysCommand(Normal), Maximized, Some(true))]),
+            (Maximized, &[(Size(Minimized), Minimized, Some(false)), (Size(Normal), Normal, Some(true))]), // 任务栏按钮之后的 WM_SIZE 以实际状态为准
+            (Minimized, &[(SysCommand(Normal), Normal, Some(true))]),
+            (Normal, &[(Size(Minimized), Minimized, Some(false)), (SysCommand(Maximized), Maximized, Some(true))])
+        ];
+        for (initial, steps) in table {
+            run(*initial, steps);
+        }
+    }
+    #[test]
+    fn hidden_while_minimized() {
+        let table: &[(WindowState, &[Step])] = &[
+            (Minimized, &[(ShowWindow(false), Hidden, None), (ShowWindow(true), Minimized, None), (SysCommand(Normal), Normal, Some(true))]),
+            (Maximized, &[(Size(Minimized), Minimized, Some(false)), (ShowWindow(false), Hidden, None), (SysCommand(Normal), Hidden, None), (ShowWindow(true), Minimized, None), (SysCommand(Normal), Maximized, Some(true))]),
+            (Minimized, &[(ShowWindow(false), Hidden, None), (Size(Maximized), Hidden, None), (ShowWindow(true), Maximized, Some(true))]),
+            (Normal, &[(ShowWindow(false), Hidden, Some(false)), (ShowWindow(false), Hidden, None), (ShowWindow(true), Normal, Some(true))]),
+            (Hidden, &[(ShowWindow(true), Normal, Some(true))])
+        ];
+        for (initial, steps) in table {
+            run(*initial, steps);
+        }
+    }
+    #[test]
+    fn translates_raw_messages() {
+        let table = [
+            (WM_SIZE, SIZE_RESTORED, Some(Size(Normal))),
+            (WM_SIZE, SIZE_MINIMIZED, Some(Size(Minimized))),
+            (WM_SIZE, SIZE_MAXIMIZED, Some(Size(Maximized))),
+            (WM_SIZE, 3, None), // SIZE_MAXSHOW
+            (WM_SHOWWINDOW, 0, Some(ShowWindow(false))),
+            (WM_SHOWWINDOW, 1, Some(ShowWindow(true))),
+            (WM_SYSCOMMAND, SC_MINIMIZE, Some(SysCommand(Minimized))),
+            (WM_SYSCOMMAND, SC_MAXIMIZE | 0x2, Some(SysCommand(Maximized))), // 双击标题栏的 SC_MAXIMIZE 带有低位
+            (WM_SYSCOMMAND, SC_RESTORE | 0xF, Some(SysCommand(Normal))),
+            (WM_SYSCOMMAND, 0xF060, None), // SC_CLOSE
+            (0x0001, 0, None) // WM_CREATE
+        ];
+        for (msg, wparam, message) in table {
+            assert_eq!(WindowMessage::from_raw(msg, wparam), message, "{msg:#06x}, {wparam:#06x}");
+        }
+    }
+    #[test]
+    fn host_events_follow_the_visibility() {
+        let event = |from, to| WindowTransition { from, to }.host_event();
+        assert_eq!(event(Normal, Minimized), Some(HostEvent::WindowMinimized));
+        assert_eq!(event(Maximized, Hidden), Some(HostEvent::WindowHidden));
+        assert_eq!(event(Minimized, Maximized), Some(HostEvent::WindowRestored));
+        assert_eq!(event(Normal, Maximized), None);
+        assert_eq!(event(Minimized, Hidden), None);
+    }
+}